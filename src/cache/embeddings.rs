@@ -0,0 +1,261 @@
+//! Cache of bi-encoder query embeddings.
+//!
+//! Agents repeat the same queries, and encoding one is a full bi-encoder
+//! forward pass. [`QueryEmbeddingCache`] keeps the embeddings of recent
+//! queries, keyed by the effective query text (agent context included).
+//! Every entry belongs to the model identified by the cache's fingerprint, so
+//! a snapshot written by a different model is never loaded.
+//!
+//! Snapshot file format:
+//! - 8 bytes: magic "ENCAPQRY"
+//! - 4 bytes: version (u32 LE)
+//! - 32 bytes: model fingerprint
+//! - 8 bytes: entry count (u64 LE)
+//! - 8 bytes: embedding_dim (u64 LE)
+//! - per entry, least recently used first: query length (u32 LE), UTF-8
+//!   query, embedding_dim f32 values (LE)
+
+use crate::cache::LruCache;
+use crate::error::{AppError, Result};
+use ndarray::Array1;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+/// Snapshot format version. Increment when format changes.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Magic bytes to identify snapshot files.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"ENCAPQRY";
+
+/// Bounded LRU cache of query embeddings for one bi-encoder model.
+#[derive(Debug)]
+pub struct QueryEmbeddingCache {
+    /// Fingerprint of the model the embeddings were computed with
+    fingerprint: [u8; 32],
+    entries: Mutex<LruCache<String, Array1<f32>>>,
+}
+
+impl QueryEmbeddingCache {
+    /// An empty cache of at most `capacity` queries (0 disables caching).
+    pub fn new(capacity: usize, fingerprint: [u8; 32]) -> Self {
+        Self {
+            fingerprint,
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().capacity() > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// The cached embedding of `query`, counting the hit or miss.
+    pub fn get(&self, query: &str) -> Option<Array1<f32>> {
+        let mut entries = self.lock();
+        if entries.capacity() == 0 {
+            return None;
+        }
+        let embedding = entries.get(query).cloned();
+        if embedding.is_some() {
+            metrics::counter!("query_embedding_cache_hits_total").increment(1);
+        } else {
+            metrics::counter!("query_embedding_cache_misses_total").increment(1);
+        }
+        embedding
+    }
+
+    pub fn insert(&self, query: String, embedding: Array1<f32>) {
+        let mut entries = self.lock();
+        entries.insert(query, embedding);
+        metrics::gauge!("query_embedding_cache_entries").set(entries.len() as f64);
+    }
+
+    /// Write every entry to `path`, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                AppError::ValidationError(format!("Failed to create cache directory: {}", e))
+            })?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a torn snapshot
+        let tmp = path.with_extension("tmp");
+        let count = {
+            let entries = self.lock();
+            let file = File::create(&tmp).map_err(|e| {
+                AppError::ValidationError(format!("Failed to create query cache file: {}", e))
+            })?;
+            let mut writer = BufWriter::new(file);
+            self.write_snapshot(&mut writer, &entries)
+                .and_then(|_| writer.flush())
+                .map_err(|e| {
+                    AppError::ValidationError(format!("Failed to write query cache: {}", e))
+                })?;
+            entries.len()
+        };
+        fs::rename(&tmp, path).map_err(|e| {
+            AppError::ValidationError(format!("Failed to replace query cache file: {}", e))
+        })?;
+
+        tracing::info!(path = %path.display(), entries = count, "Query embedding cache saved");
+        Ok(())
+    }
+
+    /// Add the entries of the snapshot at `path`, returning how many were loaded.
+    ///
+    /// A missing file, or one written for another model, loads nothing.
+    pub fn load(&self, path: &Path) -> Result<usize> {
+        if !path.exists() {
+            tracing::debug!(path = %path.display(), "Query cache file does not exist");
+            return Ok(0);
+        }
+        if !self.is_enabled() {
+            return Ok(0);
+        }
+
+        let file = File::open(path).map_err(|e| {
+            AppError::ValidationError(format!("Failed to open query cache file: {}", e))
+        })?;
+        let loaded = self
+            .read_snapshot(&mut BufReader::new(file))
+            .map_err(|e| AppError::ValidationError(format!("Failed to read query cache: {}", e)))?;
+
+        match loaded {
+            Some(count) => {
+                tracing::info!(
+                    path = %path.display(),
+                    entries = count,
+                    "Query embedding cache loaded"
+                );
+                Ok(count)
+            }
+            None => {
+                tracing::warn!(
+                    path = %path.display(),
+                    "Query cache was written by another model or format, ignoring"
+                );
+                Ok(0)
+            }
+        }
+    }
+
+    fn write_snapshot(
+        &self,
+        writer: &mut impl Write,
+        entries: &LruCache<String, Array1<f32>>,
+    ) -> io::Result<()> {
+        let dim = entries.iter().next().map_or(0, |(_, e)| e.len());
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        writer.write_all(&self.fingerprint)?;
+        writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+        writer.write_all(&(dim as u64).to_le_bytes())?;
+        for (query, embedding) in entries.iter() {
+            writer.write_all(&(query.len() as u32).to_le_bytes())?;
+            writer.write_all(query.as_bytes())?;
+            for value in embedding {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Entries loaded, or None if the header does not match this cache.
+    fn read_snapshot(&self, reader: &mut impl Read) -> io::Result<Option<usize>> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        let version = read_u32(reader)?;
+        let mut fingerprint = [0u8; 32];
+        reader.read_exact(&mut fingerprint)?;
+        if &magic != SNAPSHOT_MAGIC
+            || version != SNAPSHOT_VERSION
+            || fingerprint != self.fingerprint
+        {
+            return Ok(None);
+        }
+
+        let count = read_u64(reader)? as usize;
+        let dim = read_u64(reader)? as usize;
+        let mut snapshot = Vec::with_capacity(count.min(self.lock().capacity()));
+        for _ in 0..count {
+            let mut query = vec![0u8; read_u32(reader)? as usize];
+            reader.read_exact(&mut query)?;
+            let query = String::from_utf8(query)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let mut bytes = vec![0u8; dim * 4];
+            reader.read_exact(&mut bytes)?;
+            let embedding: Array1<f32> = bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            snapshot.push((query, embedding));
+        }
+
+        // Oldest first, so recency order survives the round trip
+        let mut entries = self.lock();
+        for (query, embedding) in snapshot {
+            entries.insert(query, embedding);
+        }
+        Ok(Some(count.min(entries.capacity())))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<String, Array1<f32>>> {
+        // A panic while holding the lock cannot leave the LRU inconsistent
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_snapshot_round_trip_checks_the_model() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("queries.bin");
+
+        let cache = QueryEmbeddingCache::new(4, [1; 32]);
+        cache.insert("send a message".to_string(), array![0.5, -1.0, 2.0]);
+        cache.insert("list buckets".to_string(), array![1.0, 0.0, 0.25]);
+        cache.save(&path).unwrap();
+
+        let restored = QueryEmbeddingCache::new(4, [1; 32]);
+        assert_eq!(restored.load(&path).unwrap(), 2);
+        assert_eq!(restored.get("list buckets"), Some(array![1.0, 0.0, 0.25]));
+        assert_eq!(restored.get("send a message"), Some(array![0.5, -1.0, 2.0]));
+        assert_eq!(restored.get("unknown"), None);
+
+        // Embeddings from another model are never reused
+        let other_model = QueryEmbeddingCache::new(4, [2; 32]);
+        assert_eq!(other_model.load(&path).unwrap(), 0);
+        assert!(other_model.is_empty());
+
+        // Loading into a smaller cache keeps the most recent entries
+        let small = QueryEmbeddingCache::new(1, [1; 32]);
+        assert_eq!(small.load(&path).unwrap(), 1);
+        assert!(small.get("list buckets").is_some());
+    }
+}
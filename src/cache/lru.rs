@@ -0,0 +1,145 @@
+//! Bounded least-recently-used map.
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// A map holding at most `capacity` entries, evicting the least recently used.
+///
+/// Recency is a monotonic tick per access; `order` maps ticks back to keys, so
+/// lookups, inserts and evictions are `O(log n)`. Not synchronized: callers
+/// wrap it in a `Mutex`.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// An empty cache; a capacity of 0 never stores anything.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The value for `key`, marking it most recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tick = self.next_tick();
+        let (value, last_used) = self.entries.get_mut(key)?;
+        let key = self
+            .order
+            .remove(last_used)
+            .expect("every entry has a recency tick");
+        *last_used = tick;
+        self.order.insert(tick, key);
+        Some(value)
+    }
+
+    /// Insert or replace `key` as the most recently used entry, evicting the
+    /// least recently used one if the cache is full.
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some((_, last_used)) = self.entries.insert(key.clone(), (value, tick)) {
+            self.order.remove(&last_used);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.insert(tick, key);
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, last_used) = self.entries.remove(key)?;
+        self.order.remove(&last_used);
+        Some(value)
+    }
+
+    /// Remove every entry for which `keep` returns false.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &V) -> bool) {
+        let entries = &mut self.entries;
+        self.order.retain(|_, key| {
+            let kept = keep(key, &entries[key].0);
+            if !kept {
+                entries.remove(key);
+            }
+            kept
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Entries from least to most recently used.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.values().map(|key| (key, &self.entries[key].0))
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(&1));
+
+        // "b" is now the least recently used
+        cache.insert("c", 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(
+            cache.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            vec![("a", 1), ("c", 3)]
+        );
+
+        // Replacing keeps the size and refreshes recency
+        cache.insert("a", 10);
+        cache.insert("d", 4);
+        assert_eq!(cache.get("a"), Some(&10));
+        assert_eq!(cache.get("c"), None);
+
+        let mut disabled = LruCache::new(0);
+        disabled.insert("a", 1);
+        assert!(disabled.is_empty());
+    }
+}
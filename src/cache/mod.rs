@@ -0,0 +1,14 @@
+//! In-memory caches for the search hot path.
+//!
+//! [`QueryEmbeddingCache`] skips the bi-encoder forward pass for repeated
+//! queries; it can be snapshotted to disk on shutdown and reloaded at startup.
+//! [`ResponseCache`] skips the whole search for repeated requests against the
+//! same catalog snapshot.
+
+pub mod embeddings;
+pub mod lru;
+pub mod results;
+
+pub use embeddings::QueryEmbeddingCache;
+pub use lru::LruCache;
+pub use results::{CacheControl, ResponseCache};
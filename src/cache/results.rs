@@ -0,0 +1,183 @@
+//! Cache of complete search responses.
+//!
+//! Whole answers repeat as often as queries do, and a cached answer skips both
+//! retrieval stages. Entries are keyed by the catalog snapshot's version and a
+//! caller-defined request key (for `/search`, the normalized query, agent
+//! context, top_k, filters and fusion settings), so a catalog change can never
+//! serve a stale answer.
+//! Entries expire after a TTL and the least recently used are evicted first.
+
+use crate::cache::LruCache;
+use crate::catalog::Catalog;
+use serde::Deserialize;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Per-request cache behavior, after the HTTP `Cache-Control` directives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheControl {
+    /// Serve a cached answer if there is one; cache fresh answers
+    #[default]
+    Default,
+    /// Always search, then cache the fresh answer
+    NoCache,
+    /// Always search and leave the cache untouched
+    NoStore,
+}
+
+impl CacheControl {
+    /// Whether a cached answer may be served.
+    pub fn reads(&self) -> bool {
+        *self == Self::Default
+    }
+
+    /// Whether the answer may be cached.
+    pub fn writes(&self) -> bool {
+        *self != Self::NoStore
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ResponseKey<K> {
+    catalog_version: u64,
+    request: K,
+}
+
+/// Bounded LRU cache of responses to requests keyed by `K`, each valid for `ttl`.
+#[derive(Debug)]
+pub struct ResponseCache<K, V> {
+    ttl: Duration,
+    entries: Mutex<LruCache<ResponseKey<K>, (Instant, V)>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> ResponseCache<K, V> {
+    /// An empty cache of at most `capacity` responses; a capacity or TTL of 0
+    /// disables caching.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        let capacity = if ttl.is_zero() { 0 } else { capacity };
+        Self {
+            ttl,
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.lock().capacity() > 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// The unexpired response cached for `request` on `catalog`, counting the
+    /// hit or miss.
+    pub fn get(&self, catalog: &Catalog, request: &K) -> Option<V> {
+        let mut entries = self.lock();
+        if entries.capacity() == 0 {
+            return None;
+        }
+        let key = ResponseKey {
+            catalog_version: catalog.version,
+            request: request.clone(),
+        };
+        let response = match entries.get(&key) {
+            Some((stored, response)) if stored.elapsed() < self.ttl => Some(response.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+        if response.is_some() {
+            metrics::counter!("search_cache_hits_total").increment(1);
+        } else {
+            metrics::counter!("search_cache_misses_total").increment(1);
+        }
+        response
+    }
+
+    /// Cache the response to `request` computed on `catalog`.
+    ///
+    /// Nothing is stored once `catalog` has been invalidated: a search that
+    /// finishes after its snapshot was replaced would only leave a dead entry.
+    pub fn insert(&self, catalog: &Catalog, request: K, response: V) {
+        let mut entries = self.lock();
+        if catalog.is_retired() {
+            return;
+        }
+        let key = ResponseKey {
+            catalog_version: catalog.version,
+            request,
+        };
+        entries.insert(key, (Instant::now(), response));
+        metrics::gauge!("search_cache_entries").set(entries.len() as f64);
+    }
+
+    /// Retire `catalog` and drop every response computed on it.
+    ///
+    /// Retiring under the cache lock means a concurrent [`ResponseCache::insert`]
+    /// either lands before and is dropped here, or sees the catalog retired.
+    pub fn invalidate_catalog(&self, catalog: &Catalog) {
+        let mut entries = self.lock();
+        catalog.retire();
+        entries.retain(|key, _| key.catalog_version != catalog.version);
+        metrics::gauge!("search_cache_entries").set(entries.len() as f64);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LruCache<ResponseKey<K>, (Instant, V)>> {
+        // A panic while holding the lock cannot leave the LRU inconsistent
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_are_scoped_to_catalog_and_expire() {
+        let [first, second, third] = [(); 3].map(|_| Catalog::empty(2));
+        let cache = ResponseCache::new(8, Duration::from_secs(60));
+        cache.insert(&first, "send a message", vec!["slack::send"]);
+        cache.insert(&second, "send a message", vec!["chat::post"]);
+        assert_eq!(
+            cache.get(&first, &"send a message"),
+            Some(vec!["slack::send"])
+        );
+        assert_eq!(cache.get(&third, &"send a message"), None);
+
+        cache.invalidate_catalog(&first);
+        assert_eq!(cache.get(&first, &"send a message"), None);
+        assert_eq!(
+            cache.get(&second, &"send a message"),
+            Some(vec!["chat::post"])
+        );
+
+        // A search that finishes after its catalog was replaced is not cached
+        cache.insert(&first, "late", vec!["slack::send"]);
+        assert_eq!(cache.len(), 1);
+
+        let expiring = ResponseCache::new(8, Duration::from_millis(1));
+        expiring.insert(&second, "q", 1);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(expiring.get(&second, &"q"), None);
+        assert!(expiring.is_empty());
+
+        assert!(!ResponseCache::<&str, u32>::new(8, Duration::ZERO).is_enabled());
+    }
+
+    #[test]
+    fn test_cache_control_directives() {
+        let parse = |s: &str| serde_json::from_str::<CacheControl>(s).unwrap();
+        assert_eq!(parse("\"no-cache\""), CacheControl::NoCache);
+        assert!(!parse("\"no-cache\"").reads() && parse("\"no-cache\"").writes());
+        assert!(!parse("\"no-store\"").reads() && !parse("\"no-store\"").writes());
+        assert!(CacheControl::default().reads() && CacheControl::default().writes());
+    }
+}
@@ -0,0 +1,419 @@
+//! Searchable catalog snapshots.
+//!
+//! A [`Catalog`] pairs the loaded tools with their stage-1 embeddings (row `i`
+//! of `embeddings` belongs to `tools[i]`), the index used to search them and
+//! lexical and tool-name indexes over the same rows.
+//! Snapshots are immutable: updates
+//! build a new catalog and swap it into `AppState`, so in-flight searches keep
+//! a consistent view of the old one.
+
+use crate::error::{AppError, Result};
+use crate::index::{Bm25Index, EmbeddingMatrix, FlatIndex, NameIndex, VectorIndex};
+use crate::ingestion::{resolve_duplicates, DuplicatePolicy, EncapureTool};
+use ndarray::Array2;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Source of catalog versions; every snapshot built gets a new one.
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
+/// Tools and their pre-computed embeddings.
+#[derive(Debug)]
+pub struct Catalog {
+    pub tools: Vec<EncapureTool>,
+    /// Shape: (num_tools, embedding_dim)
+    pub embeddings: EmbeddingMatrix,
+    /// Stage-1 nearest-neighbour index over `embeddings`
+    pub index: Arc<dyn VectorIndex>,
+    /// BM25 index over the tools' names, descriptions and parameter names
+    pub lexical: Bm25Index,
+    /// Normalized tool names for exact and fuzzy name lookups
+    pub names: NameIndex,
+    /// Unique per snapshot (across the default catalog and collections), so
+    /// anything derived from a snapshot can be keyed on it
+    pub version: u64,
+    /// Set once the snapshot has been replaced or deleted
+    retired: AtomicBool,
+}
+
+/// What changed when one server's tools were replaced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CatalogChanges {
+    pub added: usize,
+    pub removed: usize,
+    pub updated: usize,
+    /// Embedding views that had to be encoded (the rest were reused)
+    pub reembedded: usize,
+}
+
+impl CatalogChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.updated == 0
+    }
+}
+
+impl Catalog {
+    /// A catalog searched by exact scan.
+    pub fn new(tools: Vec<EncapureTool>, embeddings: EmbeddingMatrix) -> Self {
+        Self::with_index(tools, embeddings, Arc::new(FlatIndex))
+    }
+
+    /// A catalog searched with `index`, which must have been built over `embeddings`.
+    ///
+    /// The lexical and name indexes are built here from `tools`.
+    pub fn with_index(
+        tools: Vec<EncapureTool>,
+        embeddings: EmbeddingMatrix,
+        index: Arc<dyn VectorIndex>,
+    ) -> Self {
+        debug_assert_eq!(tools.len(), embeddings.nrows());
+        let lexical = Bm25Index::build(&tools);
+        let names = NameIndex::build(&tools);
+        Self {
+            tools,
+            embeddings,
+            index,
+            lexical,
+            names,
+            version: NEXT_VERSION.fetch_add(1, Ordering::Relaxed),
+            retired: AtomicBool::new(false),
+        }
+    }
+
+    /// Mark this snapshot as replaced or deleted; searches still running on it
+    /// finish, but nothing derived from it should be kept.
+    pub fn retire(&self) {
+        self.retired.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_retired(&self) -> bool {
+        self.retired.load(Ordering::SeqCst)
+    }
+
+    /// A catalog with no tools.
+    pub fn empty(embedding_dim: usize) -> Self {
+        Self::new(Vec::new(), Array2::zeros((0, embedding_dim)).into())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Build a new catalog in which `server`'s tools are replaced by `tools`.
+    ///
+    /// The server keeps its position in the catalog (new servers are appended)
+    /// and duplicates are resolved again with `policy`. Embeddings are reused
+    /// for every embedding view already in the catalog; only new or changed
+    /// views are passed to `embed`.
+    ///
+    /// Returns `None` if the server's tools are unchanged.
+    pub fn with_server_tools(
+        &self,
+        server: &str,
+        tools: Vec<EncapureTool>,
+        policy: DuplicatePolicy,
+        embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+    ) -> Result<Option<(Catalog, CatalogChanges)>> {
+        let old: Vec<&EncapureTool> = self
+            .tools
+            .iter()
+            .filter(|t| t.server_origin == server)
+            .collect();
+        let changes = diff_tools(&old, &tools);
+        if changes.is_empty() && old.len() == tools.len() {
+            return Ok(None);
+        }
+
+        // Splice the server's new tools in where its old ones were
+        let mut merged = Vec::with_capacity(self.tools.len() - old.len() + tools.len());
+        let mut replacement = Some(tools);
+        for tool in &self.tools {
+            if tool.server_origin == server {
+                if let Some(new_tools) = replacement.take() {
+                    merged.extend(new_tools);
+                }
+            } else {
+                merged.push(tool.clone());
+            }
+        }
+        if let Some(new_tools) = replacement {
+            merged.extend(new_tools);
+        }
+        let merged = resolve_duplicates(merged, policy)?;
+
+        let (next, reembedded) = self.rebuild(merged, embed)?;
+        let changes = CatalogChanges {
+            reembedded,
+            ..changes
+        };
+        Ok(Some((next, changes)))
+    }
+
+    /// Build a new catalog holding exactly `tools` (already deduplicated).
+    ///
+    /// Used when every source is reloaded at once. Embeddings are reused the
+    /// same way as in [`Catalog::with_server_tools`]; returns `None` if nothing
+    /// changed.
+    pub fn with_tools(
+        &self,
+        tools: Vec<EncapureTool>,
+        embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+    ) -> Result<Option<(Catalog, CatalogChanges)>> {
+        let old: Vec<&EncapureTool> = self.tools.iter().collect();
+        let changes = diff_tools(&old, &tools);
+        if changes.is_empty() && old.len() == tools.len() {
+            return Ok(None);
+        }
+
+        let (next, reembedded) = self.rebuild(tools, embed)?;
+        let changes = CatalogChanges {
+            reembedded,
+            ..changes
+        };
+        Ok(Some((next, changes)))
+    }
+
+    /// Pair `tools` with embeddings, reusing rows for views that are already
+    /// embedded and encoding the rest with `embed` (stored at the catalog's
+    /// precision). The index is updated the same way: reused rows keep their
+    /// place in it and only new rows are added.
+    ///
+    /// Returns the new catalog and the number of views that were encoded.
+    fn rebuild(
+        &self,
+        tools: Vec<EncapureTool>,
+        embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+    ) -> Result<(Catalog, usize)> {
+        let known: HashMap<&str, usize> = self
+            .tools
+            .iter()
+            .enumerate()
+            .map(|(row, t)| (t.embedding_view.as_str(), row))
+            .collect();
+        let mut missing: Vec<String> = Vec::new();
+        let mut missing_rows: HashMap<&str, usize> = HashMap::new();
+        for tool in &tools {
+            let view = tool.embedding_view.as_str();
+            if !known.contains_key(view) && !missing_rows.contains_key(view) {
+                missing_rows.insert(view, missing.len());
+                missing.push(view.to_string());
+            }
+        }
+        let precision = self.embeddings.precision();
+        let fresh = EmbeddingMatrix::quantize(embed(&missing)?, precision);
+
+        let mut previous_rows = Vec::with_capacity(tools.len());
+        let mut sources = Vec::with_capacity(tools.len());
+        for tool in &tools {
+            let view = tool.embedding_view.as_str();
+            match known.get(view) {
+                Some(&old_row) => sources.push((&self.embeddings, old_row)),
+                None => sources.push((&fresh, missing_rows[view])),
+            }
+            previous_rows.push(known.get(view).copied());
+        }
+        // An empty catalog's width is only a placeholder; the model's rows decide
+        let dim = if self.embeddings.nrows() == 0 {
+            fresh.ncols()
+        } else {
+            self.embeddings.ncols()
+        };
+        let embeddings = EmbeddingMatrix::gather(precision, dim, sources).map_err(|e| {
+            AppError::InternalError(format!("Failed to read stored embeddings: {}", e))
+        })?;
+
+        let index = self.index.updated(&embeddings, &previous_rows);
+        Ok((Catalog::with_index(tools, embeddings, index), missing.len()))
+    }
+}
+
+/// Count added, removed and updated tools between two versions of a server.
+fn diff_tools(old: &[&EncapureTool], new: &[EncapureTool]) -> CatalogChanges {
+    let old_by_id: HashMap<&str, &EncapureTool> = old.iter().map(|t| (t.id.as_str(), *t)).collect();
+    let new_ids: HashSet<&str> = new.iter().map(|t| t.id.as_str()).collect();
+
+    let mut changes = CatalogChanges {
+        removed: old_by_id.keys().filter(|id| !new_ids.contains(*id)).count(),
+        ..Default::default()
+    };
+    for tool in new {
+        match old_by_id.get(tool.id.as_str()) {
+            None => changes.added += 1,
+            Some(previous) if *previous != tool => changes.updated += 1,
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::cell::RefCell;
+
+    fn tool(server: &str, name: &str, desc: &str) -> EncapureTool {
+        EncapureTool::new(
+            name.to_string(),
+            server.to_string(),
+            desc.to_string(),
+            json!({ "name": name, "description": desc }),
+        )
+    }
+
+    /// Embeds each view as [len, 1.0] and records what it was asked to encode.
+    fn recording_embedder(
+        calls: &RefCell<Vec<String>>,
+    ) -> impl FnOnce(&[String]) -> Result<Array2<f32>> + '_ {
+        move |views| {
+            calls.borrow_mut().extend(views.iter().cloned());
+            let data: Vec<f32> = views.iter().flat_map(|v| [v.len() as f32, 1.0]).collect();
+            Ok(Array2::from_shape_vec((views.len(), 2), data).unwrap())
+        }
+    }
+
+    fn catalog(tools: Vec<EncapureTool>) -> Catalog {
+        let calls = RefCell::new(Vec::new());
+        let views: Vec<String> = tools.iter().map(|t| t.embedding_view.clone()).collect();
+        let embeddings = recording_embedder(&calls)(&views).unwrap();
+        Catalog::new(tools, embeddings.into())
+    }
+
+    #[test]
+    fn test_only_changed_views_are_embedded() {
+        let current = catalog(vec![
+            tool("github", "create_issue", "Create an issue"),
+            tool("slack", "send_message", "Send a message"),
+            tool("github", "list_repos", "List repositories"),
+        ]);
+        let calls = RefCell::new(Vec::new());
+
+        let (next, changes) = current
+            .with_server_tools(
+                "github",
+                vec![
+                    tool("github", "create_issue", "Create an issue"),
+                    tool("github", "close_issue", "Close an issue"),
+                ],
+                DuplicatePolicy::KeepAll,
+                recording_embedder(&calls),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*calls.borrow(), vec!["Close an issue"]);
+        assert_eq!(
+            changes,
+            CatalogChanges {
+                added: 1,
+                removed: 1,
+                updated: 0,
+                reembedded: 1
+            }
+        );
+        let ids: Vec<&str> = next.tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "github::create_issue",
+                "github::close_issue",
+                "slack::send_message"
+            ]
+        );
+        assert_eq!(next.embeddings.nrows(), 3);
+        assert_eq!(next.embeddings.row(0), current.embeddings.row(0));
+        assert_eq!(next.embeddings.row(1)[0], "Close an issue".len() as f32);
+        assert_eq!(next.embeddings.row(2), current.embeddings.row(1));
+    }
+
+    #[test]
+    fn test_unchanged_server_is_a_no_op() {
+        let current = catalog(vec![tool("github", "create_issue", "Create an issue")]);
+        let result = current
+            .with_server_tools(
+                "github",
+                vec![tool("github", "create_issue", "Create an issue")],
+                DuplicatePolicy::KeepAll,
+                |_| panic!("nothing to embed"),
+            )
+            .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_new_server_is_appended_and_raw_changes_count_as_updates() {
+        let current = catalog(vec![tool("github", "create_issue", "Create an issue")]);
+        let calls = RefCell::new(Vec::new());
+        let (next, _) = current
+            .with_server_tools(
+                "jira",
+                vec![tool("jira", "create_issue", "Create a ticket")],
+                DuplicatePolicy::Error,
+                recording_embedder(&calls),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.tools[1].id, "jira::create_issue");
+
+        // Same view, new schema: no re-embedding, but the tool is replaced
+        let mut changed = tool("github", "create_issue", "Create an issue");
+        changed.raw_definition["inputSchema"] = json!({ "type": "object" });
+        let (next, changes) = next
+            .with_server_tools("github", vec![changed], DuplicatePolicy::Error, |_| {
+                Ok(Array2::zeros((0, 2)))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(changes.updated, 1);
+        assert_eq!(changes.reembedded, 0);
+        assert!(next.tools[0].raw_definition.get("inputSchema").is_some());
+    }
+
+    #[test]
+    fn test_full_reload_reuses_embeddings() {
+        let current = catalog(vec![
+            tool("github", "create_issue", "Create an issue"),
+            tool("slack", "send_message", "Send a message"),
+        ]);
+        let calls = RefCell::new(Vec::new());
+
+        let (next, changes) = current
+            .with_tools(
+                vec![
+                    tool("slack", "send_message", "Send a message"),
+                    tool("slack", "send_file", "Upload a file"),
+                ],
+                recording_embedder(&calls),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(*calls.borrow(), vec!["Upload a file"]);
+        assert_eq!((changes.added, changes.removed), (1, 1));
+        assert_eq!(next.embeddings.row(0), current.embeddings.row(1));
+
+        let unchanged = next.with_tools(next.tools.clone(), |_| panic!("nothing to embed"));
+        assert!(unchanged.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_first_tools_set_the_width_of_an_empty_catalog() {
+        let calls = RefCell::new(Vec::new());
+        let (next, _) = Catalog::empty(768)
+            .with_tools(
+                vec![tool("billing", "refund_invoice", "Refund a paid invoice")],
+                recording_embedder(&calls),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(next.embeddings.ncols(), 2);
+        assert_eq!(next.len(), 1);
+    }
+}
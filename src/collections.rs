@@ -0,0 +1,558 @@
+//! Named collections: separate tool catalogs sharing one deployment.
+//!
+//! Each collection holds its own tools, embeddings matrix and embeddings
+//! cache file, so teams can search their own tool universe without seeing
+//! anyone else's. Collections are stored under COLLECTIONS_PATH, one
+//! directory per collection:
+//!
+//! ```text
+//! <COLLECTIONS_PATH>/<name>/collection.json   # the documents it was created from
+//! <COLLECTIONS_PATH>/<name>/embeddings.bin    # embeddings cache for its tools
+//! <COLLECTIONS_PATH>/<name>/embeddings.hnsw   # HNSW graph (VECTOR_INDEX=hnsw only)
+//! ```
+//!
+//! A new collection is written to a `.tmp-` directory and renamed into place,
+//! so searches never wait on the disk; leftovers are removed at startup.
+//!
+//! The model session pools stay shared: callers pass the embedding function.
+
+use crate::catalog::Catalog;
+use crate::error::{AppError, Result};
+use crate::index::{save_index_cache, EmbeddingMatrix, IndexConfig};
+use crate::ingestion::openapi::parse_spec;
+use crate::ingestion::{
+    atomize_tools_with_options, resolve_duplicates, AtomizeOptions, EncapureTool, ToolFormat,
+};
+use crate::persistence::{save_embeddings_cache, try_load_embeddings_cache};
+use ndarray::Array2;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Longest accepted collection name.
+const MAX_NAME_LENGTH: usize = 64;
+
+const SPEC_FILE: &str = "collection.json";
+const CACHE_FILE: &str = "embeddings.bin";
+
+/// Prefix of directories being written or deleted (never loaded as collections).
+const TEMP_PREFIX: &str = ".tmp-";
+
+/// Makes temporary directory names unique within the process.
+static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+/// One tool document loaded into a collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionDocument {
+    /// Server name the document's tools are loaded under
+    pub server: String,
+    /// Override TOOLS_FORMAT for this document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ToolFormat>,
+    /// The tool document: a JSON value, or JSON/YAML text (e.g. an OpenAPI spec)
+    pub document: Value,
+}
+
+/// Everything needed to build a collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSpec {
+    /// Letters, digits, `-` and `_` (used as a directory name)
+    pub name: String,
+    pub documents: Vec<CollectionDocument>,
+}
+
+/// A collection as reported by the API.
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionSummary {
+    pub name: String,
+    /// Number of tools in the collection
+    pub tools: usize,
+    /// Servers contributing tools, in load order
+    pub servers: Vec<String>,
+}
+
+impl CollectionSummary {
+    fn new(name: &str, catalog: &Catalog) -> Self {
+        let mut servers: Vec<String> = Vec::new();
+        for tool in &catalog.tools {
+            if !servers.contains(&tool.server_origin) {
+                servers.push(tool.server_origin.clone());
+            }
+        }
+        Self {
+            name: name.to_string(),
+            tools: catalog.len(),
+            servers,
+        }
+    }
+}
+
+/// All named collections, keyed by name.
+pub struct Collections {
+    root: PathBuf,
+    /// Index built for every collection
+    index: IndexConfig,
+    entries: RwLock<BTreeMap<String, Arc<Catalog>>>,
+}
+
+impl Collections {
+    /// Load every collection stored under `root`.
+    ///
+    /// Embeddings come from each collection's cache when it is still valid,
+    /// otherwise from `embed`. A collection that fails to load is logged and
+    /// skipped so one broken directory cannot block startup.
+    pub fn load(
+        root: &Path,
+        options: &AtomizeOptions,
+        index: &IndexConfig,
+        embed: impl Fn(&[String]) -> Result<Array2<f32>>,
+    ) -> Self {
+        let collections = Self {
+            root: root.to_path_buf(),
+            index: index.clone(),
+            entries: RwLock::new(BTreeMap::new()),
+        };
+        let Ok(dirs) = fs::read_dir(root) else {
+            return collections;
+        };
+
+        let mut entries = BTreeMap::new();
+        for dir in dirs.filter_map(|d| d.ok()).map(|d| d.path()) {
+            let is_temp = dir
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(TEMP_PREFIX));
+            if is_temp {
+                // Left behind by a create or delete that never finished
+                remove_dir(&dir);
+                continue;
+            }
+            if !dir.join(SPEC_FILE).is_file() {
+                continue;
+            }
+            match load_collection(&dir, options, index, &embed) {
+                Ok((name, catalog)) => {
+                    tracing::info!(collection = %name, tools = catalog.len(), "Loaded collection");
+                    entries.insert(name, Arc::new(catalog));
+                }
+                Err(e) => {
+                    tracing::warn!(path = %dir.display(), error = %e, "Skipping collection");
+                }
+            }
+        }
+
+        *collections
+            .entries
+            .write()
+            .expect("collections lock poisoned") = entries;
+        collections
+    }
+
+    /// The collection called `name`, if it exists.
+    pub fn get(&self, name: &str) -> Option<Arc<Catalog>> {
+        self.entries
+            .read()
+            .expect("collections lock poisoned")
+            .get(name)
+            .cloned()
+    }
+
+    /// Every collection, sorted by name.
+    pub fn list(&self) -> Vec<CollectionSummary> {
+        self.entries
+            .read()
+            .expect("collections lock poisoned")
+            .iter()
+            .map(|(name, catalog)| CollectionSummary::new(name, catalog))
+            .collect()
+    }
+
+    /// Build, persist and register a new collection.
+    ///
+    /// Blocks while embedding, so call it from a blocking context.
+    ///
+    /// # Errors
+    /// Returns `AppError::ValidationError` for an invalid name or document and
+    /// `AppError::Conflict` if the name is taken.
+    pub fn create(
+        &self,
+        spec: CollectionSpec,
+        options: &AtomizeOptions,
+        embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+    ) -> Result<CollectionSummary> {
+        validate_name(&spec.name)?;
+        if self.get(&spec.name).is_some() {
+            return Err(conflict(&spec.name));
+        }
+
+        let catalog = build_catalog(&spec, options, &self.index, embed)?;
+        let summary = CollectionSummary::new(&spec.name, &catalog);
+
+        // Write everything before taking the lock; only renames happen under it
+        let staged = self.temp_dir(&spec.name);
+        if let Err(e) = save_collection(&staged, &spec, &catalog) {
+            remove_dir(&staged);
+            return Err(e);
+        }
+
+        let dir = self.root.join(&spec.name);
+        let mut entries = self.entries.write().expect("collections lock poisoned");
+        // Another request may have created it while we were embedding
+        if entries.contains_key(&spec.name) {
+            drop(entries);
+            remove_dir(&staged);
+            return Err(conflict(&spec.name));
+        }
+        // Files of a collection that failed to load are replaced
+        let stale = self.move_aside(&dir);
+        if let Err(e) = fs::rename(&staged, &dir) {
+            if let Some(stale) = &stale {
+                let _ = fs::rename(stale, &dir);
+            }
+            drop(entries);
+            remove_dir(&staged);
+            return Err(AppError::ResourceError(format!(
+                "Failed to save collection: {}",
+                e
+            )));
+        }
+        entries.insert(spec.name, Arc::new(catalog));
+        drop(entries);
+
+        if let Some(stale) = stale {
+            remove_dir(&stale);
+        }
+        Ok(summary)
+    }
+
+    /// Unregister a collection and move its files aside.
+    ///
+    /// Searches already running against it finish normally. The files are
+    /// only renamed here; call [`DeletedCollection::remove_files`] (from a
+    /// blocking context) to delete them.
+    pub fn delete(&self, name: &str) -> Result<DeletedCollection> {
+        let mut entries = self.entries.write().expect("collections lock poisoned");
+        let catalog = entries
+            .remove(name)
+            .ok_or_else(|| AppError::NotFound(format!("Collection '{}' not found", name)))?;
+        // Renamed under the lock, so a collection created again under the
+        // same name never loses its files to this delete
+        let files = self.move_aside(&self.root.join(name));
+        Ok(DeletedCollection { catalog, files })
+    }
+
+    /// A new path under the root for `name`'s files while they are written or
+    /// deleted.
+    fn temp_dir(&self, name: &str) -> PathBuf {
+        let n = NEXT_TEMP.fetch_add(1, Ordering::Relaxed);
+        self.root.join(format!(
+            "{}{}-{}-{}",
+            TEMP_PREFIX,
+            name,
+            std::process::id(),
+            n
+        ))
+    }
+
+    /// Rename `dir` to a temporary path, if it exists, and return that path.
+    fn move_aside(&self, dir: &Path) -> Option<PathBuf> {
+        let name = dir.file_name()?.to_str()?;
+        let aside = self.temp_dir(name);
+        match fs::rename(dir, &aside) {
+            Ok(()) => Some(aside),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                tracing::warn!(path = %dir.display(), error = %e, "Failed to move collection files");
+                None
+            }
+        }
+    }
+}
+
+/// A collection removed by [`Collections::delete`].
+pub struct DeletedCollection {
+    /// Its last catalog snapshot
+    pub catalog: Arc<Catalog>,
+    /// Where its files were moved to
+    files: Option<PathBuf>,
+}
+
+impl DeletedCollection {
+    /// Delete the collection's files. Blocks on the disk.
+    pub fn remove_files(&self) {
+        if let Some(files) = &self.files {
+            remove_dir(files);
+        }
+    }
+}
+
+/// Remove a directory tree, logging failures.
+fn remove_dir(dir: &Path) {
+    if let Err(e) = fs::remove_dir_all(dir) {
+        tracing::warn!(path = %dir.display(), error = %e, "Failed to remove collection files");
+    }
+}
+
+/// Check that `name` can be used as a collection (and directory) name.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::ValidationError(format!(
+            "Invalid collection name '{}' (use up to {} letters, digits, '-' or '_')",
+            name, MAX_NAME_LENGTH
+        )))
+    }
+}
+
+/// Atomize every document of `spec` into one deduplicated tool list.
+pub fn atomize_spec(spec: &CollectionSpec, options: &AtomizeOptions) -> Result<Vec<EncapureTool>> {
+    let mut tools = Vec::new();
+    for doc in &spec.documents {
+        if doc.server.is_empty() {
+            return Err(AppError::ValidationError(
+                "server cannot be empty".to_string(),
+            ));
+        }
+        let document = match &doc.document {
+            Value::String(text) => parse_spec(text).map_err(|_| {
+                AppError::ValidationError(format!(
+                    "Document for server '{}' is neither valid JSON nor YAML",
+                    doc.server
+                ))
+            })?,
+            other => other.clone(),
+        };
+
+        let mut doc_options = options.clone();
+        if let Some(format) = doc.format {
+            doc_options.format = format;
+        }
+        tools.extend(atomize_tools_with_options(
+            &document,
+            &doc.server,
+            &doc_options,
+        )?);
+    }
+    resolve_duplicates(tools, options.duplicate_policy)
+}
+
+fn build_catalog(
+    spec: &CollectionSpec,
+    options: &AtomizeOptions,
+    index: &IndexConfig,
+    embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+) -> Result<Catalog> {
+    let tools = atomize_spec(spec, options)?;
+    if tools.is_empty() {
+        return Err(AppError::ValidationError(format!(
+            "Collection '{}' has no tools",
+            spec.name
+        )));
+    }
+    embed_tools(tools, index, embed)
+}
+
+fn embed_tools(
+    tools: Vec<EncapureTool>,
+    index: &IndexConfig,
+    embed: impl FnOnce(&[String]) -> Result<Array2<f32>>,
+) -> Result<Catalog> {
+    let views: Vec<String> = tools.iter().map(|t| t.embedding_view.clone()).collect();
+    let embeddings = EmbeddingMatrix::compress(embed(&views)?, index.precision);
+    let index = index.build(&embeddings);
+    Ok(Catalog::with_index(tools, embeddings, index))
+}
+
+fn load_collection(
+    dir: &Path,
+    options: &AtomizeOptions,
+    index: &IndexConfig,
+    embed: impl Fn(&[String]) -> Result<Array2<f32>>,
+) -> Result<(String, Catalog)> {
+    let content = fs::read_to_string(dir.join(SPEC_FILE))
+        .map_err(|e| AppError::ValidationError(format!("Failed to read collection: {}", e)))?;
+    let spec: CollectionSpec = serde_json::from_str(&content)
+        .map_err(|e| AppError::ValidationError(format!("Invalid collection file: {}", e)))?;
+    validate_name(&spec.name)?;
+    // `delete` removes the directory named after the collection
+    if dir.file_name().and_then(|n| n.to_str()) != Some(spec.name.as_str()) {
+        return Err(AppError::ValidationError(format!(
+            "Directory holds collection '{}' but is not named after it",
+            spec.name
+        )));
+    }
+
+    let tools = atomize_spec(&spec, options)?;
+    let cache_path = dir.join(CACHE_FILE);
+    if let Some(embeddings) = try_load_embeddings_cache(&cache_path, &tools, index.precision)? {
+        let index = index.load_or_build(&tools, &embeddings, Some(&cache_path));
+        return Ok((spec.name, Catalog::with_index(tools, embeddings, index)));
+    }
+
+    let catalog = embed_tools(tools, index, embed)?;
+    if let Err(e) = save_embeddings_cache(&cache_path, &catalog.tools, &catalog.embeddings) {
+        tracing::warn!(error = %e, "Failed to save collection embeddings cache (non-fatal)");
+    }
+    if let Err(e) = save_index_cache(&cache_path, &catalog.tools, catalog.index.as_ref()) {
+        tracing::warn!(error = %e, "Failed to save collection vector index (non-fatal)");
+    }
+    Ok((spec.name, catalog))
+}
+
+fn save_collection(dir: &Path, spec: &CollectionSpec, catalog: &Catalog) -> Result<()> {
+    let write_error =
+        |e: std::io::Error| AppError::ResourceError(format!("Failed to save collection: {}", e));
+    fs::create_dir_all(dir).map_err(write_error)?;
+    let json = serde_json::to_string_pretty(spec)
+        .map_err(|e| AppError::ResourceError(format!("Failed to serialize collection: {}", e)))?;
+    fs::write(dir.join(SPEC_FILE), json).map_err(write_error)?;
+
+    let cache_path = dir.join(CACHE_FILE);
+    if let Err(e) = save_embeddings_cache(&cache_path, &catalog.tools, &catalog.embeddings) {
+        tracing::warn!(error = %e, "Failed to save collection embeddings cache (non-fatal)");
+    }
+    if let Err(e) = save_index_cache(&cache_path, &catalog.tools, catalog.index.as_ref()) {
+        tracing::warn!(error = %e, "Failed to save collection vector index (non-fatal)");
+    }
+    Ok(())
+}
+
+fn conflict(name: &str) -> AppError {
+    AppError::Conflict(format!("Collection '{}' already exists", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::IndexKind;
+    use serde_json::json;
+    use std::cell::Cell;
+    use tempfile::tempdir;
+
+    /// Embeds each view as [len, 1.0].
+    fn embed(views: &[String]) -> Result<Array2<f32>> {
+        let data: Vec<f32> = views.iter().flat_map(|v| [v.len() as f32, 1.0]).collect();
+        Ok(Array2::from_shape_vec((views.len(), 2), data).unwrap())
+    }
+
+    fn spec(name: &str, tools: &[&str]) -> CollectionSpec {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|n| json!({ "name": n, "description": format!("The {} tool", n) }))
+            .collect();
+        CollectionSpec {
+            name: name.to_string(),
+            documents: vec![CollectionDocument {
+                server: "github".to_string(),
+                format: None,
+                document: json!({ "tools": tools }),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("team-a_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn test_create_list_and_delete() {
+        let dir = tempdir().unwrap();
+        let options = AtomizeOptions::default();
+        let collections = Collections::load(dir.path(), &options, &IndexConfig::default(), embed);
+        assert!(collections.list().is_empty());
+
+        let summary = collections
+            .create(
+                spec("team-a", &["create_issue", "close_issue"]),
+                &options,
+                embed,
+            )
+            .unwrap();
+        assert_eq!(summary.tools, 2);
+        assert_eq!(summary.servers, vec!["github"]);
+        assert!(dir.path().join("team-a").join(SPEC_FILE).is_file());
+        // Nothing is left of the staging directory
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let taken = collections.create(spec("team-a", &["other"]), &options, embed);
+        assert!(matches!(taken, Err(AppError::Conflict(_))));
+
+        let deleted = collections.delete("team-a").unwrap();
+        assert!(collections.get("team-a").is_none());
+        assert!(!dir.path().join("team-a").exists());
+        assert_eq!(deleted.catalog.len(), 2);
+        deleted.remove_files();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert!(matches!(
+            collections.delete("team-a"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_reload_uses_embeddings_cache() {
+        let dir = tempdir().unwrap();
+        let options = AtomizeOptions::default();
+        let index = IndexConfig {
+            kind: IndexKind::Hnsw,
+            ..Default::default()
+        };
+        Collections::load(dir.path(), &options, &index, embed)
+            .create(spec("team-a", &["create_issue"]), &options, embed)
+            .unwrap();
+        std::fs::create_dir_all(dir.path().join("not-a-collection")).unwrap();
+
+        let calls = Cell::new(0);
+        assert!(dir.path().join("team-a").join("embeddings.hnsw").is_file());
+
+        let reloaded = Collections::load(dir.path(), &options, &index, |views| {
+            calls.set(calls.get() + 1);
+            embed(views)
+        });
+
+        assert_eq!(calls.get(), 0);
+        let names: Vec<String> = reloaded.list().into_iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["team-a"]);
+        let catalog = reloaded.get("team-a").unwrap();
+        assert_eq!(catalog.embeddings.nrows(), 1);
+        assert_eq!(catalog.index.kind(), IndexKind::Hnsw);
+    }
+
+    #[test]
+    fn test_renamed_directory_is_skipped() {
+        let dir = tempdir().unwrap();
+        let options = AtomizeOptions::default();
+        let index = IndexConfig::default();
+        Collections::load(dir.path(), &options, &index, embed)
+            .create(spec("team-a", &["create_issue"]), &options, embed)
+            .unwrap();
+        std::fs::rename(dir.path().join("team-a"), dir.path().join("team-b")).unwrap();
+        let leftover = dir.path().join(format!("{}team-c-1-0", TEMP_PREFIX));
+        std::fs::create_dir_all(&leftover).unwrap();
+
+        let reloaded = Collections::load(dir.path(), &options, &index, embed);
+        assert!(reloaded.list().is_empty());
+        assert!(!leftover.exists());
+
+        // The unloaded directory does not block creating the collection it held
+        reloaded
+            .create(spec("team-b", &["close_issue"]), &options, embed)
+            .unwrap();
+        let saved = std::fs::read_to_string(dir.path().join("team-b").join(SPEC_FILE)).unwrap();
+        assert!(saved.contains("close_issue"));
+    }
+}
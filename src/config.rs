@@ -1,5 +1,12 @@
+use crate::index::{FusionConfig, HnswParams, IndexConfig, NameMatchConfig};
+use crate::ingestion::{
+    AtomizeOptions, DuplicatePolicy, TokenBudget, TokenCounter, ToolFormat, ViewTemplate,
+    DEFAULT_ID_SEPARATOR,
+};
+use crate::mcp::{parse_server_list, McpServerConfig};
 use std::env;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 /// Operating mode for Encapure server.
 /// Controls pool_size, permits, and intra_threads settings.
@@ -42,14 +49,51 @@ pub struct Config {
     pub max_documents: usize,
     /// Batch size for internal chunking during inference.
     pub batch_size: usize,
-    /// Optional path to tools JSON file for semantic routing.
-    pub tools_path: Option<PathBuf>,
+    /// Tool catalog entries for semantic routing: JSON files, directories or
+    /// glob patterns. Empty if no TOOLS_PATH is configured.
+    pub tools_paths: Vec<PathBuf>,
+    /// Format of the tool documents in TOOLS_PATH (default: auto-detect).
+    pub tools_format: ToolFormat,
+    /// Template for the stage-1 (bi-encoder) tool view.
+    pub embedding_view_template: ViewTemplate,
+    /// Template for the stage-2 (cross-encoder) tool view.
+    pub rerank_view_template: ViewTemplate,
+    /// Optional token budget for each tool's inference view, measured with the
+    /// reranker tokenizer. Leave headroom below MAX_SEQ_LENGTH for the query.
+    pub view_token_budget: Option<usize>,
+    /// Separator between server and tool name in qualified tool ids (default "::").
+    pub tool_id_separator: String,
+    /// How tools with the same qualified id are handled when catalogs are merged.
+    pub duplicate_policy: DuplicatePolicy,
+    /// Optional sidecar manifest adding tags and metadata to tools.
+    pub tools_metadata_path: Option<PathBuf>,
+    /// File persisting tools registered, replaced or deleted through the `/tools` API.
+    pub tools_overlay_path: PathBuf,
+    /// Seconds between checks of TOOLS_PATH for changed files (0 disables watching).
+    pub tools_watch_interval_secs: u64,
+    /// Directory holding named collections (one subdirectory per collection).
+    pub collections_path: PathBuf,
+    /// MCP servers to start and query for tools at startup (MCP_SERVERS JSON array).
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Optional `mcpServers` client configuration file listing more servers.
+    pub mcp_config_path: Option<PathBuf>,
+    /// Timeout in seconds for each request to an MCP server.
+    pub mcp_timeout_secs: u64,
+    /// Seconds between tool refreshes of HTTP MCP servers (0 disables refreshing).
+    pub mcp_refresh_interval_secs: u64,
     /// Path to bi-encoder ONNX model for fast semantic search.
     pub bi_encoder_model_path: PathBuf,
     /// Path to bi-encoder tokenizer.
     pub bi_encoder_tokenizer_path: PathBuf,
     /// Number of candidates to retrieve in first-stage (bi-encoder) before reranking.
     pub retrieval_candidates: usize,
+    /// Stage-1 index type (exact flat scan or HNSW graph) and its parameters.
+    pub vector_index: IndexConfig,
+    /// How stage-1 dense and lexical (BM25) candidates are merged, unless a
+    /// request overrides it.
+    pub fusion: FusionConfig,
+    /// Boost and edit distance of tool-name matches injected into stage 2.
+    pub name_match: NameMatchConfig,
     /// Number of threads per ONNX session for intra-op parallelism.
     /// Default: 8. Higher values improve single-request latency at the cost of concurrency.
     /// Formula: permits × intra_threads ≤ physical_cores
@@ -60,6 +104,15 @@ pub struct Config {
     /// Path to embeddings cache file. Pre-computed embeddings are stored here
     /// to avoid loading the bi-encoder model at runtime.
     pub embeddings_cache_path: PathBuf,
+    /// Number of query embeddings kept in memory (0 disables the cache).
+    pub query_cache_size: usize,
+    /// Optional file the query embedding cache is saved to on shutdown and
+    /// reloaded from at startup.
+    pub query_cache_path: Option<PathBuf>,
+    /// Number of search responses kept in memory (0 disables the cache).
+    pub search_cache_size: usize,
+    /// Seconds a cached search response stays valid (0 disables the cache).
+    pub search_cache_ttl_secs: u64,
 }
 
 impl Config {
@@ -93,6 +146,38 @@ impl Config {
             }
         };
 
+        // VIEW_TEMPLATE sets both stages; the per-stage variables override it
+        let view_template = env::var("VIEW_TEMPLATE").ok();
+        let stage_template = |var: &str| -> anyhow::Result<ViewTemplate> {
+            match env::var(var).ok().or_else(|| view_template.clone()) {
+                Some(t) => Ok(t.parse()?),
+                None => Ok(ViewTemplate::default()),
+            }
+        };
+
+        let fusion = FusionConfig {
+            method: env::var("HYBRID_FUSION").unwrap_or_default().parse()?,
+            dense_weight: env::var("HYBRID_DENSE_WEIGHT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()?,
+            lexical_weight: env::var("HYBRID_LEXICAL_WEIGHT")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()?,
+        };
+        fusion.validate()?;
+
+        let name_match = NameMatchConfig {
+            boost: env::var("NAME_MATCH_BOOST")
+                .unwrap_or_else(|_| "0.3".to_string())
+                .parse()?,
+            max_distance: env::var("NAME_MATCH_MAX_DISTANCE")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()?,
+        };
+        if !name_match.boost.is_finite() || name_match.boost < 0.0 {
+            anyhow::bail!("NAME_MATCH_BOOST must be a non-negative number");
+        }
+
         Ok(Self {
             host: env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
             port: env::var("PORT")
@@ -118,7 +203,54 @@ impl Config {
             batch_size: env::var("BATCH_SIZE")
                 .unwrap_or_else(|_| "32".to_string())
                 .parse()?,
-            tools_path: env::var("TOOLS_PATH").ok().map(PathBuf::from),
+            // Multiple entries use the platform path separator (':' on Unix, ';' on Windows)
+            tools_paths: env::var_os("TOOLS_PATH")
+                .map(|v| {
+                    env::split_paths(&v)
+                        .filter(|p| !p.as_os_str().is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            tools_format: env::var("TOOLS_FORMAT").unwrap_or_default().parse()?,
+            embedding_view_template: stage_template("EMBEDDING_VIEW_TEMPLATE")?,
+            rerank_view_template: stage_template("RERANK_VIEW_TEMPLATE")?,
+            view_token_budget: env::var("VIEW_TOKEN_BUDGET")
+                .ok()
+                .map(|s| s.parse())
+                .transpose()?,
+            tool_id_separator: match env::var("TOOL_ID_SEPARATOR") {
+                Ok(sep) if sep.is_empty() => anyhow::bail!("TOOL_ID_SEPARATOR cannot be empty"),
+                Ok(sep) => sep,
+                Err(_) => DEFAULT_ID_SEPARATOR.to_string(),
+            },
+            duplicate_policy: env::var("DUPLICATE_TOOLS").unwrap_or_default().parse()?,
+            tools_metadata_path: env::var_os("TOOLS_METADATA_PATH")
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+            tools_overlay_path: PathBuf::from(
+                env::var("TOOLS_OVERLAY_PATH")
+                    .unwrap_or_else(|_| ".encapure/tools_overlay.json".to_string()),
+            ),
+            tools_watch_interval_secs: env::var("TOOLS_WATCH_INTERVAL")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()?,
+            collections_path: PathBuf::from(
+                env::var("COLLECTIONS_PATH")
+                    .unwrap_or_else(|_| ".encapure/collections".to_string()),
+            ),
+            mcp_servers: match env::var("MCP_SERVERS") {
+                Ok(json) if !json.trim().is_empty() => parse_server_list(&json)?,
+                _ => Vec::new(),
+            },
+            mcp_config_path: env::var_os("MCP_CONFIG_PATH")
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+            mcp_timeout_secs: env::var("MCP_TIMEOUT")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            mcp_refresh_interval_secs: env::var("MCP_REFRESH_INTERVAL")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()?,
             bi_encoder_model_path: PathBuf::from(
                 env::var("BI_ENCODER_MODEL_PATH")
                     .unwrap_or_else(|_| "./bi-encoder-model/model_int8.onnx".to_string()),
@@ -130,15 +262,64 @@ impl Config {
             retrieval_candidates: env::var("RETRIEVAL_CANDIDATES")
                 .unwrap_or_else(|_| "20".to_string())
                 .parse()?,
+            vector_index: IndexConfig {
+                kind: env::var("VECTOR_INDEX").unwrap_or_default().parse()?,
+                hnsw: HnswParams {
+                    m: env::var("HNSW_M")
+                        .unwrap_or_else(|_| "16".to_string())
+                        .parse()?,
+                    ef_construction: env::var("HNSW_EF_CONSTRUCTION")
+                        .unwrap_or_else(|_| "200".to_string())
+                        .parse()?,
+                    ef_search: env::var("HNSW_EF_SEARCH")
+                        .unwrap_or_else(|_| "64".to_string())
+                        .parse()?,
+                },
+                precision: env::var("EMBEDDING_PRECISION")
+                    .unwrap_or_default()
+                    .parse()?,
+            },
+            fusion,
+            name_match,
             intra_threads,
             permits,
             embeddings_cache_path: PathBuf::from(
                 env::var("EMBEDDINGS_CACHE_PATH")
                     .unwrap_or_else(|_| ".encapure/embeddings.bin".to_string()),
             ),
+            query_cache_size: env::var("QUERY_CACHE_SIZE")
+                .unwrap_or_else(|_| "1024".to_string())
+                .parse()?,
+            query_cache_path: env::var_os("QUERY_CACHE_PATH")
+                .filter(|p| !p.is_empty())
+                .map(PathBuf::from),
+            search_cache_size: env::var("SEARCH_CACHE_SIZE")
+                .unwrap_or_else(|_| "1024".to_string())
+                .parse()?,
+            search_cache_ttl_secs: env::var("SEARCH_CACHE_TTL")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()?,
         })
     }
 
+    /// Atomizer options derived from the tool ingestion settings.
+    ///
+    /// `token_counter` enforces VIEW_TOKEN_BUDGET when one is configured. The
+    /// TOOLS_METADATA_PATH manifest is loaded separately and set by the caller.
+    pub fn atomize_options(&self, token_counter: Arc<dyn TokenCounter>) -> AtomizeOptions {
+        AtomizeOptions {
+            format: self.tools_format,
+            embedding_template: self.embedding_view_template.clone(),
+            rerank_template: self.rerank_view_template.clone(),
+            token_budget: self
+                .view_token_budget
+                .map(|max_tokens| TokenBudget::new(token_counter, max_tokens)),
+            id_separator: self.tool_id_separator.clone(),
+            duplicate_policy: self.duplicate_policy,
+            metadata: None,
+        }
+    }
+
     /// Returns the operating mode based on current configuration.
     pub fn mode(&self) -> OperatingMode {
         OperatingMode::from_env()
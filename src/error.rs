@@ -22,8 +22,19 @@ pub enum AppError {
     TokenizationError(String),
 
     #[error("Atomization failed: {0}")]
-    #[allow(dead_code)] // Used in ingestion module, will have endpoint soon
     AtomizerError(String),
+
+    #[error("MCP server error: {0}")]
+    McpError(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Internal error: {0}")]
+    InternalError(String),
 }
 
 #[derive(Serialize)]
@@ -55,6 +66,22 @@ impl IntoResponse for AppError {
                 tracing::warn!(error = %msg, "Atomizer error");
                 (StatusCode::BAD_REQUEST, msg.clone())
             }
+            AppError::McpError(msg) => {
+                tracing::error!(error = %msg, "MCP server error");
+                (StatusCode::BAD_GATEWAY, msg.clone())
+            }
+            AppError::NotFound(msg) => {
+                tracing::debug!(error = %msg, "Not found");
+                (StatusCode::NOT_FOUND, msg.clone())
+            }
+            AppError::Conflict(msg) => {
+                tracing::warn!(error = %msg, "Conflict");
+                (StatusCode::CONFLICT, msg.clone())
+            }
+            AppError::InternalError(msg) => {
+                tracing::error!(error = %msg, "Internal error");
+                (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
+            }
         };
 
         let body = Json(ErrorResponse {
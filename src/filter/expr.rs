@@ -0,0 +1,490 @@
+//! Boolean filter expressions over tool fields, tags and metadata.
+//!
+//! # Syntax
+//! ```text
+//! environment == prod and not sensitivity in [pii, phi]
+//! (tags == billing or owner == "data team") and cost_tier != high
+//! ```
+//! - Comparisons: `field == value` (or `=`), `field != value`,
+//!   `field in [a, b]`, `field not in [a, b]`
+//! - Combinators: `and`, `or`, `not` and parentheses; `and` binds tighter than `or`
+//! - Values are bare words or quoted strings (`"..."` or `'...'`); quote
+//!   values containing spaces or keywords. Numbers compare numerically.
+//!
+//! Fields are `id`, `name`, `server`, `kind`, `title`, `tags`, or any key of
+//! the tool's metadata; dotted paths reach nested objects (`owner.team`) and a
+//! `metadata.` prefix reaches keys shadowed by the built-in fields. A field
+//! holding a list (such as `tags`) matches when any element does. Missing
+//! fields never equal anything, so `!=` and `not in` match them.
+
+use crate::error::{AppError, Result};
+use crate::ingestion::EncapureTool;
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExpr {
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    /// `field == value`
+    Eq {
+        field: String,
+        value: String,
+    },
+    /// `field in [values]`
+    In {
+        field: String,
+        values: Vec<String>,
+    },
+}
+
+impl FilterExpr {
+    /// Whether `tool` satisfies the expression.
+    pub fn matches(&self, tool: &EncapureTool) -> bool {
+        match self {
+            Self::And(exprs) => exprs.iter().all(|e| e.matches(tool)),
+            Self::Or(exprs) => exprs.iter().any(|e| e.matches(tool)),
+            Self::Not(expr) => !expr.matches(tool),
+            Self::Eq { field, value } => field_value(tool, field).is_some_and(|f| f.equals(value)),
+            Self::In { field, values } => {
+                field_value(tool, field).is_some_and(|f| values.iter().any(|value| f.equals(value)))
+            }
+        }
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            pos: 0,
+        };
+        let expr = parser.or_expr()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(_) => Err(parser.unexpected("end of expression")),
+        }
+    }
+}
+
+/// The value of a field on one tool.
+enum Field<'a> {
+    Text(&'a str),
+    List(&'a [String]),
+    Json(&'a Value),
+}
+
+impl Field<'_> {
+    fn equals(&self, literal: &str) -> bool {
+        match self {
+            Self::Text(text) => *text == literal,
+            Self::List(items) => items.iter().any(|item| item == literal),
+            Self::Json(value) => json_equals(value, literal),
+        }
+    }
+}
+
+fn field_value<'a>(tool: &'a EncapureTool, field: &str) -> Option<Field<'a>> {
+    if let Some(key) = field.strip_prefix("metadata.") {
+        return lookup(&tool.metadata, key).map(Field::Json);
+    }
+    match field {
+        "id" => Some(Field::Text(&tool.id)),
+        "name" => Some(Field::Text(&tool.name)),
+        "server" => Some(Field::Text(&tool.server_origin)),
+        "kind" => Some(Field::Text(tool.kind.as_str())),
+        "title" => tool.title.as_deref().map(Field::Text),
+        "tags" => Some(Field::List(&tool.tags)),
+        key => lookup(&tool.metadata, key).map(Field::Json),
+    }
+}
+
+/// Find `path` in `map`, trying the whole key before descending at each dot.
+fn lookup<'a>(map: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    if let Some(value) = map.get(path) {
+        return Some(value);
+    }
+    let (head, rest) = path.split_once('.')?;
+    map.get(head)?
+        .as_object()
+        .and_then(|nested| lookup(nested, rest))
+}
+
+fn json_equals(value: &Value, literal: &str) -> bool {
+    match value {
+        Value::String(s) => s == literal,
+        Value::Number(n) => literal.parse::<f64>().ok() == n.as_f64(),
+        Value::Bool(b) => literal == if *b { "true" } else { "false" },
+        Value::Array(items) => items.iter().any(|item| json_equals(item, literal)),
+        Value::Null | Value::Object(_) => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Eq,
+    Ne,
+    /// Unquoted word: a field, a value or a keyword
+    Word(String),
+    /// Quoted string: always a value
+    Quoted(String),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::LParen => "'('".to_string(),
+            Self::RParen => "')'".to_string(),
+            Self::LBracket => "'['".to_string(),
+            Self::RBracket => "']'".to_string(),
+            Self::Comma => "','".to_string(),
+            Self::Eq => "'=='".to_string(),
+            Self::Ne => "'!='".to_string(),
+            Self::Word(w) => format!("'{}'", w),
+            Self::Quoted(q) => format!("\"{}\"", q),
+        }
+    }
+}
+
+fn invalid(message: String) -> AppError {
+    AppError::ValidationError(format!("Invalid filter expression: {}", message))
+}
+
+/// Split an expression into tokens, each paired with its byte offset.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | '[' | ']' | ',' => {
+                chars.next();
+                match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    _ => Token::Comma,
+                }
+            }
+            '=' | '!' => {
+                chars.next();
+                let doubled = chars.next_if(|&(_, next)| next == '=').is_some();
+                match (c, doubled) {
+                    ('=', _) => Token::Eq,
+                    ('!', true) => Token::Ne,
+                    _ => return Err(invalid(format!("expected '!=' at position {}", pos))),
+                }
+            }
+            '"' | '\'' => {
+                chars.next();
+                let unterminated =
+                    || invalid(format!("unterminated string starting at position {}", pos));
+                let mut text = String::new();
+                loop {
+                    match chars.next().ok_or_else(unterminated)? {
+                        (_, '\\') => {
+                            let (_, escaped) = chars.next().ok_or_else(unterminated)?;
+                            text.push(escaped);
+                        }
+                        (_, q) if q == c => break,
+                        (_, other) => text.push(other),
+                    }
+                }
+                Token::Quoted(text)
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some((_, next)) = chars.next_if(|&(_, n)| is_word_char(n)) {
+                    word.push(next);
+                }
+                if word.is_empty() {
+                    return Err(invalid(format!(
+                        "unexpected character '{}' at position {}",
+                        c, pos
+                    )));
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push((pos, token));
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/' | '@' | '+' | '*' | '#')
+}
+
+/// Recursive-descent parser; `and` binds tighter than `or`, `not` tighter than both.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn unexpected(&self, expected: &str) -> AppError {
+        match self.tokens.get(self.pos) {
+            Some((pos, token)) => invalid(format!(
+                "expected {}, found {} at position {}",
+                expected,
+                token.describe(),
+                pos
+            )),
+            None => invalid(format!("expected {}, found end of expression", expected)),
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<FilterExpr> {
+        let mut terms = vec![self.and_expr()?];
+        while self.eat_keyword("or") {
+            terms.push(self.and_expr()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            FilterExpr::Or(terms)
+        })
+    }
+
+    fn and_expr(&mut self) -> Result<FilterExpr> {
+        let mut terms = vec![self.not_expr()?];
+        while self.eat_keyword("and") {
+            terms.push(self.not_expr()?);
+        }
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            FilterExpr::And(terms)
+        })
+    }
+
+    fn not_expr(&mut self) -> Result<FilterExpr> {
+        if self.eat_keyword("not") {
+            return Ok(FilterExpr::Not(Box::new(self.not_expr()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or_expr()?;
+            if self.peek() != Some(&Token::RParen) {
+                return Err(self.unexpected("')'"));
+            }
+            self.pos += 1;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<FilterExpr> {
+        let field = match self.peek() {
+            Some(Token::Word(w)) if !is_reserved(w) => w.clone(),
+            _ => return Err(self.unexpected("a field name")),
+        };
+        self.pos += 1;
+
+        let negated = self.eat_keyword("not");
+        if self.eat_keyword("in") {
+            let values = self.list()?;
+            let expr = FilterExpr::In { field, values };
+            return Ok(if negated {
+                FilterExpr::Not(Box::new(expr))
+            } else {
+                expr
+            });
+        }
+        if negated {
+            return Err(self.unexpected("'in'"));
+        }
+
+        let negated = match self.peek() {
+            Some(Token::Eq) => false,
+            Some(Token::Ne) => true,
+            _ => return Err(self.unexpected("'==', '!=' or 'in'")),
+        };
+        self.pos += 1;
+        let expr = FilterExpr::Eq {
+            field,
+            value: self.value()?,
+        };
+        Ok(if negated {
+            FilterExpr::Not(Box::new(expr))
+        } else {
+            expr
+        })
+    }
+
+    fn list(&mut self) -> Result<Vec<String>> {
+        if self.peek() != Some(&Token::LBracket) {
+            return Err(self.unexpected("'['"));
+        }
+        self.pos += 1;
+
+        let mut values = Vec::new();
+        if self.peek() == Some(&Token::RBracket) {
+            self.pos += 1;
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value()?);
+            match self.next() {
+                Some(Token::Comma) => {}
+                Some(Token::RBracket) => return Ok(values),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected("',' or ']'"));
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<String> {
+        match self.peek() {
+            Some(Token::Quoted(q)) => {
+                let value = q.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            Some(Token::Word(w)) if !is_reserved(w) => {
+                let value = w.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+}
+
+fn is_reserved(word: &str) -> bool {
+    ["and", "or", "not", "in"]
+        .iter()
+        .any(|k| word.eq_ignore_ascii_case(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::ItemKind;
+    use serde_json::json;
+
+    fn tool(name: &str, tags: &[&str], metadata: Value) -> EncapureTool {
+        let mut tool = EncapureTool::new(
+            name.to_string(),
+            "db".to_string(),
+            name.to_string(),
+            json!({ "name": name }),
+        );
+        tool.tags = tags.iter().map(|t| t.to_string()).collect();
+        tool.metadata = metadata.as_object().cloned().unwrap_or_default();
+        tool
+    }
+
+    fn matches(expr: &str, tool: &EncapureTool) -> bool {
+        expr.parse::<FilterExpr>().unwrap().matches(tool)
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expr: FilterExpr = "a == 1 or b == 2 and not c == 3".parse().unwrap();
+        let eq = |field: &str, value: &str| FilterExpr::Eq {
+            field: field.to_string(),
+            value: value.to_string(),
+        };
+        assert_eq!(
+            expr,
+            FilterExpr::Or(vec![
+                eq("a", "1"),
+                FilterExpr::And(vec![eq("b", "2"), FilterExpr::Not(Box::new(eq("c", "3")))]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_equality_and_membership() {
+        let query = tool(
+            "query",
+            &["sql", "read"],
+            json!({ "environment": "prod", "cost_tier": 2, "owner": { "team": "data eng" } }),
+        );
+
+        assert!(matches("environment == prod", &query));
+        assert!(matches("environment = 'prod' and server == db", &query));
+        assert!(!matches("environment != prod", &query));
+        assert!(matches("tags == sql", &query));
+        assert!(matches("tags in [write, read]", &query));
+        assert!(matches("cost_tier in [1, 2.0]", &query));
+        assert!(matches("owner.team == \"data eng\"", &query));
+        assert!(matches("kind == tool AND NOT tags in [admin]", &query));
+        assert!(matches(
+            "(environment == staging or tags == read) and name == query",
+            &query
+        ));
+        assert!(matches("sensitivity not in [pii, phi]", &query));
+        assert!(!matches("sensitivity == pii", &query));
+    }
+
+    #[test]
+    fn test_metadata_prefix_reaches_shadowed_keys() {
+        let mut prompt = tool("triage", &[], json!({ "kind": "workflow" }));
+        prompt.kind = ItemKind::Prompt;
+
+        assert!(matches("kind == prompt", &prompt));
+        assert!(matches("metadata.kind == workflow", &prompt));
+    }
+
+    #[test]
+    fn test_invalid_expressions_are_rejected() {
+        for expr in [
+            "",
+            "environment",
+            "environment ==",
+            "environment == prod and",
+            "(a == 1",
+            "a in [1, 2",
+            "a in 1",
+            "a not == 1",
+            "a == 'unterminated",
+            "a == 'escaped\\",
+            "a ! b",
+            "a == 1 b == 2",
+        ] {
+            let err = expr.parse::<FilterExpr>().unwrap_err();
+            assert!(matches!(err, AppError::ValidationError(_)), "{}", expr);
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Search-time tool filters.
+//!
+//! Filters are applied before stage 1, so excluded tools are never scored by
+//! either model and can never be offered to the agent.
+
+pub mod expr;
+
+pub use expr::FilterExpr;
+
+use crate::ingestion::{EncapureTool, ItemKind};
+
+/// Restrictions on which tools a search may return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolFilter {
+    /// Item kinds to search; empty means every kind
+    pub kinds: Vec<ItemKind>,
+    /// Only tools annotated `readOnlyHint: true`
+    pub read_only: bool,
+    /// Drop tools that may perform destructive updates (see [`EncapureTool::is_destructive`])
+    pub exclude_destructive: bool,
+    /// Servers to search; empty means every server
+    pub servers: Vec<String>,
+    /// Allowlist of qualified ids or bare tool names; empty means every tool
+    pub include_tools: Vec<String>,
+    /// Denylist of qualified ids or bare tool names (wins over `include_tools`)
+    pub exclude_tools: Vec<String>,
+    /// Expression over tool fields, tags and metadata
+    pub expression: Option<FilterExpr>,
+}
+
+impl ToolFilter {
+    /// Whether the filter lets every tool through.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether `tool` passes the filter.
+    pub fn matches(&self, tool: &EncapureTool) -> bool {
+        if !self.kinds.is_empty() && !self.kinds.contains(&tool.kind) {
+            return false;
+        }
+        if self.read_only && !tool.is_read_only() {
+            return false;
+        }
+        if self.exclude_destructive && tool.is_destructive() {
+            return false;
+        }
+        if !self.servers.is_empty() && !self.servers.contains(&tool.server_origin) {
+            return false;
+        }
+        if !self.include_tools.is_empty() && !names_tool(&self.include_tools, tool) {
+            return false;
+        }
+        if names_tool(&self.exclude_tools, tool) {
+            return false;
+        }
+        if let Some(expression) = &self.expression {
+            return expression.matches(tool);
+        }
+        true
+    }
+
+    /// Indices of the tools that pass the filter, or `None` if all of them do.
+    pub fn allowed_indices(&self, tools: &[EncapureTool]) -> Option<Vec<usize>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(
+            tools
+                .iter()
+                .enumerate()
+                .filter(|(_, tool)| self.matches(tool))
+                .map(|(idx, _)| idx)
+                .collect(),
+        )
+    }
+}
+
+/// Whether `entries` lists `tool` by qualified id or by bare name.
+fn names_tool(entries: &[String], tool: &EncapureTool) -> bool {
+    entries.iter().any(|e| *e == tool.id || *e == tool.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::ToolAnnotations;
+    use serde_json::json;
+
+    fn item(name: &str, kind: ItemKind) -> EncapureTool {
+        let mut item = tool(name, None, None);
+        item.kind = kind;
+        item
+    }
+
+    fn tool(name: &str, read_only: Option<bool>, destructive: Option<bool>) -> EncapureTool {
+        let mut tool = EncapureTool::new(
+            name.to_string(),
+            "test".to_string(),
+            name.to_string(),
+            json!({ "name": name }),
+        );
+        tool.annotations = ToolAnnotations {
+            read_only_hint: read_only,
+            destructive_hint: destructive,
+            ..Default::default()
+        };
+        tool
+    }
+
+    fn catalog() -> Vec<EncapureTool> {
+        vec![
+            tool("read_file", Some(true), None),
+            tool("delete_file", Some(false), Some(true)),
+            tool("append_log", Some(false), Some(false)),
+            tool("unannotated", None, None),
+        ]
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        assert_eq!(ToolFilter::default().allowed_indices(&catalog()), None);
+    }
+
+    #[test]
+    fn test_read_only_filter() {
+        let filter = ToolFilter {
+            read_only: true,
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&catalog()), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_exclude_destructive_treats_unannotated_as_destructive() {
+        let filter = ToolFilter {
+            exclude_destructive: true,
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&catalog()), Some(vec![0, 2]));
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let mut items = catalog();
+        items.push(item("summarize", ItemKind::Prompt));
+        items.push(item("file:///readme", ItemKind::Resource));
+
+        let filter = ToolFilter {
+            kinds: vec![ItemKind::Prompt, ItemKind::Resource],
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![4, 5]));
+
+        // Prompts and resources are never destructive
+        let filter = ToolFilter {
+            exclude_destructive: true,
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![0, 2, 4, 5]));
+    }
+
+    #[test]
+    fn test_server_and_tool_lists() {
+        let mut items = catalog();
+        let mut remote = tool("read_file", Some(true), None);
+        remote.server_origin = "remote".to_string();
+        remote.id = "remote::read_file".to_string();
+        items.push(remote);
+
+        let filter = ToolFilter {
+            servers: vec!["remote".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![4]));
+
+        // Bare names match on every server, qualified ids on one
+        let filter = ToolFilter {
+            include_tools: vec!["read_file".to_string(), "test::append_log".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![0, 2, 4]));
+
+        // The denylist wins over the allowlist
+        let filter = ToolFilter {
+            include_tools: vec!["read_file".to_string()],
+            exclude_tools: vec!["remote::read_file".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_expression_combines_with_other_filters() {
+        let mut items = catalog();
+        items[0]
+            .metadata
+            .insert("environment".to_string(), json!("prod"));
+        items[1]
+            .metadata
+            .insert("environment".to_string(), json!("prod"));
+        items[2]
+            .metadata
+            .insert("environment".to_string(), json!("staging"));
+
+        let filter = ToolFilter {
+            expression: Some("environment == prod".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![0, 1]));
+
+        let filter = ToolFilter {
+            read_only: true,
+            ..filter
+        };
+        assert_eq!(filter.allowed_indices(&items), Some(vec![0]));
+    }
+}
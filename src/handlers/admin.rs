@@ -0,0 +1,34 @@
+//! Operator endpoints for managing the running service.
+
+use crate::catalog::CatalogChanges;
+use crate::error::Result;
+use crate::state::AppState;
+use axum::{extract::State, Json};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+
+#[derive(Debug, Serialize)]
+pub struct ReloadResponse {
+    #[serde(flatten)]
+    pub changes: CatalogChanges,
+    /// Number of tools in the catalog after the reload
+    pub total_tools: usize,
+    pub reload_time_ms: u64,
+}
+
+/// POST /admin/reload - Reload TOOLS_PATH and MCP servers into a new catalog.
+///
+/// Responds once the new catalog is live. Searches keep running against the
+/// previous catalog until then; if loading fails the previous catalog stays
+/// in place and the error is returned.
+pub async fn reload_handler(State(state): State<Arc<AppState>>) -> Result<Json<ReloadResponse>> {
+    let start = Instant::now();
+    let changes = state.reload("admin endpoint").await?;
+
+    Ok(Json(ReloadResponse {
+        changes,
+        total_tools: state.catalog().len(),
+        reload_time_ms: start.elapsed().as_millis() as u64,
+    }))
+}
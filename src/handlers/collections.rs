@@ -0,0 +1,89 @@
+//! Named collection handlers.
+//!
+//! Collections are separate tool catalogs for teams sharing one deployment.
+//! They are searched with the same two-stage pipeline (and model session
+//! pools) as the default catalog.
+
+use crate::collections::{CollectionSpec, CollectionSummary};
+use crate::error::{AppError, Result};
+use crate::handlers::search::{search_catalog, SearchRequest, SearchResponse};
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize)]
+pub struct CollectionsResponse {
+    pub collections: Vec<CollectionSummary>,
+}
+
+/// GET /collections - List every collection.
+pub async fn list_collections_handler(
+    State(state): State<Arc<AppState>>,
+) -> Json<CollectionsResponse> {
+    Json(CollectionsResponse {
+        collections: state.collections.list(),
+    })
+}
+
+/// POST /collections - Create a collection from one or more tool documents.
+///
+/// Every tool is embedded before the collection becomes searchable. Responds
+/// 409 Conflict if the name is taken.
+pub async fn create_collection_handler(
+    State(state): State<Arc<AppState>>,
+    Json(spec): Json<CollectionSpec>,
+) -> Result<(StatusCode, Json<CollectionSummary>)> {
+    let summary = tokio::task::spawn_blocking(move || {
+        state
+            .collections
+            .create(spec, &state.atomize_options, |views| {
+                state.encode_tool_views(views)
+            })
+    })
+    .await
+    .map_err(|e| AppError::ResourceError(format!("Collection task join error: {}", e)))??;
+
+    tracing::info!(
+        collection = %summary.name,
+        tools = summary.tools,
+        "Collection created"
+    );
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+/// DELETE /collections/{name} - Delete a collection and its files.
+pub async fn delete_collection_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode> {
+    let deleted = state.collections.delete(&name)?;
+    state.search_cache.invalidate_catalog(&deleted.catalog);
+    tokio::task::spawn_blocking(move || deleted.remove_files())
+        .await
+        .map_err(|e| AppError::InternalError(format!("Collection task join error: {}", e)))?;
+    tracing::info!(collection = %name, "Collection deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /collections/{name}/search - Search one collection's tools.
+///
+/// Takes the same request as `POST /search`.
+pub async fn collection_search_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(request): Json<SearchRequest>,
+) -> Result<Json<SearchResponse>> {
+    request.validate()?;
+
+    let catalog = state
+        .collections
+        .get(&name)
+        .ok_or_else(|| AppError::NotFound(format!("Collection '{}' not found", name)))?;
+
+    search_catalog(&state, catalog, &request).await.map(Json)
+}
@@ -0,0 +1,90 @@
+//! Ingestion dry-run handler.
+//!
+//! Lets operators check a tool document (MCP response, OpenAI/Anthropic tool
+//! list, or OpenAPI spec) before adding it to TOOLS_PATH. The document is
+//! atomized with the running configuration, but the loaded catalog is never
+//! modified.
+
+use crate::error::{AppError, Result};
+use crate::ingestion::openapi::parse_spec;
+use crate::ingestion::{validate_document, ToolFormat, ValidationReport};
+use crate::state::AppState;
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Default server name for validated documents
+fn default_server() -> String {
+    "unknown".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateRequest {
+    /// Server name the tools would be loaded under (default: "unknown")
+    #[serde(default = "default_server")]
+    pub server: String,
+    /// Override the configured TOOLS_FORMAT for this document
+    #[serde(default)]
+    pub format: Option<ToolFormat>,
+    /// The tool document: a JSON value, or JSON/YAML text (e.g. an OpenAPI spec)
+    pub document: Value,
+}
+
+/// POST /ingest/validate - Dry-run atomization of a tool document.
+///
+/// Returns the tools that would be ingested with their inference and
+/// embedding views, skipped definitions with index and reason, and warnings.
+/// Tool ids that already exist in the loaded catalog are reported as warnings.
+pub async fn validate_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<ValidateRequest>,
+) -> Result<Json<ValidationReport>> {
+    if request.server.is_empty() {
+        return Err(AppError::ValidationError(
+            "server cannot be empty".to_string(),
+        ));
+    }
+
+    let document = match request.document {
+        Value::String(text) => parse_spec(&text).map_err(|_| {
+            AppError::ValidationError("document text is neither valid JSON nor YAML".to_string())
+        })?,
+        other => other,
+    };
+
+    let mut options = state.atomize_options.clone();
+    if let Some(format) = request.format {
+        options.format = format;
+    }
+
+    // Token budgets tokenize every view, so keep this off the async runtime
+    let server = request.server;
+    let mut report =
+        tokio::task::spawn_blocking(move || validate_document(&document, &server, &options))
+            .await
+            .map_err(|e| AppError::InternalError(format!("Validation task join error: {}", e)))??;
+
+    let catalog = state.catalog();
+    let loaded: HashSet<&str> = catalog.tools.iter().map(|t| t.id.as_str()).collect();
+    for tool in &report.tools {
+        if loaded.contains(tool.id.as_str()) {
+            report.warnings.push(format!(
+                "Tool id '{}' is already in the loaded catalog",
+                tool.id
+            ));
+        }
+    }
+
+    tracing::info!(
+        server = %report.server,
+        total = report.total,
+        parsed = report.tools.len(),
+        skipped = report.skipped.len(),
+        warnings = report.warnings.len(),
+        "Ingestion dry-run completed"
+    );
+
+    Ok(Json(report))
+}
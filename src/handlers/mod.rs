@@ -1,7 +1,18 @@
+pub mod admin;
+pub mod collections;
 pub mod health;
+pub mod ingest;
 pub mod rerank;
 pub mod search;
+pub mod tools;
 
+pub use admin::reload_handler;
+pub use collections::{
+    collection_search_handler, create_collection_handler, delete_collection_handler,
+    list_collections_handler,
+};
 pub use health::{health_handler, ready_handler};
+pub use ingest::validate_handler;
 pub use rerank::rerank_handler;
 pub use search::search_handler;
+pub use tools::{create_tool_handler, delete_tool_handler, get_tool_handler, update_tool_handler};
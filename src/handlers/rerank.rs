@@ -112,7 +112,7 @@ pub async fn rerank_handler(
     // Apply sigmoid and create ranked results
     let mut results: Vec<RankedDocument> = scores
         .into_iter()
-        .zip(request.documents.into_iter())
+        .zip(request.documents)
         .enumerate()
         .map(|(index, (logit, document))| RankedDocument {
             index,
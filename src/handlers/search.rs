@@ -3,17 +3,24 @@
 //! This handler enables AI agents to discover relevant tools by semantic matching
 //! against a query. Uses a two-stage retrieval architecture for performance:
 //!
-//! **Stage 1 (Bi-encoder)**: Fast cosine similarity search using pre-computed embeddings
+//! **Stage 1 (Bi-encoder)**: Fast cosine similarity search over pre-computed embeddings,
+//! by exact scan or through an HNSW index (see [`crate::index`]), run alongside a
+//! BM25 lexical search; the two candidate lists are merged by rank fusion, and
+//! tools whose name the query matches are always added
 //! **Stage 2 (Cross-encoder)**: Accurate reranking on top candidates only
 //!
 //! This reduces latency from O(n × inference) to O(1 + k × inference) where k << n.
 
+use crate::cache::CacheControl;
+use crate::catalog::Catalog;
 use crate::error::{AppError, Result};
-use crate::inference::BiEncoderModel;
+use crate::filter::{FilterExpr, ToolFilter};
+use crate::index::{FusionConfig, FusionMethod, NameMatchKind};
+use crate::ingestion::{ItemKind, ToolAnnotations};
 use crate::state::AppState;
 use axum::{extract::State, Json};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -33,20 +40,195 @@ pub struct SearchRequest {
     /// When provided, biases results toward tools relevant to the agent's role.
     #[serde(default)]
     pub agent_description: Option<String>,
+    /// Item kinds to search (tool, prompt, resource, resource_template).
+    /// Empty (default) returns a mixed ranking of every kind.
+    #[serde(default)]
+    pub kinds: Vec<ItemKind>,
+    /// Only return tools annotated as read-only (`readOnlyHint: true`)
+    #[serde(default)]
+    pub read_only: bool,
+    /// Never return tools that may perform destructive updates.
+    /// Tools without annotations are treated as destructive (MCP default).
+    #[serde(default)]
+    pub exclude_destructive: bool,
+    /// Only search tools from these servers (e.g. the servers the agent has connected).
+    /// Empty (default) searches every server.
+    #[serde(default)]
+    pub servers: Vec<String>,
+    /// Only search these tools, by qualified id ("github::create_issue") or bare name
+    #[serde(default)]
+    pub include_tools: Vec<String>,
+    /// Never return these tools, by qualified id or bare name
+    #[serde(default)]
+    pub exclude_tools: Vec<String>,
+    /// Filter expression over tool fields, tags and metadata,
+    /// e.g. `environment == prod and not sensitivity in [pii, phi]`
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// How dense and lexical candidates are merged: `rrf` or `weighted`
+    /// (default: HYBRID_FUSION)
+    #[serde(default)]
+    pub fusion: Option<FusionMethod>,
+    /// Weight of the bi-encoder ranking; 0 disables it (default: HYBRID_DENSE_WEIGHT)
+    #[serde(default)]
+    pub dense_weight: Option<f32>,
+    /// Weight of the BM25 ranking; 0 disables it (default: HYBRID_LEXICAL_WEIGHT)
+    #[serde(default)]
+    pub lexical_weight: Option<f32>,
+    /// `no-cache` always searches (and caches the answer); `no-store` always
+    /// searches and leaves the response cache untouched
+    #[serde(default)]
+    pub cache_control: CacheControl,
+}
+
+impl SearchRequest {
+    /// Check the query, result count and filter expression.
+    pub fn validate(&self) -> Result<()> {
+        if self.query.is_empty() {
+            return Err(AppError::ValidationError(
+                "Query cannot be empty".to_string(),
+            ));
+        }
+
+        if self.top_k == 0 {
+            return Err(AppError::ValidationError(
+                "top_k must be at least 1".to_string(),
+            ));
+        }
+
+        self.filter()?;
+
+        Ok(())
+    }
+
+    /// Rank fusion settings: `defaults` overridden by the request's own.
+    ///
+    /// # Errors
+    /// Returns `AppError::ValidationError` if a weight is negative or both are 0.
+    pub fn fusion(&self, defaults: FusionConfig) -> Result<FusionConfig> {
+        let fusion = FusionConfig {
+            method: self.fusion.unwrap_or(defaults.method),
+            dense_weight: self.dense_weight.unwrap_or(defaults.dense_weight),
+            lexical_weight: self.lexical_weight.unwrap_or(defaults.lexical_weight),
+        };
+        fusion.validate()?;
+        Ok(fusion)
+    }
+
+    /// Response cache key: every field that affects the answer, normalized so
+    /// equivalent requests share it. `fusion` is the resolved fusion config.
+    pub fn cache_key(&self, fusion: FusionConfig) -> SearchCacheKey {
+        let mut kinds: Vec<&'static str> = self.kinds.iter().map(ItemKind::as_str).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        SearchCacheKey {
+            query: normalize_text(&self.query),
+            agent_description: self.agent_description.as_deref().map(normalize_text),
+            top_k: self.top_k,
+            kinds,
+            read_only: self.read_only,
+            exclude_destructive: self.exclude_destructive,
+            servers: sorted_set(&self.servers),
+            include_tools: sorted_set(&self.include_tools),
+            exclude_tools: sorted_set(&self.exclude_tools),
+            filter: self.filter.as_deref().map(|f| f.trim().to_string()),
+            fusion: fusion.method,
+            dense_weight: fusion.dense_weight.to_bits(),
+            lexical_weight: fusion.lexical_weight.to_bits(),
+        }
+    }
+
+    /// Tool restrictions requested by the caller.
+    ///
+    /// # Errors
+    /// Returns `AppError::ValidationError` if the filter expression is invalid.
+    pub fn filter(&self) -> Result<ToolFilter> {
+        Ok(ToolFilter {
+            kinds: self.kinds.clone(),
+            read_only: self.read_only,
+            exclude_destructive: self.exclude_destructive,
+            servers: self.servers.clone(),
+            include_tools: self.include_tools.clone(),
+            exclude_tools: self.exclude_tools.clone(),
+            expression: self
+                .filter
+                .as_deref()
+                .map(str::parse::<FilterExpr>)
+                .transpose()?,
+        })
+    }
+}
+
+/// Normalized form of a [`SearchRequest`], for the response cache.
+///
+/// Queries and agent descriptions are compared case-insensitively with runs
+/// of whitespace collapsed; tool, server and kind lists as sets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchCacheKey {
+    query: String,
+    agent_description: Option<String>,
+    top_k: usize,
+    kinds: Vec<&'static str>,
+    read_only: bool,
+    exclude_destructive: bool,
+    servers: Vec<String>,
+    include_tools: Vec<String>,
+    exclude_tools: Vec<String>,
+    filter: Option<String>,
+    fusion: FusionMethod,
+    /// Weights by bit pattern (f32 is not `Eq`)
+    dense_weight: u32,
+    lexical_weight: u32,
+}
+
+/// Lowercased, with whitespace runs collapsed to one space.
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-#[derive(Debug, Serialize)]
+fn sorted_set(values: &[String]) -> Vec<String> {
+    let mut values = values.to_vec();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct SearchResponse {
     pub results: Vec<SearchResult>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
-    /// The tool name
+    /// Qualified tool id, unique across servers (e.g. "github::create_issue")
+    pub id: String,
+    /// Item kind: tool, prompt, resource or resource_template
+    pub kind: ItemKind,
+    /// The tool name as published by its server
     pub name: String,
+    /// The server the tool came from
+    pub server: String,
+    /// Human-readable display name, if the tool declares one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Behavior hints declared by the server (readOnlyHint, destructiveHint, ...)
+    pub annotations: ToolAnnotations,
+    /// Free-form labels from the definition and the metadata manifest
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Tool metadata (owner, environment, cost tier, ...)
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
     /// Relevance score (0.0 to 1.0, higher is more relevant)
     pub score: f32,
-    /// The original MCP tool definition (for agent execution)
+    /// Set when the query is, or is close to, this tool's name
+    /// (`exact`, `tokens` or `fuzzy`); the score includes the name-match boost
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_match: Option<NameMatchKind>,
+    /// The original MCP definition: tool, prompt or resource (for agent execution)
     pub raw_definition: Value,
 }
 
@@ -56,41 +238,78 @@ pub struct SearchResult {
 ///
 /// # Two-Stage Retrieval Flow
 /// 1. **Validation**: Check query non-empty, top_k > 0
-/// 2. **Stage 1 (Bi-encoder)**: Compute query embedding, cosine similarity with
-///    pre-computed tool embeddings, retrieve top-N candidates (fast: ~10ms)
+/// 2. **Stage 1 (Bi-encoder + BM25)**: Compute query embedding and search the
+///    catalog's vector index while the lexical index is searched in parallel,
+///    then fuse both rankings into the top-N candidates (fast: ~10ms); tools
+///    whose name matches the query are added to the candidates
 /// 3. **Stage 2 (Cross-encoder)**: Run reranker only on N candidates (accurate)
-/// 4. Apply sigmoid, sort descending, return top-K results
+/// 4. Apply sigmoid and the name-match boost, sort descending, return top-K results
+///
+/// Responses are cached per catalog snapshot (see [`crate::cache::ResponseCache`]);
+/// a repeated request skips both stages unless it sets `cache_control`.
 ///
 /// This reduces latency from O(n × inference) to O(1 + k × inference).
 pub async fn search_handler(
     State(state): State<Arc<AppState>>,
     Json(request): Json<SearchRequest>,
 ) -> Result<Json<SearchResponse>> {
-    let start_time = std::time::Instant::now();
+    request.validate()?;
+
+    // Pin one catalog snapshot for the whole request (indices are per snapshot)
+    let catalog = state.catalog();
 
-    // Validation
-    if request.query.is_empty() {
+    // Check if tools are loaded
+    if catalog.is_empty() {
         return Err(AppError::ValidationError(
-            "Query cannot be empty".to_string(),
+            "No tools loaded. Set TOOLS_PATH or configure MCP servers.".to_string(),
         ));
     }
 
-    if request.top_k == 0 {
-        return Err(AppError::ValidationError(
-            "top_k must be at least 1".to_string(),
-        ));
+    search_catalog(&state, catalog, &request).await.map(Json)
+}
+
+/// Run the two-stage search for a validated `request` against one catalog.
+///
+/// The caller pins the catalog snapshot and rejects empty catalogs; the model
+/// session pools and semaphore are shared by every catalog.
+pub async fn search_catalog(
+    state: &AppState,
+    catalog: Arc<Catalog>,
+    request: &SearchRequest,
+) -> Result<SearchResponse> {
+    let start_time = std::time::Instant::now();
+    let tools = &catalog.tools;
+    let fusion = request.fusion(state.config.fusion)?;
+
+    // Whole answers repeat: serve them from the cache while the catalog is unchanged
+    let cache_key = state
+        .search_cache
+        .is_enabled()
+        .then(|| request.cache_key(fusion));
+    if let (Some(key), true) = (&cache_key, request.cache_control.reads()) {
+        if let Some(response) = state.search_cache.get(&catalog, key) {
+            let total_time = start_time.elapsed();
+            tracing::info!(
+                query = %request.query,
+                total_ms = total_time.as_millis(),
+                "Search completed (cached)"
+            );
+            metrics::counter!("search_requests_total").increment(1);
+            metrics::histogram!("search_latency_ms").record(total_time.as_millis() as f64);
+            return Ok(response);
+        }
     }
 
-    // Check if tools are loaded
-    if state.tools.is_empty() {
-        return Err(AppError::ValidationError(
-            "No tools loaded. Set TOOLS_PATH environment variable.".to_string(),
-        ));
+    // Restrict the searchable tools before stage 1 (None = all tools allowed)
+    let allowed = request.filter()?.allowed_indices(tools);
+    let searchable = allowed.as_ref().map_or(tools.len(), |a| a.len());
+    if searchable == 0 {
+        tracing::info!(query = %request.query, "No tools match the search filters");
+        return Ok(SearchResponse { results: Vec::new() });
     }
 
-    let tools = Arc::clone(&state.tools);
-    let top_k = request.top_k.min(tools.len());
-    let retrieval_candidates = state.config.retrieval_candidates.min(tools.len());
+    let top_k = request.top_k.min(searchable);
+    let retrieval_candidates = state.config.retrieval_candidates.min(searchable);
 
     // Context injection: prepend agent description to query for context-aware search
     let effective_query = match &request.agent_description {
@@ -99,58 +318,78 @@ pub async fn search_handler(
     };
 
     // =========================================================================
-    // STAGE 1: Bi-encoder fast retrieval (cosine similarity)
+    // STAGE 1: Bi-encoder fast retrieval (cosine similarity) + BM25
     // =========================================================================
 
-    // Acquire bi-encoder session BEFORE spawn_blocking (lock-free pool access)
-    let bi_encoder_session_idx = state.bi_encoder.acquire_session()?;
-
-    let bi_encoder = Arc::clone(&state.bi_encoder);
-    let tool_embeddings = Arc::clone(&state.tool_embeddings);
-    let query_for_biencoder = effective_query.clone();
-
-    // Compute query embedding (fast - single forward pass)
-    let stage1_result = tokio::task::spawn_blocking(move || {
-        let t0 = std::time::Instant::now();
-        let query_embedding = bi_encoder.encode_with_session(bi_encoder_session_idx, &query_for_biencoder)?;
-        let encode_time = t0.elapsed();
-
-        // Compute cosine similarities with all pre-computed tool embeddings
-        let t1 = std::time::Instant::now();
-        let similarities = BiEncoderModel::cosine_similarity(&query_embedding, &tool_embeddings);
-        let similarity_time = t1.elapsed();
-
-        // Get top-N candidate indices
-        let t2 = std::time::Instant::now();
-        let mut indexed_sims: Vec<(usize, f32)> =
-            similarities.into_iter().enumerate().collect();
-        indexed_sims.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-        let candidates: Vec<usize> = indexed_sims
-            .into_iter()
-            .take(retrieval_candidates)
-            .map(|(idx, _)| idx)
-            .collect();
-        let sort_time = t2.elapsed();
-
-        tracing::info!(
-            encode_ms = encode_time.as_millis(),
-            similarity_ms = similarity_time.as_millis(),
-            sort_ms = sort_time.as_millis(),
-            num_tools = tool_embeddings.nrows(),
-            "Stage 1 breakdown"
-        );
+    // Tools named by the query are reranked whatever their stage-1 rank
+    let name_config = state.config.name_match;
+    let name_matches = catalog.names.lookup(
+        &request.query,
+        name_config.max_distance,
+        allowed.as_deref(),
+    );
 
-        Ok::<Vec<usize>, AppError>(candidates)
-    })
-    .await;
+    // Lexical search runs on the raw query, in parallel with query encoding
+    let lexical_task = fusion.uses_lexical().then(|| {
+        let catalog_for_lexical = Arc::clone(&catalog);
+        let query_for_lexical = request.query.clone();
+        let allowed = allowed.clone();
+        tokio::task::spawn_blocking(move || {
+            let t0 = std::time::Instant::now();
+            let results = catalog_for_lexical.lexical.search(
+                &query_for_lexical,
+                retrieval_candidates,
+                allowed.as_deref(),
+            );
+            tracing::debug!(
+                lexical_ms = t0.elapsed().as_millis(),
+                matches = results.len(),
+                "Lexical search completed"
+            );
+            results
+        })
+    });
 
-    // Release bi-encoder session after blocking task completes
-    state.bi_encoder.release_session(bi_encoder_session_idx);
+    let dense_candidates = if fusion.uses_dense() {
+        dense_search(
+            state,
+            &catalog,
+            effective_query.clone(),
+            retrieval_candidates,
+            allowed,
+            searchable,
+        )
+        .await?
+    } else {
+        Vec::new()
+    };
+    let lexical_candidates = match lexical_task {
+        Some(task) => task
+            .await
+            .map_err(|e| AppError::ModelError(format!("Lexical search task join error: {}", e)))?,
+        None => Vec::new(),
+    };
 
-    // Handle the result
-    let stage1_result = stage1_result
-        .map_err(|e| AppError::ModelError(format!("Stage 1 task join error: {}", e)))??;
+    let mut stage1_result: Vec<usize> = fusion
+        .fuse(&dense_candidates, &lexical_candidates, retrieval_candidates)
+        .into_iter()
+        .map(|(idx, _)| idx)
+        .collect();
+    tracing::debug!(
+        fusion = fusion.method.as_str(),
+        dense = dense_candidates.len(),
+        lexical = lexical_candidates.len(),
+        fused = stage1_result.len(),
+        "Stage 1 candidates fused"
+    );
+    for name_match in &name_matches {
+        if !stage1_result.contains(&name_match.row) {
+            stage1_result.push(name_match.row);
+        }
+    }
+    if !name_matches.is_empty() {
+        tracing::debug!(name_matches = name_matches.len(), "Tool-name matches added");
+    }
 
     let stage1_time = start_time.elapsed();
     tracing::debug!(
@@ -186,7 +425,7 @@ pub async fn search_handler(
     let query = effective_query.clone();  // Use context-injected query for reranking
     let batch_size = state.config.batch_size;
     let candidate_indices = stage1_result;
-    let tools_for_rerank = Arc::clone(&tools);
+    let catalog_for_rerank = Arc::clone(&catalog);
 
     // Run cross-encoder only on candidate tools
     let result = tokio::task::spawn_blocking(move || {
@@ -195,7 +434,7 @@ pub async fn search_handler(
         // Get inference views only for candidate tools
         let documents: Vec<String> = candidate_indices
             .iter()
-            .map(|&idx| tools_for_rerank[idx].inference_view.clone())
+            .map(|&idx| catalog_for_rerank.tools[idx].inference_view.clone())
             .collect();
         let doc_collect_time = t0.elapsed();
 
@@ -226,7 +465,7 @@ pub async fn search_handler(
         // Map scores back to original tool indices
         let scored_candidates: Vec<(usize, f32)> = candidate_indices
             .into_iter()
-            .zip(all_scores.into_iter())
+            .zip(all_scores)
             .collect();
 
         tracing::info!(
@@ -256,10 +495,17 @@ pub async fn search_handler(
         "Stage 2 (cross-encoder) completed"
     );
 
-    // Apply sigmoid and sort by score descending
+    // Apply sigmoid and the name-match boost, then sort by score descending
+    let name_match = |idx: usize| name_matches.iter().find(|m| m.row == idx);
     let mut final_scores: Vec<(usize, f32)> = scored_candidates
         .into_iter()
-        .map(|(idx, logit)| (idx, sigmoid(logit)))
+        .map(|(idx, logit)| {
+            let score = match name_match(idx) {
+                Some(m) => (sigmoid(logit) + name_config.boost * m.strength).min(1.0),
+                None => sigmoid(logit),
+            };
+            (idx, score)
+        })
         .collect();
 
     final_scores.sort_by(|a, b| {
@@ -272,10 +518,18 @@ pub async fn search_handler(
         .into_iter()
         .take(top_k)
         .map(|(idx, score)| {
-            let tool = &state.tools[idx];
+            let tool = &tools[idx];
             SearchResult {
+                id: tool.id.clone(),
+                kind: tool.kind,
                 name: tool.name.clone(),
+                server: tool.server_origin.clone(),
+                title: tool.title.clone(),
+                annotations: tool.annotations.clone(),
+                tags: tool.tags.clone(),
+                metadata: tool.metadata.clone(),
                 score,
+                name_match: name_match(idx).map(|m| m.kind),
                 raw_definition: tool.raw_definition.clone(),
             }
         })
@@ -302,7 +556,84 @@ pub async fn search_handler(
     metrics::counter!("search_requests_total").increment(1);
     metrics::histogram!("search_latency_ms").record(total_time.as_millis() as f64);
 
-    Ok(Json(response))
+    if let (Some(key), true) = (cache_key, request.cache_control.writes()) {
+        state.search_cache.insert(&catalog, key, response.clone());
+    }
+
+    Ok(response)
+}
+
+/// Encode `query` with the bi-encoder (or take its embedding from the query
+/// cache) and search the catalog's vector index for the `k` nearest searchable
+/// tools.
+async fn dense_search(
+    state: &AppState,
+    catalog: &Arc<Catalog>,
+    query: String,
+    k: usize,
+    allowed: Option<Vec<usize>>,
+    searchable: usize,
+) -> Result<Vec<(usize, f32)>> {
+    let cached = state.query_cache.get(&query);
+    let cache_hit = cached.is_some();
+
+    // Acquire bi-encoder session BEFORE spawn_blocking (lock-free pool access)
+    let bi_encoder_session_idx = match cached {
+        Some(_) => None,
+        None => Some(state.bi_encoder.acquire_session()?),
+    };
+
+    let bi_encoder = Arc::clone(&state.bi_encoder);
+    let catalog_for_biencoder = Arc::clone(catalog);
+
+    // Compute query embedding (fast - single forward pass), unless cached
+    let result = tokio::task::spawn_blocking(move || {
+        let t0 = std::time::Instant::now();
+        let query_embedding = match (cached, bi_encoder_session_idx) {
+            (Some(embedding), _) => embedding,
+            (None, session_idx) => bi_encoder.encode_with_session(
+                session_idx.expect("a session is acquired on every cache miss"),
+                &query,
+            )?,
+        };
+        let encode_time = t0.elapsed();
+
+        // Nearest neighbours among the searchable tools (exact scan or HNSW)
+        let t1 = std::time::Instant::now();
+        let index = &catalog_for_biencoder.index;
+        let candidates = index.search(
+            &catalog_for_biencoder.embeddings,
+            &query_embedding,
+            k,
+            allowed.as_deref(),
+        );
+        let search_time = t1.elapsed();
+
+        tracing::info!(
+            encode_ms = encode_time.as_millis(),
+            cache_hit,
+            search_ms = search_time.as_millis(),
+            index = index.kind().as_str(),
+            num_tools = catalog_for_biencoder.len(),
+            searchable,
+            "Stage 1 breakdown"
+        );
+
+        Ok::<_, AppError>((candidates, (!cache_hit).then_some((query, query_embedding))))
+    })
+    .await;
+
+    // Release bi-encoder session after blocking task completes
+    if let Some(session_idx) = bi_encoder_session_idx {
+        state.bi_encoder.release_session(session_idx);
+    }
+
+    let (candidates, encoded) =
+        result.map_err(|e| AppError::ModelError(format!("Stage 1 task join error: {}", e)))??;
+    if let Some((query, embedding)) = encoded {
+        state.query_cache.insert(query, embedding);
+    }
+    Ok(candidates)
 }
 
 /// Sigmoid activation: 1 / (1 + e^-x)
@@ -0,0 +1,82 @@
+//! Tool management handlers.
+//!
+//! Register, replace, inspect or retire single tools without rewriting the
+//! catalog files. Changes are embedded incrementally, swapped into the live
+//! catalog, and persisted to the tools overlay (TOOLS_OVERLAY_PATH).
+
+use crate::error::{AppError, Result};
+use crate::ingestion::EncapureTool;
+use crate::persistence::OverlayTool;
+use crate::state::AppState;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+/// GET /tools/{id} - Fetch a tool from the live catalog.
+pub async fn get_tool_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<EncapureTool>> {
+    state
+        .catalog()
+        .tools
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound(format!("Tool '{}' not found", id)))
+}
+
+/// POST /tools - Register a new tool.
+///
+/// Responds 409 Conflict if a tool with the same id is already loaded.
+pub async fn create_tool_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<OverlayTool>,
+) -> Result<(StatusCode, Json<EncapureTool>)> {
+    let tool = run_blocking(move || state.create_tool(request)).await?;
+    tracing::info!(id = %tool.id, "Tool registered");
+    Ok((StatusCode::CREATED, Json(tool)))
+}
+
+/// PUT /tools/{id} - Create or replace a tool.
+///
+/// The definition must atomize to `id` (same server and name).
+pub async fn update_tool_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(request): Json<OverlayTool>,
+) -> Result<(StatusCode, Json<EncapureTool>)> {
+    let (tool, created) = run_blocking(move || state.replace_tool(&id, request)).await?;
+    tracing::info!(id = %tool.id, created, "Tool updated");
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(tool)))
+}
+
+/// DELETE /tools/{id} - Remove a tool from the catalog.
+///
+/// Tools from TOOLS_PATH or MCP servers stay removed across reloads.
+pub async fn delete_tool_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode> {
+    let tool = run_blocking(move || state.delete_tool(&id)).await?;
+    tracing::info!(id = %tool.id, "Tool deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Catalog writes embed with the bi-encoder, so keep them off the async runtime.
+async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T> + Send + 'static,
+) -> Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::ResourceError(format!("Tool update task join error: {}", e)))?
+}
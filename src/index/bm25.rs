@@ -0,0 +1,236 @@
+//! BM25 lexical index over tool names, descriptions and parameter names.
+//!
+//! The bi-encoder is weak on exact identifiers users type verbatim (`kubectl`,
+//! `s3`, `jql`, product names). A [`Bm25Index`] is built with every catalog
+//! and searched alongside the vector index; the two candidate lists are
+//! merged by [`fusion`](crate::index::fusion) before reranking.
+//!
+//! Text is split on non-alphanumeric characters and camelCase boundaries and
+//! lowercased, so `list_s3_buckets` and `listS3Buckets` both index `list`,
+//! `s3` and `buckets`. Name tokens are counted twice to favour tools whose
+//! name matches over tools that only mention the term.
+
+use crate::index::kernel::TopK;
+use crate::ingestion::atomizer::{tool_parts, ToolFormat};
+use crate::ingestion::EncapureTool;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Term frequency saturation.
+const K1: f32 = 1.2;
+
+/// Document length normalization.
+const B: f32 = 0.75;
+
+/// Words too common in queries and descriptions to carry any signal.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "do", "for", "from", "how", "i", "in",
+    "is", "it", "me", "my", "of", "on", "or", "the", "this", "to", "use", "want", "we", "what",
+    "with", "you", "your",
+];
+
+/// Inverted index with BM25 scoring.
+#[derive(Debug, Clone, Default)]
+pub struct Bm25Index {
+    /// Term -> (row, term frequency) for every row containing it
+    postings: HashMap<String, Vec<(u32, u32)>>,
+    /// Token count of each row
+    lengths: Vec<u32>,
+    average_length: f32,
+}
+
+impl Bm25Index {
+    /// Index the name, title, description and parameter names of `tools`.
+    pub fn build(tools: &[EncapureTool]) -> Self {
+        let mut postings: HashMap<String, Vec<(u32, u32)>> = HashMap::new();
+        let mut lengths = Vec::with_capacity(tools.len());
+        for (row, tool) in tools.iter().enumerate() {
+            let tokens = document_tokens(tool);
+            lengths.push(tokens.len() as u32);
+
+            let mut counts: HashMap<String, u32> = HashMap::new();
+            for token in tokens {
+                *counts.entry(token).or_default() += 1;
+            }
+            for (term, count) in counts {
+                postings.entry(term).or_default().push((row as u32, count));
+            }
+        }
+        let total: u64 = lengths.iter().map(|&l| l as u64).sum();
+        let average_length = total as f32 / lengths.len().max(1) as f32;
+        Self {
+            postings,
+            lengths,
+            average_length,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lengths.is_empty()
+    }
+
+    /// The `k` rows scoring highest for `query`, best first.
+    ///
+    /// Only rows listed in `allowed` (sorted ascending) are returned; rows
+    /// sharing no term with the query are never returned.
+    pub fn search(&self, query: &str, k: usize, allowed: Option<&[usize]>) -> Vec<(usize, f32)> {
+        let mut terms = tokenize(query);
+        terms.sort_unstable();
+        terms.dedup();
+
+        let documents = self.len() as f32;
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let frequency = postings.len() as f32;
+            let idf = ((documents - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+            for &(row, count) in postings {
+                if allowed.is_some_and(|rows| rows.binary_search(&(row as usize)).is_err()) {
+                    continue;
+                }
+                let tf = count as f32;
+                let length = self.lengths[row as usize] as f32 / self.average_length.max(1.0);
+                let score = idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length));
+                *scores.entry(row).or_default() += score;
+            }
+        }
+
+        let mut top = TopK::new(k.min(scores.len()));
+        for (row, score) in scores {
+            top.push(row as usize, score);
+        }
+        top.into_sorted_vec()
+    }
+}
+
+/// Lowercased terms of `text`, split on non-alphanumeric characters and
+/// camelCase boundaries, without stopwords and single letters.
+pub fn tokenize(text: &str) -> Vec<String> {
+    split_words(text)
+        .into_iter()
+        .filter(|t| t.chars().count() > 1 && !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
+
+/// Lowercased words of `text`, split on non-alphanumeric characters and
+/// camelCase boundaries (`getHTTPResponse` -> `get`, `http`, `response`).
+pub fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars: Vec<char> = word.chars().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (previous, current) = (chars[i - 1], chars[i]);
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            let boundary = current.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(lowercase(&chars[start..i]));
+                start = i;
+            }
+        }
+        if start < chars.len() {
+            words.push(lowercase(&chars[start..]));
+        }
+    }
+    words
+}
+
+fn lowercase(chars: &[char]) -> String {
+    chars.iter().flat_map(|c| c.to_lowercase()).collect()
+}
+
+/// Every token a tool is indexed under.
+fn document_tokens(tool: &EncapureTool) -> Vec<String> {
+    let (definition, input_schema) = tool_parts(&tool.raw_definition, ToolFormat::Auto);
+    let mut tokens = tokenize(&tool.name);
+    tokens.extend(tokenize(&tool.name));
+    if let Some(title) = &tool.title {
+        tokens.extend(tokenize(title));
+    }
+    if let Some(description) = definition.get("description").and_then(|d| d.as_str()) {
+        tokens.extend(tokenize(description));
+    }
+    for parameter in parameter_names(definition, input_schema) {
+        tokens.extend(tokenize(parameter));
+    }
+    tokens
+}
+
+/// Top-level input schema properties, or prompt argument names.
+fn parameter_names<'a>(definition: &'a Value, input_schema: Option<&'a Value>) -> Vec<&'a str> {
+    let properties = input_schema
+        .and_then(|schema| schema.get("properties"))
+        .and_then(|p| p.as_object())
+        .into_iter()
+        .flat_map(|p| p.keys().map(String::as_str));
+    let arguments = definition
+        .get("arguments")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.get("name").and_then(|n| n.as_str()));
+    properties.chain(arguments).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str, description: &str, properties: Value) -> EncapureTool {
+        EncapureTool::new(
+            name.to_string(),
+            "test".to_string(),
+            description.to_string(),
+            json!({
+                "name": name,
+                "description": description,
+                "inputSchema": { "type": "object", "properties": properties }
+            }),
+        )
+    }
+
+    #[test]
+    fn test_tokenize_splits_identifiers() {
+        assert_eq!(
+            tokenize("listS3Buckets in the AWS_region"),
+            vec!["list", "s3", "buckets", "aws", "region"]
+        );
+        assert_eq!(tokenize("getHTTPResponse"), vec!["get", "http", "response"]);
+        assert_eq!(tokenize("run a JQL query"), vec!["run", "jql", "query"]);
+    }
+
+    #[test]
+    fn test_exact_identifiers_rank_first() {
+        let tools = vec![
+            tool("apply_manifest", "Apply a manifest with kubectl", json!({})),
+            tool(
+                "deploy_app",
+                "Deploy an application to the cluster",
+                json!({}),
+            ),
+            tool("search_issues", "Search Jira issues", json!({ "jql": {} })),
+            tool("list_s3_buckets", "List storage buckets", json!({})),
+        ];
+        let index = Bm25Index::build(&tools);
+
+        assert_eq!(index.search("kubectl apply", 5, None)[0].0, 0);
+        assert_eq!(index.search("jql", 5, None)[0].0, 2);
+        assert_eq!(index.search("my s3 buckets", 5, None)[0].0, 3);
+        assert!(index.search("terraform", 5, None).is_empty());
+
+        // Filtered rows are never returned
+        let results = index.search("buckets jql", 5, Some(&[2]));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 2);
+    }
+}
@@ -0,0 +1,115 @@
+//! Exact nearest-neighbour search by scanning every embedding.
+
+use crate::index::kernel::TopK;
+use crate::index::quantize::BINARY_RESCORE_FACTOR;
+use crate::index::{query_slice, EmbeddingMatrix, IndexKind, VectorIndex};
+use ndarray::Array1;
+use std::sync::Arc;
+
+/// Brute-force index: scores every row and keeps the best `k`.
+///
+/// Needs no build step or storage, and its results are exact. A full scan is
+/// one blocked matrix-vector product feeding a bounded top-`k` heap. Binary
+/// embeddings are first narrowed down by Hamming distance, and only the
+/// survivors' f32 rows are read back from disk to rescore them against the
+/// f32 query.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlatIndex;
+
+impl VectorIndex for FlatIndex {
+    fn kind(&self) -> IndexKind {
+        IndexKind::Flat
+    }
+
+    fn search(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        query: &Array1<f32>,
+        k: usize,
+        allowed: Option<&[usize]>,
+    ) -> Vec<(usize, f32)> {
+        let query = query_slice(query);
+        if let Some(survivors) =
+            embeddings.hamming_nearest(&query, k.saturating_mul(BINARY_RESCORE_FACTOR), allowed)
+        {
+            let mut top = TopK::new(k.min(survivors.len()));
+            for (row, score) in embeddings.rescore(&query, &survivors) {
+                top.push(row, score);
+            }
+            return top.into_sorted_vec();
+        }
+
+        match allowed {
+            Some(rows) => {
+                let mut top = TopK::new(k.min(rows.len()));
+                for &row in rows {
+                    top.push(row, embeddings.score(&query, row));
+                }
+                top.into_sorted_vec()
+            }
+            None => {
+                let mut top = TopK::new(k.min(embeddings.nrows()));
+                embeddings.for_each_score(&query, |row, score| top.push(row, score));
+                top.into_sorted_vec()
+            }
+        }
+    }
+
+    fn updated(
+        &self,
+        _embeddings: &EmbeddingMatrix,
+        _previous_rows: &[Option<usize>],
+    ) -> Arc<dyn VectorIndex> {
+        Arc::new(FlatIndex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::random_embeddings;
+    use crate::index::Precision;
+
+    #[test]
+    fn test_flat_search_is_exact_and_respects_allowed_rows() {
+        let embeddings: EmbeddingMatrix = random_embeddings(20, 4, 3).into();
+        let query = embeddings.row(5);
+
+        let results = FlatIndex.search(&embeddings, &query, 3, None);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, 5);
+        assert!(results.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let results = FlatIndex.search(&embeddings, &query, 3, Some(&[1, 2]));
+        let rows: Vec<usize> = results.iter().map(|r| r.0).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.contains(&1) && rows.contains(&2));
+    }
+
+    #[test]
+    fn test_allowed_rows_fill_k_when_the_best_rows_are_excluded() {
+        let data = random_embeddings(40, 8, 11);
+        let query = data.row(0).to_owned();
+        // Every even row, including the query's own, is filtered out
+        let allowed: Vec<usize> = (1..40).step_by(2).collect();
+
+        for precision in [
+            Precision::F32,
+            Precision::F16,
+            Precision::Int8,
+            Precision::Binary,
+        ] {
+            let embeddings = EmbeddingMatrix::quantize(data.clone(), precision);
+
+            let results = FlatIndex.search(&embeddings, &query, 5, Some(&allowed));
+            assert_eq!(results.len(), 5, "{:?}", precision);
+            assert!(results.iter().all(|(row, _)| allowed.contains(row)));
+
+            // k beyond the allowed rows returns all of them and nothing else
+            let results = FlatIndex.search(&embeddings, &query, 100, Some(&allowed));
+            let mut rows: Vec<usize> = results.iter().map(|r| r.0).collect();
+            rows.sort_unstable();
+            assert_eq!(rows, allowed, "{:?}", precision);
+        }
+    }
+}
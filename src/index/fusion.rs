@@ -0,0 +1,205 @@
+//! Merging dense and lexical candidate lists.
+//!
+//! Stage 1 retrieves candidates from the vector index and the
+//! [`Bm25Index`](crate::index::Bm25Index) independently; [`FusionConfig::fuse`]
+//! combines both rankings into the candidate list passed to the reranker.
+//!
+//! - [`FusionMethod::Rrf`]: reciprocal rank fusion, `Σ weight / (60 + rank)`.
+//!   Ignores score scales, so it needs no tuning.
+//! - [`FusionMethod::Weighted`]: each list's scores are min-max normalized to
+//!   `[0, 1]` and summed with the given weights.
+
+use crate::error::{AppError, Result};
+use crate::index::kernel::TopK;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Rank offset of reciprocal rank fusion (Cormack et al., 2009).
+const RRF_K: f32 = 60.0;
+
+/// How the dense and lexical rankings are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionMethod {
+    /// Reciprocal rank fusion
+    #[default]
+    Rrf,
+    /// Weighted sum of min-max normalized scores
+    Weighted,
+}
+
+impl FromStr for FusionMethod {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" | "rrf" => Ok(Self::Rrf),
+            "weighted" => Ok(Self::Weighted),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown fusion method '{}' (expected rrf or weighted)",
+                other
+            ))),
+        }
+    }
+}
+
+impl FusionMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rrf => "rrf",
+            Self::Weighted => "weighted",
+        }
+    }
+}
+
+/// Fusion method and the weight of each ranking.
+///
+/// A weight of zero disables that retriever entirely.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FusionConfig {
+    pub method: FusionMethod,
+    pub dense_weight: f32,
+    pub lexical_weight: f32,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            method: FusionMethod::Rrf,
+            dense_weight: 1.0,
+            lexical_weight: 1.0,
+        }
+    }
+}
+
+impl FusionConfig {
+    /// Check that both weights are finite and non-negative, and not both zero.
+    pub fn validate(&self) -> Result<()> {
+        for (name, weight) in [
+            ("dense_weight", self.dense_weight),
+            ("lexical_weight", self.lexical_weight),
+        ] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(AppError::ValidationError(format!(
+                    "{} must be a non-negative number",
+                    name
+                )));
+            }
+        }
+        if self.dense_weight == 0.0 && self.lexical_weight == 0.0 {
+            return Err(AppError::ValidationError(
+                "dense_weight and lexical_weight cannot both be 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn uses_dense(&self) -> bool {
+        self.dense_weight > 0.0
+    }
+
+    pub fn uses_lexical(&self) -> bool {
+        self.lexical_weight > 0.0
+    }
+
+    /// The `k` best rows of the two rankings (each best first), best first.
+    pub fn fuse(
+        &self,
+        dense: &[(usize, f32)],
+        lexical: &[(usize, f32)],
+        k: usize,
+    ) -> Vec<(usize, f32)> {
+        let mut fused: HashMap<usize, f32> = HashMap::new();
+        for (ranking, weight) in [(dense, self.dense_weight), (lexical, self.lexical_weight)] {
+            if weight == 0.0 {
+                continue;
+            }
+            match self.method {
+                FusionMethod::Rrf => {
+                    for (rank, &(row, _)) in ranking.iter().enumerate() {
+                        *fused.entry(row).or_default() += weight / (RRF_K + rank as f32 + 1.0);
+                    }
+                }
+                FusionMethod::Weighted => {
+                    let (min, max) = ranking
+                        .iter()
+                        .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), r| {
+                            (lo.min(r.1), hi.max(r.1))
+                        });
+                    let range = max - min;
+                    for &(row, score) in ranking {
+                        let normalized = if range > 0.0 {
+                            (score - min) / range
+                        } else {
+                            1.0
+                        };
+                        *fused.entry(row).or_default() += weight * normalized;
+                    }
+                }
+            }
+        }
+
+        let mut top = TopK::new(k.min(fused.len()));
+        for (row, score) in fused {
+            top.push(row, score);
+        }
+        top.into_sorted_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(fused: &[(usize, f32)]) -> Vec<usize> {
+        fused.iter().map(|r| r.0).collect()
+    }
+
+    #[test]
+    fn test_rrf_rewards_agreement_and_keeps_exclusive_hits() {
+        let dense = [(1, 0.9), (2, 0.8), (3, 0.7)];
+        let lexical = [(4, 12.0), (2, 3.0)];
+        let fused = FusionConfig::default().fuse(&dense, &lexical, 3);
+        // Row 2 is in both lists; rows 1 and 4 tie at rank 1, lower row first
+        assert_eq!(rows(&fused), vec![2, 1, 4]);
+
+        let dense_only = FusionConfig {
+            lexical_weight: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(rows(&dense_only.fuse(&dense, &lexical, 3)), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_weighted_fusion_normalizes_scores() {
+        let dense = [(1, 0.9), (2, 0.5)];
+        let lexical = [(2, 20.0), (3, 10.0)];
+        let config = FusionConfig {
+            method: FusionMethod::Weighted,
+            dense_weight: 1.0,
+            lexical_weight: 2.0,
+        };
+        let fused = config.fuse(&dense, &lexical, 3);
+        assert_eq!(rows(&fused), vec![2, 1, 3]);
+        assert!((fused[0].1 - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_weights_are_validated() {
+        assert!(FusionConfig::default().validate().is_ok());
+        for (dense_weight, lexical_weight) in [(0.0, 0.0), (-1.0, 1.0), (1.0, f32::NAN)] {
+            let config = FusionConfig {
+                dense_weight,
+                lexical_weight,
+                ..Default::default()
+            };
+            assert!(config.validate().is_err());
+        }
+        assert_eq!(
+            "weighted".parse::<FusionMethod>().unwrap(),
+            FusionMethod::Weighted
+        );
+        assert!("max".parse::<FusionMethod>().is_err());
+    }
+}
@@ -0,0 +1,819 @@
+//! Approximate nearest-neighbour search over an HNSW graph.
+//!
+//! Hierarchical navigable small world graphs (Malkov & Yashunin, 2016) link
+//! every embedding to its closest neighbours on a stack of layers that get
+//! sparser towards the top. A search descends greedily through the upper
+//! layers and explores the bottom layer with a beam of `ef_search` nodes, so
+//! it visits a small fraction of the catalog.
+//!
+//! The graph stores only row numbers: distances are computed against the
+//! catalog's embeddings matrix. Levels are derived from each row number, so
+//! building the same embeddings always produces the same graph.
+
+use crate::error::{AppError, Result};
+use crate::index::kernel::TopK;
+use crate::index::quantize::BINARY_RESCORE_FACTOR;
+use crate::index::{query_slice, EmbeddingMatrix, FlatIndex, IndexKind, Precision, VectorIndex};
+use ndarray::Array1;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Index file format version. Increment when the format changes.
+const INDEX_VERSION: u32 = 1;
+
+/// Magic bytes to identify index files.
+const INDEX_MAGIC: &[u8; 8] = b"ENCAHNSW";
+
+/// Highest layer a node can be assigned to.
+const MAX_LEVEL: usize = 16;
+
+/// Filters allowing fewer than 1/N of the rows are searched exactly instead:
+/// the graph would have to be explored almost entirely to fill the results.
+const EXACT_FILTER_RATIO: usize = 10;
+
+/// Recall/latency trade-off parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HnswParams {
+    /// Links per node on the upper layers (twice as many on the bottom layer).
+    /// Higher improves recall at the cost of memory and build time.
+    pub m: usize,
+    /// Beam width while inserting. Higher builds a better graph, more slowly.
+    pub ef_construction: usize,
+    /// Beam width while searching (at least the number of results requested).
+    /// Higher improves recall at the cost of latency.
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+/// An HNSW graph over the rows of an embeddings matrix.
+#[derive(Debug, Clone)]
+pub struct HnswIndex {
+    params: HnswParams,
+    /// `links[node][layer]`: neighbours of `node` on `layer` (node level + 1 layers)
+    links: Vec<Vec<Vec<u32>>>,
+    entry_point: Option<u32>,
+}
+
+/// A node and its similarity to the query, ordered by similarity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+impl HnswIndex {
+    /// Build a graph over every row of `embeddings`.
+    pub fn build(params: HnswParams, embeddings: &EmbeddingMatrix) -> Self {
+        let start = std::time::Instant::now();
+        let mut index = Self::empty(params, embeddings.nrows());
+        for node in 0..embeddings.nrows() {
+            index.insert(embeddings, node as u32);
+        }
+        tracing::info!(
+            nodes = embeddings.nrows(),
+            m = params.m,
+            ef_construction = params.ef_construction,
+            build_ms = start.elapsed().as_millis(),
+            "HNSW index built"
+        );
+        index
+    }
+
+    fn empty(params: HnswParams, nodes: usize) -> Self {
+        Self {
+            params: HnswParams {
+                m: params.m.max(2),
+                ef_construction: params.ef_construction.max(1),
+                ef_search: params.ef_search.max(1),
+            },
+            links: vec![Vec::new(); nodes],
+            entry_point: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Layer assigned to `node`: geometric with ratio 1/m, derived from the row number.
+    fn level_for(&self, node: u32) -> usize {
+        let uniform = (splitmix64(node as u64) >> 11) as f64 / (1u64 << 53) as f64;
+        let level = -(1.0 - uniform).ln() / (self.params.m as f64).ln();
+        (level as usize).min(MAX_LEVEL)
+    }
+
+    fn top_level(&self, node: u32) -> usize {
+        self.links[node as usize].len() - 1
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    /// Link `node` into the graph (its own links must still be empty).
+    fn insert(&mut self, embeddings: &EmbeddingMatrix, node: u32) {
+        let level = self.level_for(node);
+        self.links[node as usize] = vec![Vec::new(); level + 1];
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+        let query = embeddings.row_vec(node as usize);
+        let query = query.as_slice();
+        let top = self.top_level(entry);
+
+        // Greedy descent to the node's own top layer
+        let mut entries = vec![entry];
+        for layer in (level + 1..=top).rev() {
+            let nearest = self.search_layer(embeddings, query, &entries, 1, layer, None);
+            entries = vec![nearest[0].1];
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(
+                embeddings,
+                query,
+                &entries,
+                self.params.ef_construction,
+                layer,
+                None,
+            );
+            let selected = select_neighbors(embeddings, &found, self.params.m);
+            for &neighbor in &selected {
+                let links = &mut self.links[neighbor as usize][layer];
+                links.push(node);
+                if links.len() > self.max_links(layer) {
+                    self.prune(embeddings, neighbor, layer);
+                }
+            }
+            self.links[node as usize][layer] = selected;
+            entries = found.iter().map(|s| s.1).collect();
+        }
+
+        if level > top {
+            self.entry_point = Some(node);
+        }
+    }
+
+    /// Trim the links of `node` on `layer` back to `max_links` with [`select_neighbors`].
+    fn prune(&mut self, embeddings: &EmbeddingMatrix, node: u32, layer: usize) {
+        let mut scored: Vec<Scored> = self.links[node as usize][layer]
+            .iter()
+            .map(|&n| Scored(embeddings.similarity(node as usize, n as usize), n))
+            .collect();
+        scored.sort_unstable_by(|a, b| b.cmp(a));
+        let keep = select_neighbors(embeddings, &scored, self.max_links(layer));
+        self.links[node as usize][layer] = keep;
+    }
+
+    /// Beam search on one layer; returns up to `ef` accepted nodes, best first.
+    ///
+    /// Nodes rejected by `accept` are still traversed, but never returned.
+    fn search_layer(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        query: &[f32],
+        entries: &[u32],
+        ef: usize,
+        layer: usize,
+        accept: Option<&[bool]>,
+    ) -> Vec<Scored> {
+        let accepted = |node: u32| accept.map_or(true, |mask| mask[node as usize]);
+        let score = |node: u32| Scored(embeddings.score(query, node as usize), node);
+
+        let mut visited: HashSet<u32> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = entries.iter().map(|&e| score(e)).collect();
+        let mut results: BinaryHeap<Reverse<Scored>> = candidates
+            .iter()
+            .filter(|s| accepted(s.1))
+            .map(|&s| Reverse(s))
+            .collect();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let worst = results.peek().map(|r| r.0 .0);
+            if results.len() >= ef && worst.is_some_and(|w| candidate.0 < w) {
+                break;
+            }
+            let Some(links) = self.links[candidate.1 as usize].get(layer) else {
+                continue;
+            };
+            for &neighbor in links {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let scored = score(neighbor);
+                let worst = results.peek().map(|r| r.0 .0);
+                if results.len() < ef || worst.is_some_and(|w| scored.0 > w) {
+                    candidates.push(scored);
+                    if accepted(neighbor) {
+                        results.push(Reverse(scored));
+                        if results.len() > ef {
+                            results.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<Scored> = results.into_iter().map(|r| r.0).collect();
+        found.sort_unstable_by(|a, b| b.cmp(a));
+        found
+    }
+
+    /// An index over `embeddings` that keeps the links between reused rows and
+    /// inserts the new ones (see [`VectorIndex::updated`]).
+    ///
+    /// Nodes left with fewer than `m` links on a layer by removed rows are
+    /// re-linked afterwards, so repeated updates do not thin out the graph.
+    /// Falls back to a full build when fewer than half of the previous rows
+    /// survive.
+    fn update(&self, embeddings: &EmbeddingMatrix, previous_rows: &[Option<usize>]) -> Self {
+        let mut new_row = vec![None; self.len()];
+        for (row, previous) in previous_rows.iter().enumerate() {
+            if let Some(slot) = previous.and_then(|p| new_row.get_mut(p)) {
+                slot.get_or_insert(row as u32);
+            }
+        }
+        let reused = new_row.iter().filter(|r| r.is_some()).count();
+        if reused == 0 || reused * 2 < self.len() {
+            return Self::build(self.params, embeddings);
+        }
+
+        let mut index = Self::empty(self.params, embeddings.nrows());
+        let mut pending = Vec::new();
+        let mut thinned = Vec::new();
+        for (row, previous) in previous_rows.iter().enumerate() {
+            match previous {
+                Some(p) if new_row[*p] == Some(row as u32) => {
+                    for (layer, links) in self.links[*p].iter().enumerate() {
+                        let kept: Vec<u32> =
+                            links.iter().filter_map(|&n| new_row[n as usize]).collect();
+                        if kept.len() < links.len() && kept.len() < self.params.m {
+                            thinned.push((row as u32, layer));
+                        }
+                        index.links[row].push(kept);
+                    }
+                }
+                _ => pending.push(row as u32),
+            }
+        }
+        index.entry_point = self
+            .entry_point
+            .and_then(|e| new_row[e as usize])
+            .or_else(|| {
+                (0..index.len() as u32)
+                    .filter(|&n| !index.links[n as usize].is_empty())
+                    .max_by_key(|&n| index.top_level(n))
+            });
+
+        for &node in &pending {
+            index.insert(embeddings, node);
+        }
+        for &(node, layer) in &thinned {
+            // Inserted rows may have linked back to it since
+            if index.links[node as usize][layer].len() < self.params.m {
+                index.relink(embeddings, node, layer);
+            }
+        }
+        tracing::debug!(
+            reused,
+            inserted = pending.len(),
+            relinked = thinned.len(),
+            "HNSW index updated incrementally"
+        );
+        index
+    }
+
+    /// Link `node` on `layer` to the nearest nodes found by searching from its
+    /// remaining neighbours, keeping their links to it as well.
+    ///
+    /// A node with no neighbours left on the layer is compared against every
+    /// node on it instead.
+    fn relink(&mut self, embeddings: &EmbeddingMatrix, node: u32, layer: usize) {
+        let query = embeddings.row_vec(node as usize);
+        let entries = self.links[node as usize][layer].clone();
+        let mut found = if entries.is_empty() {
+            let mut all: Vec<Scored> = (0..self.len() as u32)
+                .filter(|&n| self.links[n as usize].len() > layer)
+                .map(|n| Scored(embeddings.similarity(node as usize, n as usize), n))
+                .collect();
+            all.sort_unstable_by(|a, b| b.cmp(a));
+            all.truncate(self.params.ef_construction + 1);
+            all
+        } else {
+            self.search_layer(
+                embeddings,
+                &query,
+                &entries,
+                self.params.ef_construction + 1,
+                layer,
+                None,
+            )
+        };
+        found.retain(|s| s.1 != node);
+
+        let selected = select_neighbors(embeddings, &found, self.params.m);
+        for &neighbor in &selected {
+            let links = &mut self.links[neighbor as usize][layer];
+            if links.contains(&node) {
+                continue;
+            }
+            links.push(node);
+            if links.len() > self.max_links(layer) {
+                self.prune(embeddings, neighbor, layer);
+            }
+        }
+        self.links[node as usize][layer] = selected;
+    }
+
+    /// Read an index saved by [`VectorIndex::save`].
+    ///
+    /// Returns `None` if the file does not exist or was built for other tools,
+    /// other graph parameters or another number of rows. `ef_search` is taken
+    /// from `params`, since it only affects searching.
+    pub fn load(
+        path: &Path,
+        tools_hash: &[u8; 32],
+        params: HnswParams,
+        nodes: usize,
+    ) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = fs::read(path)
+            .map_err(|e| AppError::ValidationError(format!("Failed to read HNSW index: {}", e)))?;
+        let mut reader = ByteReader { bytes: &bytes };
+
+        let truncated = || AppError::ValidationError("HNSW index file is truncated".to_string());
+        if reader.take(8).ok_or_else(truncated)? != INDEX_MAGIC
+            || reader.u32().ok_or_else(truncated)? != INDEX_VERSION
+            || reader.take(32).ok_or_else(truncated)? != tools_hash
+        {
+            tracing::info!(path = %path.display(), "HNSW index is stale, rebuilding");
+            return Ok(None);
+        }
+
+        let mut index = Self::empty(params, nodes);
+        let m = reader.u64().ok_or_else(truncated)? as usize;
+        let ef_construction = reader.u64().ok_or_else(truncated)? as usize;
+        let stored_nodes = reader.u64().ok_or_else(truncated)? as usize;
+        if m != index.params.m
+            || ef_construction != index.params.ef_construction
+            || stored_nodes != nodes
+        {
+            tracing::info!(path = %path.display(), "HNSW parameters changed, rebuilding");
+            return Ok(None);
+        }
+
+        let corrupt = |what: &str| AppError::ValidationError(format!("HNSW index {}", what));
+        let entry = reader.u64().ok_or_else(truncated)?;
+        index.entry_point = match entry {
+            u64::MAX if nodes == 0 => None,
+            entry if entry < nodes as u64 => Some(entry as u32),
+            _ => return Err(corrupt("has an invalid entry point")),
+        };
+        for links in &mut index.links {
+            let layers = reader.u32().ok_or_else(truncated)? as usize;
+            if layers > MAX_LEVEL + 1 {
+                return Err(corrupt("has too many layers"));
+            }
+            for _ in 0..layers {
+                let count = reader.u32().ok_or_else(truncated)? as usize;
+                // Never trust a count beyond what the file can still hold
+                let mut neighbors = Vec::with_capacity(count.min(reader.bytes.len() / 4));
+                for _ in 0..count {
+                    let neighbor = reader.u32().ok_or_else(truncated)?;
+                    if neighbor as usize >= nodes {
+                        return Err(corrupt("links to a missing node"));
+                    }
+                    neighbors.push(neighbor);
+                }
+                links.push(neighbors);
+            }
+        }
+        if index.links.iter().any(|l| l.is_empty()) {
+            return Err(corrupt("has unlinked nodes"));
+        }
+
+        Ok(Some(index))
+    }
+}
+
+impl VectorIndex for HnswIndex {
+    fn kind(&self) -> IndexKind {
+        IndexKind::Hnsw
+    }
+
+    fn search(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        query: &Array1<f32>,
+        k: usize,
+        allowed: Option<&[usize]>,
+    ) -> Vec<(usize, f32)> {
+        let Some(entry) = self.entry_point.filter(|_| k > 0) else {
+            return Vec::new();
+        };
+        let mask = match allowed {
+            Some(rows) if rows.len() * EXACT_FILTER_RATIO < self.len() => {
+                return FlatIndex.search(embeddings, query, k, allowed);
+            }
+            Some(rows) => {
+                let mut mask = vec![false; self.len()];
+                for &row in rows {
+                    mask[row] = true;
+                }
+                Some(mask)
+            }
+            None => None,
+        };
+
+        let query = query_slice(query);
+        let mut entries = vec![entry];
+        for layer in (1..=self.top_level(entry)).rev() {
+            let nearest = self.search_layer(embeddings, &query, &entries, 1, layer, None);
+            entries = vec![nearest[0].1];
+        }
+        if embeddings.precision() == Precision::Binary {
+            // Sign bits only guide the traversal; rank the survivors exactly
+            let ef = self.params.ef_search.max(k * BINARY_RESCORE_FACTOR);
+            let found = self.search_layer(embeddings, &query, &entries, ef, 0, mask.as_deref());
+            let rows: Vec<usize> = found.iter().map(|s| s.1 as usize).collect();
+            let mut top = TopK::new(k.min(rows.len()));
+            for (row, score) in embeddings.rescore(&query, &rows) {
+                top.push(row, score);
+            }
+            return top.into_sorted_vec();
+        }
+        let ef = self.params.ef_search.max(k);
+        let mut found = self.search_layer(embeddings, &query, &entries, ef, 0, mask.as_deref());
+        found.truncate(k);
+        found.into_iter().map(|s| (s.1 as usize, s.0)).collect()
+    }
+
+    /// See [`HnswIndex::update`].
+    fn updated(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        previous_rows: &[Option<usize>],
+    ) -> Arc<dyn VectorIndex> {
+        Arc::new(self.update(embeddings, previous_rows))
+    }
+
+    /// File format:
+    /// - 8 bytes: magic "ENCAHNSW"
+    /// - 4 bytes: version (u32 LE)
+    /// - 32 bytes: tools_hash
+    /// - 8 bytes each: m, ef_construction, num_nodes, entry point (u64 LE, u64::MAX if empty)
+    /// - per node: layer count (u32 LE), then per layer a link count and the linked rows (u32 LE)
+    fn save(&self, path: &Path, tools_hash: &[u8; 32]) -> Result<()> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(INDEX_MAGIC);
+        bytes.extend_from_slice(&INDEX_VERSION.to_le_bytes());
+        bytes.extend_from_slice(tools_hash);
+        for value in [
+            self.params.m as u64,
+            self.params.ef_construction as u64,
+            self.len() as u64,
+            self.entry_point.map_or(u64::MAX, u64::from),
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for layers in &self.links {
+            bytes.extend_from_slice(&(layers.len() as u32).to_le_bytes());
+            for links in layers {
+                bytes.extend_from_slice(&(links.len() as u32).to_le_bytes());
+                for link in links {
+                    bytes.extend_from_slice(&link.to_le_bytes());
+                }
+            }
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                AppError::ValidationError(format!("Failed to create index directory: {}", e))
+            })?;
+        }
+        fs::write(path, &bytes)
+            .map_err(|e| AppError::ValidationError(format!("Failed to write HNSW index: {}", e)))?;
+
+        tracing::info!(
+            path = %path.display(),
+            nodes = self.len(),
+            size_bytes = bytes.len(),
+            "HNSW index saved"
+        );
+        Ok(())
+    }
+}
+
+/// Pick up to `m` neighbours from `candidates` (best first), preferring
+/// candidates closer to the node than to any neighbour already picked so that
+/// links spread out in different directions. Skipped candidates fill any
+/// remaining slots.
+fn select_neighbors(embeddings: &EmbeddingMatrix, candidates: &[Scored], m: usize) -> Vec<u32> {
+    let mut selected: Vec<u32> = Vec::with_capacity(m);
+    let mut skipped = Vec::new();
+    for candidate in candidates {
+        if selected.len() == m {
+            break;
+        }
+        let diverse = selected
+            .iter()
+            .all(|&s| embeddings.similarity(candidate.1 as usize, s as usize) < candidate.0);
+        if diverse {
+            selected.push(candidate.1);
+        } else {
+            skipped.push(candidate.1);
+        }
+    }
+    let missing = m.saturating_sub(selected.len());
+    selected.extend(skipped.into_iter().take(missing));
+    selected
+}
+
+/// SplitMix64 finalizer: a well-mixed hash of `x`.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Some(head)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.take(8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::random_embeddings;
+    use ndarray::Array2;
+
+    fn recall(
+        index: &dyn VectorIndex,
+        embeddings: &EmbeddingMatrix,
+        queries: &Array2<f32>,
+        k: usize,
+    ) -> f32 {
+        let mut hits = 0;
+        for query in queries.outer_iter() {
+            let query = query.to_owned();
+            let exact: HashSet<usize> = FlatIndex
+                .search(embeddings, &query, k, None)
+                .into_iter()
+                .map(|r| r.0)
+                .collect();
+            hits += index
+                .search(embeddings, &query, k, None)
+                .iter()
+                .filter(|r| exact.contains(&r.0))
+                .count();
+        }
+        hits as f32 / (queries.nrows() * k) as f32
+    }
+
+    /// Smaller than the defaults to keep debug-build tests fast.
+    const TEST_PARAMS: HnswParams = HnswParams {
+        m: 12,
+        ef_construction: 100,
+        ef_search: 64,
+    };
+
+    #[test]
+    fn test_hnsw_recall() {
+        let embeddings: EmbeddingMatrix = random_embeddings(1000, 16, 7).into();
+        let queries = random_embeddings(50, 16, 99);
+        let index = HnswIndex::build(TEST_PARAMS, &embeddings);
+
+        let recall = recall(&index, &embeddings, &queries, 10);
+        assert!(recall > 0.9, "recall {}", recall);
+    }
+
+    #[test]
+    fn test_hnsw_filtered_search_only_returns_allowed_rows() {
+        let embeddings: EmbeddingMatrix = random_embeddings(500, 16, 5).into();
+        let index = HnswIndex::build(TEST_PARAMS, &embeddings);
+        let query = embeddings.row(42);
+
+        // Broad filter: searched on the graph
+        let allowed: Vec<usize> = (0..500).filter(|r| r % 2 == 0).collect();
+        let results = index.search(&embeddings, &query, 10, Some(&allowed));
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[0].0, 42);
+        assert!(results.iter().all(|r| r.0 % 2 == 0));
+
+        // Narrow filter: exact scan of the allowed rows
+        let results = index.search(&embeddings, &query, 10, Some(&[3, 4]));
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_binary_search_rescores_with_f32_rows() {
+        let data = random_embeddings(500, 64, 41);
+        let embeddings = EmbeddingMatrix::quantize(data.clone(), Precision::Binary);
+        let index = HnswIndex::build(TEST_PARAMS, &embeddings);
+
+        let query = data.row(42).to_owned();
+        let results = index.search(&embeddings, &query, 5, None);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].0, 42);
+        for (row, score) in results {
+            assert!((score - data.row(row).dot(&query)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_incremental_update_keeps_recall() {
+        let embeddings: EmbeddingMatrix = random_embeddings(500, 16, 11).into();
+        let index = HnswIndex::build(TEST_PARAMS, &embeddings);
+
+        // Drop the first 50 rows and append 100 new ones
+        let fresh = random_embeddings(100, 16, 12);
+        let mut next = Array2::zeros((550, 16));
+        let mut previous_rows = Vec::new();
+        for row in 0..450 {
+            next.row_mut(row).assign(&embeddings.row(row + 50));
+            previous_rows.push(Some(row + 50));
+        }
+        for row in 0..100 {
+            next.row_mut(450 + row).assign(&fresh.row(row));
+            previous_rows.push(None);
+        }
+
+        let next: EmbeddingMatrix = next.into();
+        let updated = index.updated(&next, &previous_rows);
+        assert_eq!(updated.kind(), IndexKind::Hnsw);
+        let queries = random_embeddings(50, 16, 13);
+        let recall = recall(updated.as_ref(), &next, &queries, 10);
+        assert!(recall > 0.9, "recall {}", recall);
+    }
+
+    #[test]
+    fn test_repeated_churn_keeps_nodes_connected() {
+        let mut embeddings: EmbeddingMatrix = random_embeddings(500, 16, 31).into();
+        let mut index = HnswIndex::build(TEST_PARAMS, &embeddings);
+
+        // Each round drops 40% of the rows (below the rebuild threshold) and adds as
+        // many; without re-linking, survivors drift below m links
+        for round in 0..5u64 {
+            let fresh = random_embeddings(200, 16, 100 + round);
+            let mut next = Array2::zeros((500, 16));
+            let mut previous_rows = Vec::new();
+            for row in 0..300 {
+                next.row_mut(row).assign(&embeddings.row(row * 5 / 3));
+                previous_rows.push(Some(row * 5 / 3));
+            }
+            for row in 0..200 {
+                next.row_mut(300 + row).assign(&fresh.row(row));
+                previous_rows.push(None);
+            }
+            embeddings = next.into();
+            index = index.update(&embeddings, &previous_rows);
+        }
+
+        let queries = random_embeddings(50, 16, 32);
+        let recall = recall(&index, &embeddings, &queries, 10);
+        assert!(recall > 0.9, "recall {}", recall);
+        let weakest = index.links.iter().map(|l| l[0].len()).min().unwrap();
+        assert!(
+            weakest >= TEST_PARAMS.m,
+            "weakest node has {} links",
+            weakest
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("embeddings.hnsw");
+        let embeddings: EmbeddingMatrix = random_embeddings(300, 16, 21).into();
+        let index = HnswIndex::build(HnswParams::default(), &embeddings);
+        let hash = [7u8; 32];
+        index.save(&path, &hash).unwrap();
+
+        let loaded = HnswIndex::load(&path, &hash, HnswParams::default(), 300)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.links, index.links);
+        assert_eq!(loaded.entry_point, index.entry_point);
+
+        // Other tools, graph parameters or sizes invalidate the file
+        assert!(
+            HnswIndex::load(&path, &[0u8; 32], HnswParams::default(), 300)
+                .unwrap()
+                .is_none()
+        );
+        let params = HnswParams {
+            m: 8,
+            ..Default::default()
+        };
+        assert!(HnswIndex::load(&path, &hash, params, 300)
+            .unwrap()
+            .is_none());
+        assert!(HnswIndex::load(&path, &hash, HnswParams::default(), 301)
+            .unwrap()
+            .is_none());
+
+        // ef_search only affects searching and can change freely
+        let params = HnswParams {
+            ef_search: 200,
+            ..Default::default()
+        };
+        assert!(HnswIndex::load(&path, &hash, params, 300)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_corrupt_file_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("embeddings.hnsw");
+        let embeddings: EmbeddingMatrix = random_embeddings(50, 16, 23).into();
+        let hash = [7u8; 32];
+        HnswIndex::build(TEST_PARAMS, &embeddings)
+            .save(&path, &hash)
+            .unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let load = |bytes: &[u8]| {
+            std::fs::write(&path, bytes).unwrap();
+            HnswIndex::load(&path, &hash, TEST_PARAMS, 50)
+        };
+
+        // The entry point follows the magic, version, hash and three u64 parameters
+        let entry_offset = 8 + 4 + 32 + 3 * 8;
+        let mut bad_entry = bytes.clone();
+        bad_entry[entry_offset..entry_offset + 8].copy_from_slice(&50u64.to_le_bytes());
+        assert!(load(&bad_entry).is_err());
+
+        let mut no_entry = bytes.clone();
+        no_entry[entry_offset..entry_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(load(&no_entry).is_err());
+
+        let mut bad_link = bytes.clone();
+        let last = bad_link.len() - 4;
+        bad_link[last..].copy_from_slice(&50u32.to_le_bytes());
+        assert!(load(&bad_link).is_err());
+
+        assert!(load(&bytes[..bytes.len() - 1]).is_err());
+        assert!(load(&bytes).unwrap().is_some());
+    }
+}
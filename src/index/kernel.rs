@@ -0,0 +1,216 @@
+//! Scoring kernels shared by the indexes.
+//!
+//! Embeddings are stored as one contiguous row-major matrix, so scoring every
+//! row against a query is a single matrix-vector product. [`for_each_score`]
+//! computes it a block of rows at a time with fixed-width accumulators that
+//! the compiler lowers to SIMD, and [`TopK`] keeps the best rows in a bounded
+//! heap, so a scan allocates nothing proportional to the catalog size.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Accumulator width; a multiple of every common SIMD register width for f32.
+const LANES: usize = 8;
+/// Rows scored together so each query chunk is loaded once per block.
+const BLOCK_ROWS: usize = 4;
+
+/// Dot product of two equal-length slices.
+#[inline]
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    debug_assert_eq!(a.len(), b.len());
+    let split = a.len() / LANES * LANES;
+    let mut acc = [0.0f32; LANES];
+    for (x, y) in a[..split]
+        .chunks_exact(LANES)
+        .zip(b[..split].chunks_exact(LANES))
+    {
+        for ((acc, x), y) in acc.iter_mut().zip(x).zip(y) {
+            *acc += x * y;
+        }
+    }
+    let tail: f32 = a[split..].iter().zip(&b[split..]).map(|(x, y)| x * y).sum();
+    acc.iter().sum::<f32>() + tail
+}
+
+/// Dot product of `values`, converted to f32 by `convert`, with `query`.
+///
+/// Used to score quantized rows against an f32 query without dequantizing
+/// them first.
+#[inline]
+pub fn dot_mapped<T: Copy>(values: &[T], query: &[f32], convert: impl Fn(T) -> f32) -> f32 {
+    debug_assert_eq!(values.len(), query.len());
+    let split = values.len() / LANES * LANES;
+    let mut acc = [0.0f32; LANES];
+    for (x, y) in values[..split]
+        .chunks_exact(LANES)
+        .zip(query[..split].chunks_exact(LANES))
+    {
+        for ((acc, &x), y) in acc.iter_mut().zip(x).zip(y) {
+            *acc += convert(x) * y;
+        }
+    }
+    let tail: f32 = values[split..]
+        .iter()
+        .zip(&query[split..])
+        .map(|(&x, y)| convert(x) * y)
+        .sum();
+    acc.iter().sum::<f32>() + tail
+}
+
+/// Score every row of the row-major `matrix` (rows as long as `query`)
+/// against `query`, calling `visit(row, score)` in row order.
+pub fn for_each_score(matrix: &[f32], query: &[f32], mut visit: impl FnMut(usize, f32)) {
+    let dim = query.len();
+    if dim == 0 {
+        return;
+    }
+
+    let mut row = 0;
+    let mut blocks = matrix.chunks_exact(dim * BLOCK_ROWS);
+    for block in &mut blocks {
+        for score in dot_block(block, query) {
+            visit(row, score);
+            row += 1;
+        }
+    }
+    for doc in blocks.remainder().chunks_exact(dim) {
+        visit(row, dot(doc, query));
+        row += 1;
+    }
+}
+
+/// Dot products of `query` with the `BLOCK_ROWS` consecutive rows in `block`.
+#[inline]
+fn dot_block(block: &[f32], query: &[f32]) -> [f32; BLOCK_ROWS] {
+    let dim = query.len();
+    let rows: [&[f32]; BLOCK_ROWS] = std::array::from_fn(|r| &block[r * dim..(r + 1) * dim]);
+    let split = dim / LANES * LANES;
+    let mut acc = [[0.0f32; LANES]; BLOCK_ROWS];
+    for start in (0..split).step_by(LANES) {
+        let q = &query[start..start + LANES];
+        for (acc, row) in acc.iter_mut().zip(&rows) {
+            for ((acc, x), q) in acc.iter_mut().zip(&row[start..start + LANES]).zip(q) {
+                *acc += x * q;
+            }
+        }
+    }
+    std::array::from_fn(|r| {
+        let tail: f32 = rows[r][split..]
+            .iter()
+            .zip(&query[split..])
+            .map(|(x, q)| x * q)
+            .sum();
+        acc[r].iter().sum::<f32>() + tail
+    })
+}
+
+/// A row and its score, ordered by score; ties prefer the lower row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate(f32, usize);
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| other.1.cmp(&self.1))
+    }
+}
+
+/// The `k` best-scoring rows seen so far.
+///
+/// A min-heap of at most `k` entries: each push is one comparison against
+/// the current worst unless the row makes the cut, so selecting from `n` rows
+/// is `O(n log k)` with a single allocation of `k` entries.
+#[derive(Debug)]
+pub struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<Candidate>>,
+}
+
+impl TopK {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k),
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, row: usize, score: f32) {
+        let candidate = Candidate(score, row);
+        if self.heap.len() < self.k {
+            self.heap.push(Reverse(candidate));
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if candidate > worst.0 {
+                *worst = Reverse(candidate);
+            }
+        }
+    }
+
+    /// The selected rows, best first.
+    pub fn into_sorted_vec(self) -> Vec<(usize, f32)> {
+        // Ascending `Reverse` order is descending score order
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(Candidate(score, row))| (row, score))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::random_embeddings;
+
+    #[test]
+    fn test_blocked_scores_match_naive_dot_products() {
+        // Row count and dimension not multiples of the block or lane sizes
+        let embeddings = random_embeddings(23, 13, 5);
+        let query = embeddings.row(9).to_owned();
+
+        let mut scores = Vec::new();
+        for_each_score(
+            embeddings.as_slice().unwrap(),
+            query.as_slice().unwrap(),
+            |row, score| scores.push((row, score)),
+        );
+
+        assert_eq!(scores.len(), 23);
+        for (row, score) in scores {
+            let expected = query.dot(&embeddings.row(row));
+            assert!((score - expected).abs() < 1e-5);
+            let mapped = dot_mapped(
+                embeddings.row(row).as_slice().unwrap(),
+                query.as_slice().unwrap(),
+                |x| x,
+            );
+            assert!((mapped - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_top_k_matches_full_sort() {
+        let scores = [0.3, 0.9, 0.1, 0.9, 0.5, -0.2, 0.7];
+        let mut top = TopK::new(4);
+        for (row, &score) in scores.iter().enumerate() {
+            top.push(row, score);
+        }
+        assert_eq!(
+            top.into_sorted_vec(),
+            vec![(1, 0.9), (3, 0.9), (6, 0.7), (4, 0.5)]
+        );
+
+        let mut empty = TopK::new(0);
+        empty.push(0, 1.0);
+        assert!(empty.into_sorted_vec().is_empty());
+    }
+}
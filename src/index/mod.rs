@@ -0,0 +1,251 @@
+//! Stage-1 nearest-neighbour indexes over tool embeddings.
+//!
+//! A [`VectorIndex`] finds the catalog rows most similar to a query embedding.
+//! Indexes never own the embeddings: the [`Catalog`](crate::catalog::Catalog)
+//! holds the matrix and passes it to every call, so switching index types
+//! does not duplicate the vectors.
+//!
+//! - [`FlatIndex`]: exact scan over every row (default, best for small catalogs)
+//! - [`HnswIndex`]: approximate search over a hierarchical navigable small
+//!   world graph, for catalogs with hundreds of thousands of tools. The graph
+//!   is persisted next to the embeddings cache.
+//!
+//! Both search an [`EmbeddingMatrix`], which may store the embeddings at a
+//! reduced [`Precision`] to save memory (see [`quantize`]).
+//!
+//! Alongside the vector index, every catalog has a [`Bm25Index`] for exact
+//! terms the bi-encoder misses; [`fusion`] merges the two candidate lists.
+
+pub mod bm25;
+pub mod flat;
+pub mod fusion;
+pub mod hnsw;
+pub mod kernel;
+pub mod names;
+pub mod quantize;
+
+pub use bm25::Bm25Index;
+pub use flat::FlatIndex;
+pub use fusion::{FusionConfig, FusionMethod};
+pub use hnsw::{HnswIndex, HnswParams};
+pub use names::{NameIndex, NameMatch, NameMatchConfig, NameMatchKind};
+pub use quantize::{EmbeddingMatrix, Precision};
+
+use crate::error::{AppError, Result};
+use crate::ingestion::EncapureTool;
+use crate::persistence::EmbeddingsCache;
+use ndarray::Array1;
+use std::borrow::Cow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Nearest-neighbour search over a catalog's embeddings matrix.
+pub trait VectorIndex: fmt::Debug + Send + Sync {
+    fn kind(&self) -> IndexKind;
+
+    /// The `k` rows of `embeddings` most similar to `query`, best first.
+    ///
+    /// Only rows listed in `allowed` are returned (`None` allows every row).
+    /// Embeddings are L2-normalized, so similarity is the dot product.
+    fn search(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        query: &Array1<f32>,
+        k: usize,
+        allowed: Option<&[usize]>,
+    ) -> Vec<(usize, f32)>;
+
+    /// An index of the same kind over `embeddings`.
+    ///
+    /// `previous_rows[i]` is the row of the embedding in the matrix this index
+    /// was built over that row `i` reuses, if any, so only new rows have to
+    /// be inserted.
+    fn updated(
+        &self,
+        embeddings: &EmbeddingMatrix,
+        previous_rows: &[Option<usize>],
+    ) -> Arc<dyn VectorIndex>;
+
+    /// Persist the index to `path`, if this kind is persisted.
+    ///
+    /// `tools_hash` identifies the tools the index was built for (see
+    /// [`EmbeddingsCache::compute_tools_hash`]).
+    fn save(&self, _path: &Path, _tools_hash: &[u8; 32]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Which stage-1 index to build.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IndexKind {
+    /// Exact scan over every embedding
+    #[default]
+    Flat,
+    /// Approximate HNSW graph search
+    Hnsw,
+}
+
+impl FromStr for IndexKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" | "flat" | "exact" => Ok(Self::Flat),
+            "hnsw" => Ok(Self::Hnsw),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown vector index '{}' (expected flat or hnsw)",
+                other
+            ))),
+        }
+    }
+}
+
+impl IndexKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Flat => "flat",
+            Self::Hnsw => "hnsw",
+        }
+    }
+}
+
+/// Index type and its recall/latency parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexConfig {
+    pub kind: IndexKind,
+    /// Graph parameters, used when `kind` is `Hnsw`
+    pub hnsw: HnswParams,
+    /// How the embeddings searched by the index are stored
+    pub precision: Precision,
+}
+
+impl IndexConfig {
+    /// Build an index over `embeddings` from scratch.
+    pub fn build(&self, embeddings: &EmbeddingMatrix) -> Arc<dyn VectorIndex> {
+        match self.kind {
+            IndexKind::Flat => Arc::new(FlatIndex),
+            IndexKind::Hnsw => Arc::new(HnswIndex::build(self.hnsw, embeddings)),
+        }
+    }
+
+    /// Load the index persisted next to `cache_path` if it still matches
+    /// `tools`, otherwise build it and persist the result.
+    ///
+    /// `cache_path` is the embeddings cache file; `None` skips persistence.
+    pub fn load_or_build(
+        &self,
+        tools: &[EncapureTool],
+        embeddings: &EmbeddingMatrix,
+        cache_path: Option<&Path>,
+    ) -> Arc<dyn VectorIndex> {
+        let Some(cache_path) = cache_path.filter(|_| self.kind == IndexKind::Hnsw) else {
+            return self.build(embeddings);
+        };
+
+        let path = index_cache_path(cache_path);
+        let tools_hash = EmbeddingsCache::compute_tools_hash(tools);
+        match HnswIndex::load(&path, &tools_hash, self.hnsw, embeddings.nrows()) {
+            Ok(Some(index)) => {
+                tracing::info!(path = %path.display(), "Using cached HNSW index");
+                return Arc::new(index);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "Failed to load HNSW index, rebuilding"),
+        }
+
+        let index = self.build(embeddings);
+        if let Err(e) = index.save(&path, &tools_hash) {
+            tracing::warn!(error = %e, "Failed to save HNSW index (non-fatal)");
+        }
+        index
+    }
+}
+
+/// `query` as a contiguous slice, copied only if it is not already one.
+pub(crate) fn query_slice(query: &Array1<f32>) -> Cow<'_, [f32]> {
+    match query.as_slice() {
+        Some(slice) => Cow::Borrowed(slice),
+        None => Cow::Owned(query.to_vec()),
+    }
+}
+
+/// File holding the index persisted next to an embeddings cache
+/// (`embeddings.bin` -> `embeddings.hnsw`).
+pub fn index_cache_path(embeddings_cache_path: &Path) -> PathBuf {
+    embeddings_cache_path.with_extension("hnsw")
+}
+
+/// Persist `index` next to the embeddings cache at `cache_path`.
+pub fn save_index_cache(
+    cache_path: &Path,
+    tools: &[EncapureTool],
+    index: &dyn VectorIndex,
+) -> Result<()> {
+    index.save(
+        &index_cache_path(cache_path),
+        &EmbeddingsCache::compute_tools_hash(tools),
+    )
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ndarray::Array2;
+
+    /// `n` deterministic pseudo-random unit vectors of dimension `dim`.
+    pub(crate) fn random_embeddings(n: usize, dim: usize, seed: u64) -> Array2<f32> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 33) as f32 / (1u64 << 31) as f32) - 0.5
+        };
+        let mut embeddings = Array2::from_shape_fn((n, dim), |_| next());
+        for mut row in embeddings.outer_iter_mut() {
+            let norm = row.dot(&row).sqrt();
+            row /= norm;
+        }
+        embeddings
+    }
+
+    #[test]
+    fn test_index_kind_from_str() {
+        assert_eq!("".parse::<IndexKind>().unwrap(), IndexKind::Flat);
+        assert_eq!("HNSW".parse::<IndexKind>().unwrap(), IndexKind::Hnsw);
+        assert!("ivf".parse::<IndexKind>().is_err());
+    }
+
+    #[test]
+    fn test_index_is_persisted_next_to_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_path = dir.path().join("embeddings.bin");
+        let embeddings: EmbeddingMatrix = random_embeddings(50, 8, 1).into();
+        let tools: Vec<EncapureTool> = (0..50)
+            .map(|i| {
+                EncapureTool::new(
+                    format!("tool_{}", i),
+                    "s".to_string(),
+                    format!("view {}", i),
+                    serde_json::json!({}),
+                )
+            })
+            .collect();
+        let config = IndexConfig {
+            kind: IndexKind::Hnsw,
+            ..Default::default()
+        };
+
+        let built = config.load_or_build(&tools, &embeddings, Some(&cache_path));
+        assert!(index_cache_path(&cache_path).exists());
+
+        let loaded = config.load_or_build(&tools, &embeddings, Some(&cache_path));
+        let query = embeddings.row(7);
+        assert_eq!(
+            built.search(&embeddings, &query, 5, None),
+            loaded.search(&embeddings, &query, 5, None)
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! Tool-name lookup for queries that are, or are close to, a tool name.
+//!
+//! Agents often search with a tool name they have seen before
+//! (`send_slack_dm`, `slack dm send`, `sendSlackDM`). A [`NameIndex`] finds
+//! those tools directly so they are always reranked, however the embeddings
+//! rank them. Names and queries are normalized to lowercase words split on
+//! snake_case, kebab-case and camelCase boundaries, then matched:
+//!
+//! - exactly, by name or by qualified id (`slack::send_slack_dm`)
+//! - as the same set of words in any order
+//! - within a small edit distance of the name, through a BK-tree
+
+use crate::index::bm25::split_words;
+use crate::ingestion::EncapureTool;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Queries with more words than this are sentences, not names.
+const MAX_QUERY_WORDS: usize = 8;
+
+/// Most tools a single lookup returns.
+const MAX_NAME_MATCHES: usize = 10;
+
+/// How a query matched a tool name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameMatchKind {
+    /// Same words in the same order, by name or qualified id
+    Exact,
+    /// Same words in another order
+    Tokens,
+    /// Within the configured edit distance
+    Fuzzy,
+}
+
+/// A tool whose name matched the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NameMatch {
+    pub row: usize,
+    pub kind: NameMatchKind,
+    /// 1.0 for exact and word-set matches, decreasing with edit distance
+    pub strength: f32,
+}
+
+/// Score boost and fuzziness of name matches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NameMatchConfig {
+    /// Added to a matched tool's reranker score, scaled by match strength
+    /// (the result is capped at 1.0)
+    pub boost: f32,
+    /// Largest edit distance accepted between the query and a name (0 disables
+    /// fuzzy matching). Short names allow proportionally less.
+    pub max_distance: usize,
+}
+
+impl Default for NameMatchConfig {
+    fn default() -> Self {
+        Self {
+            boost: 0.3,
+            max_distance: 2,
+        }
+    }
+}
+
+/// Normalized tool names of a catalog.
+#[derive(Debug, Clone, Default)]
+pub struct NameIndex {
+    /// Normalized name and qualified id -> rows
+    exact: HashMap<String, Vec<u32>>,
+    /// Sorted words of the name -> rows
+    words: HashMap<String, Vec<u32>>,
+    fuzzy: BkTree,
+}
+
+impl NameIndex {
+    pub fn build(tools: &[EncapureTool]) -> Self {
+        let mut index = Self::default();
+        for (row, tool) in tools.iter().enumerate() {
+            let row = row as u32;
+            let name = split_words(&tool.name);
+            if name.is_empty() {
+                continue;
+            }
+            let mut qualified = split_words(&tool.server_origin);
+            qualified.extend(name.iter().cloned());
+
+            let canonical = name.join("_");
+            index.exact.entry(canonical.clone()).or_default().push(row);
+            index
+                .exact
+                .entry(qualified.join("_"))
+                .or_default()
+                .push(row);
+            index.words.entry(sorted(&name)).or_default().push(row);
+            index.fuzzy.insert(&canonical, row);
+        }
+        index
+    }
+
+    /// Tools whose name matches `query`, strongest first.
+    ///
+    /// Only rows listed in `allowed` (sorted ascending) are returned.
+    pub fn lookup(
+        &self,
+        query: &str,
+        max_distance: usize,
+        allowed: Option<&[usize]>,
+    ) -> Vec<NameMatch> {
+        let words = split_words(query);
+        if words.is_empty() || words.len() > MAX_QUERY_WORDS {
+            return Vec::new();
+        }
+        let canonical = words.join("_");
+
+        let mut matches: Vec<NameMatch> = Vec::new();
+        let mut add = |row: u32, kind: NameMatchKind, strength: f32| {
+            let row = row as usize;
+            let permitted = allowed.map_or(true, |rows| rows.binary_search(&row).is_ok());
+            if permitted && !matches.iter().any(|m| m.row == row) {
+                matches.push(NameMatch {
+                    row,
+                    kind,
+                    strength,
+                });
+            }
+        };
+
+        for &row in self.exact.get(&canonical).into_iter().flatten() {
+            add(row, NameMatchKind::Exact, 1.0);
+        }
+        for &row in self.words.get(&sorted(&words)).into_iter().flatten() {
+            add(row, NameMatchKind::Tokens, 1.0);
+        }
+        let radius = max_distance.min(canonical.chars().count() / 4);
+        if radius > 0 {
+            for (row, distance) in self.fuzzy.find(&canonical, radius) {
+                let strength = 1.0 - distance as f32 / (radius + 1) as f32;
+                add(row, NameMatchKind::Fuzzy, strength);
+            }
+        }
+
+        matches.sort_by(|a, b| b.strength.total_cmp(&a.strength).then(a.row.cmp(&b.row)));
+        matches.truncate(MAX_NAME_MATCHES);
+        matches
+    }
+}
+
+fn sorted(words: &[String]) -> String {
+    let mut words = words.to_vec();
+    words.sort_unstable();
+    words.join("_")
+}
+
+/// Burkhard-Keller tree: metric tree over edit distance, so a lookup only
+/// compares the query against a small fraction of the names.
+#[derive(Debug, Clone, Default)]
+struct BkTree {
+    nodes: Vec<BkNode>,
+}
+
+#[derive(Debug, Clone)]
+struct BkNode {
+    key: Vec<char>,
+    rows: Vec<u32>,
+    /// (distance to this node's key, child node)
+    children: Vec<(usize, usize)>,
+}
+
+impl BkTree {
+    fn insert(&mut self, key: &str, row: u32) {
+        let key: Vec<char> = key.chars().collect();
+        if self.nodes.is_empty() {
+            self.nodes.push(BkNode {
+                key,
+                rows: vec![row],
+                children: Vec::new(),
+            });
+            return;
+        }
+
+        let mut node = 0;
+        loop {
+            let distance = levenshtein(&self.nodes[node].key, &key);
+            if distance == 0 {
+                self.nodes[node].rows.push(row);
+                return;
+            }
+            match self.nodes[node]
+                .children
+                .iter()
+                .find(|(d, _)| *d == distance)
+            {
+                Some(&(_, child)) => node = child,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(BkNode {
+                        key,
+                        rows: vec![row],
+                        children: Vec::new(),
+                    });
+                    self.nodes[node].children.push((distance, child));
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Rows of every key within `radius` of `query`, with their distance.
+    fn find(&self, query: &str, radius: usize) -> Vec<(u32, usize)> {
+        let query: Vec<char> = query.chars().collect();
+        let mut found = Vec::new();
+        let mut pending = if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        };
+        while let Some(node) = pending.pop() {
+            let node = &self.nodes[node];
+            let distance = levenshtein(&node.key, &query);
+            if distance <= radius {
+                found.extend(node.rows.iter().map(|&row| (row, distance)));
+            }
+            // Triangle inequality: only subtrees at distance ± radius can match
+            pending.extend(
+                node.children
+                    .iter()
+                    .filter(|(d, _)| d.abs_diff(distance) <= radius)
+                    .map(|&(_, child)| child),
+            );
+        }
+        found
+    }
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(server: &str, name: &str) -> EncapureTool {
+        EncapureTool::new(
+            name.to_string(),
+            server.to_string(),
+            name.to_string(),
+            json!({ "name": name }),
+        )
+    }
+
+    fn index() -> NameIndex {
+        NameIndex::build(&[
+            tool("slack", "send_slack_dm"),
+            tool("slack", "sendSlackMessage"),
+            tool("github", "create_issue"),
+            tool("jira", "create_issue"),
+        ])
+    }
+
+    fn rows(matches: &[NameMatch]) -> Vec<(usize, NameMatchKind)> {
+        matches.iter().map(|m| (m.row, m.kind)).collect()
+    }
+
+    #[test]
+    fn test_exact_and_reordered_names() {
+        let index = index();
+        assert_eq!(
+            rows(&index.lookup("send_slack_dm", 2, None)),
+            vec![(0, NameMatchKind::Exact)]
+        );
+        assert_eq!(
+            rows(&index.lookup("slack dm send", 2, None)),
+            vec![(0, NameMatchKind::Tokens)]
+        );
+        assert_eq!(
+            rows(&index.lookup("send-slack-message", 2, None)),
+            vec![(1, NameMatchKind::Exact)]
+        );
+
+        // Qualified ids pick one server; bare names match every server
+        assert_eq!(
+            rows(&index.lookup("jira::create_issue", 2, None)),
+            vec![(3, NameMatchKind::Exact)]
+        );
+        assert_eq!(index.lookup("create_issue", 2, None).len(), 2);
+        assert_eq!(index.lookup("create_issue", 2, Some(&[2])).len(), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_names() {
+        let index = index();
+        let matches = index.lookup("send_slak_dm", 2, None);
+        assert_eq!(rows(&matches), vec![(0, NameMatchKind::Fuzzy)]);
+        assert!(matches[0].strength < 1.0);
+
+        assert!(index.lookup("send_slak_dm", 0, None).is_empty());
+        assert!(index
+            .lookup(
+                "how do I open a ticket in the tracker for my team today",
+                2,
+                None
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(levenshtein(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(levenshtein(&chars(""), &chars("abc")), 3);
+        assert_eq!(levenshtein(&chars("same"), &chars("same")), 0);
+    }
+}
@@ -0,0 +1,1012 @@
+//! Embedding storage precisions.
+//!
+//! The catalog keeps its stage-1 embeddings in an [`EmbeddingMatrix`], which
+//! stores every row at one of four [`Precision`]s:
+//!
+//! - `f32`: full precision, 4 bytes per dimension
+//! - `f16`: IEEE half precision, 2 bytes per dimension, near-lossless
+//! - `int8`: symmetric scalar quantization with one scale per row, 1 byte per
+//!   dimension plus 4 bytes per row
+//! - `binary`: one sign bit per dimension, 1/32 of the f32 size in memory.
+//!   Searches rank rows by Hamming distance to the query's sign bits, then
+//!   rescore the best [`BINARY_RESCORE_FACTOR`]` × k` by the dot product of
+//!   the f32 query with their f32 rows, which stay on disk in an anonymous
+//!   temporary file (under `TMPDIR`) and are only read for those survivors
+//!
+//! Queries always stay in f32: quantized rows are scored against the f32 query
+//! directly (asymmetric distance), which loses far less recall than
+//! quantizing both sides. [`EmbeddingMatrix::compress`] logs the memory saved
+//! and the recall@10 measured against the f32 embeddings.
+
+use crate::error::AppError;
+use crate::index::kernel::{self, TopK};
+use crate::index::{FlatIndex, VectorIndex};
+use ndarray::{Array1, Array2};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Candidates kept per requested result by the binary Hamming prefilter.
+pub const BINARY_RESCORE_FACTOR: usize = 4;
+
+/// Number of catalog rows used as queries when measuring recall.
+const RECALL_QUERIES: usize = 50;
+
+/// Result count recall is measured at.
+const RECALL_K: usize = 10;
+
+/// How each embedding dimension is stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// 32-bit floats (no quantization)
+    #[default]
+    F32,
+    /// 16-bit IEEE half floats
+    F16,
+    /// Signed bytes with a per-row scale
+    Int8,
+    /// Sign bits, with f32 rows on disk for rescoring
+    Binary,
+}
+
+impl FromStr for Precision {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" | "f32" | "float32" => Ok(Self::F32),
+            "f16" | "float16" | "half" => Ok(Self::F16),
+            "int8" | "i8" => Ok(Self::Int8),
+            "binary" | "bit" | "1bit" => Ok(Self::Binary),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown embedding precision '{}' (expected f32, f16, int8 or binary)",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Precision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::F16 => "f16",
+            Self::Int8 => "int8",
+            Self::Binary => "binary",
+        }
+    }
+
+    /// Tag stored in the embeddings cache header.
+    pub(crate) fn code(&self) -> u8 {
+        match self {
+            Self::F32 => 0,
+            Self::F16 => 1,
+            Self::Int8 => 2,
+            Self::Binary => 3,
+        }
+    }
+
+    pub(crate) fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::F32),
+            1 => Some(Self::F16),
+            2 => Some(Self::Int8),
+            3 => Some(Self::Binary),
+            _ => None,
+        }
+    }
+}
+
+/// Row-major embeddings matrix stored at a given [`Precision`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingMatrix {
+    rows: usize,
+    dim: usize,
+    storage: Storage,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Storage {
+    /// Always in standard (row-major, contiguous) layout
+    F32(Array2<f32>),
+    /// Half-float bit patterns
+    F16(Vec<u16>),
+    /// `values ≈ x / scales[row]`
+    Int8 { values: Vec<i8>, scales: Vec<f32> },
+    /// Bit `j` of a row is set when dimension `j` is positive; rows are padded
+    /// to whole words. `full` holds the f32 rows, read only to rescore the rows
+    /// the bits preselect.
+    Binary { bits: Vec<u64>, full: Arc<FullRows> },
+}
+
+/// The f32 rows of a binary matrix, row-major.
+#[derive(Debug)]
+enum FullRows {
+    /// Anonymous temporary file, deleted once the last matrix sharing it is dropped
+    File(Mutex<File>),
+    /// Used when no temporary file could be written
+    Memory(Vec<f32>),
+}
+
+impl From<Array2<f32>> for EmbeddingMatrix {
+    fn from(embeddings: Array2<f32>) -> Self {
+        Self::quantize(embeddings, Precision::F32)
+    }
+}
+
+impl EmbeddingMatrix {
+    /// Store `embeddings` at `precision`.
+    pub fn quantize(embeddings: Array2<f32>, precision: Precision) -> Self {
+        let (rows, dim) = embeddings.dim();
+        let embeddings = standard_layout(embeddings);
+        let storage = match precision {
+            Precision::F32 => Storage::F32(embeddings),
+            Precision::F16 => Storage::F16(embeddings.iter().map(|&x| f32_to_f16(x)).collect()),
+            Precision::Int8 => {
+                let (values, scales) = int8_rows(&embeddings);
+                Storage::Int8 { values, scales }
+            }
+            Precision::Binary => {
+                let data = embeddings
+                    .as_slice()
+                    .expect("standard layout rows are contiguous");
+                let bits = data.chunks(dim.max(1)).flat_map(sign_bits).collect();
+                let full = FullRows::spill_or_keep(rows, dim, |row, values| {
+                    values.copy_from_slice(&data[row * dim..(row + 1) * dim]);
+                    Ok(())
+                })
+                .expect("copying rows from memory cannot fail");
+                Storage::Binary {
+                    bits,
+                    full: Arc::new(full),
+                }
+            }
+        };
+        Self { rows, dim, storage }
+    }
+
+    /// [`quantize`](Self::quantize), logging the memory saved and the recall
+    /// lost compared to the f32 `embeddings`.
+    pub fn compress(embeddings: Array2<f32>, precision: Precision) -> Self {
+        if precision == Precision::F32 {
+            return Self::quantize(embeddings, precision);
+        }
+        let start = std::time::Instant::now();
+        let embeddings = standard_layout(embeddings);
+        let f32_bytes = embeddings.len() * 4;
+        let recall_sample: Vec<usize> = sample_rows(embeddings.nrows());
+        let queries: Vec<Vec<f32>> = recall_sample
+            .iter()
+            .map(|&row| embeddings.row(row).to_vec())
+            .collect();
+        let exact: Vec<Vec<usize>> = queries
+            .iter()
+            .map(|query| exact_top_k(&embeddings, query, RECALL_K))
+            .collect();
+
+        let matrix = Self::quantize(embeddings, precision);
+        let recall = matrix.recall(&queries, &exact);
+        tracing::info!(
+            precision = %precision,
+            num_tools = matrix.nrows(),
+            embedding_dim = matrix.ncols(),
+            memory_bytes = matrix.memory_bytes(),
+            f32_bytes,
+            recall_at_10 = recall.map(|r| format!("{:.3}", r)).unwrap_or_default(),
+            elapsed_ms = start.elapsed().as_millis(),
+            "Embeddings quantized"
+        );
+        matrix
+    }
+
+    /// Rows copied from other matrices of the same precision and dimension.
+    ///
+    /// Fails only if the f32 rows of a binary source cannot be read back.
+    pub fn gather<'a>(
+        precision: Precision,
+        dim: usize,
+        sources: impl IntoIterator<Item = (&'a EmbeddingMatrix, usize)>,
+    ) -> io::Result<Self> {
+        let sources: Vec<(&EmbeddingMatrix, usize)> = sources.into_iter().collect();
+        debug_assert!(sources
+            .iter()
+            .all(|(m, _)| m.precision() == precision && m.dim == dim));
+        let rows = sources.len();
+        let storage = match precision {
+            Precision::F32 => {
+                let mut data = Vec::with_capacity(rows * dim);
+                for (matrix, row) in &sources {
+                    data.extend_from_slice(matrix.f32_row(*row));
+                }
+                Storage::F32(
+                    Array2::from_shape_vec((rows, dim), data)
+                        .expect("gathered rows match the dimension"),
+                )
+            }
+            Precision::F16 => {
+                let mut data = Vec::with_capacity(rows * dim);
+                for (matrix, row) in &sources {
+                    if let Storage::F16(values) = &matrix.storage {
+                        data.extend_from_slice(&values[row * dim..(row + 1) * dim]);
+                    }
+                }
+                Storage::F16(data)
+            }
+            Precision::Int8 => {
+                let mut values = Vec::with_capacity(rows * dim);
+                let mut scales = Vec::with_capacity(rows);
+                for (matrix, row) in &sources {
+                    if let Storage::Int8 {
+                        values: v,
+                        scales: s,
+                    } = &matrix.storage
+                    {
+                        values.extend_from_slice(&v[row * dim..(row + 1) * dim]);
+                        scales.push(s[*row]);
+                    }
+                }
+                Storage::Int8 { values, scales }
+            }
+            Precision::Binary => {
+                let words = words_per_row(dim);
+                let mut bits = Vec::with_capacity(rows * words);
+                for (matrix, row) in &sources {
+                    if let Storage::Binary { bits: b, .. } = &matrix.storage {
+                        bits.extend_from_slice(&b[row * words..(row + 1) * words]);
+                    }
+                }
+                let full = FullRows::spill_or_keep(rows, dim, |i, values| {
+                    let (matrix, row) = sources[i];
+                    match &matrix.storage {
+                        Storage::Binary { full, .. } => {
+                            full.read(dim, &[row], |_, v| values.copy_from_slice(v))
+                        }
+                        _ => Ok(()),
+                    }
+                })?;
+                Storage::Binary {
+                    bits,
+                    full: Arc::new(full),
+                }
+            }
+        };
+        Ok(Self { rows, dim, storage })
+    }
+
+    pub fn precision(&self) -> Precision {
+        match self.storage {
+            Storage::F32(_) => Precision::F32,
+            Storage::F16(_) => Precision::F16,
+            Storage::Int8 { .. } => Precision::Int8,
+            Storage::Binary { .. } => Precision::Binary,
+        }
+    }
+
+    pub fn nrows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.dim
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Bytes of memory used by the stored values.
+    pub fn memory_bytes(&self) -> usize {
+        match &self.storage {
+            Storage::F32(data) => data.len() * 4,
+            Storage::F16(data) => data.len() * 2,
+            Storage::Int8 { values, scales } => values.len() + scales.len() * 4,
+            Storage::Binary { bits, full } => match full.as_ref() {
+                FullRows::File(_) => bits.len() * 8,
+                FullRows::Memory(values) => bits.len() * 8 + values.len() * 4,
+            },
+        }
+    }
+
+    /// Bytes written by [`write_payload`](Self::write_payload).
+    pub(crate) fn payload_bytes(&self) -> usize {
+        match &self.storage {
+            Storage::Binary { bits, .. } => bits.len() * 8 + self.rows * self.dim * 4,
+            _ => self.memory_bytes(),
+        }
+    }
+
+    /// The f32 matrix, if stored without quantization.
+    pub fn into_f32(self) -> Option<Array2<f32>> {
+        match self.storage {
+            Storage::F32(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Row `row`, dequantized.
+    pub fn row(&self, row: usize) -> Array1<f32> {
+        Array1::from_vec(self.row_vec(row))
+    }
+
+    /// Row `row`, dequantized.
+    pub fn row_vec(&self, row: usize) -> Vec<f32> {
+        let dim = self.dim;
+        match &self.storage {
+            Storage::F32(_) => self.f32_row(row).to_vec(),
+            Storage::F16(values) => {
+                let table = f16_table();
+                values[row * dim..(row + 1) * dim]
+                    .iter()
+                    .map(|&h| table[h as usize])
+                    .collect()
+            }
+            Storage::Int8 { values, scales } => values[row * dim..(row + 1) * dim]
+                .iter()
+                .map(|&v| v as f32 * scales[row])
+                .collect(),
+            Storage::Binary { full, .. } => {
+                let mut values = Vec::new();
+                match full.read(dim, &[row], |_, v| values = v.to_vec()) {
+                    Ok(()) => values,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to read binary rescoring row");
+                        let words = self.bits_row(row);
+                        (0..dim)
+                            .map(|j| sign(words, j) / (dim as f32).sqrt())
+                            .collect()
+                    }
+                }
+            }
+        }
+    }
+
+    /// Similarity of the f32 `query` to row `row`.
+    ///
+    /// For binary matrices this is only an estimate from the sign bits; see
+    /// [`rescore`](Self::rescore).
+    #[inline]
+    pub fn score(&self, query: &[f32], row: usize) -> f32 {
+        self.dense_score(query, row)
+    }
+
+    /// Exact similarity of the f32 `query` to each of `rows`.
+    ///
+    /// Binary matrices read the f32 rows back from disk; if that fails, the
+    /// sign-bit estimates of [`score`](Self::score) are returned instead.
+    pub fn rescore(&self, query: &[f32], rows: &[usize]) -> Vec<(usize, f32)> {
+        if let Storage::Binary { full, .. } = &self.storage {
+            let mut scored = Vec::with_capacity(rows.len());
+            match full.read(self.dim, rows, |row, values| {
+                scored.push((row, kernel::dot(values, query)))
+            }) {
+                Ok(()) => return scored,
+                Err(e) => tracing::warn!(error = %e, "Failed to read binary rescoring rows"),
+            }
+        }
+        rows.iter()
+            .map(|&row| (row, self.dense_score(query, row)))
+            .collect()
+    }
+
+    /// Similarity between two stored rows (for binary matrices, estimated
+    /// from their sign bits).
+    pub fn similarity(&self, a: usize, b: usize) -> f32 {
+        let dim = self.dim;
+        match &self.storage {
+            Storage::F32(_) => kernel::dot(self.f32_row(a), self.f32_row(b)),
+            Storage::F16(values) => {
+                let table = f16_table();
+                values[a * dim..(a + 1) * dim]
+                    .iter()
+                    .zip(&values[b * dim..(b + 1) * dim])
+                    .map(|(&x, &y)| table[x as usize] * table[y as usize])
+                    .sum()
+            }
+            Storage::Int8 { values, scales } => {
+                let dot: i32 = values[a * dim..(a + 1) * dim]
+                    .iter()
+                    .zip(&values[b * dim..(b + 1) * dim])
+                    .map(|(&x, &y)| x as i32 * y as i32)
+                    .sum();
+                dot as f32 * scales[a] * scales[b]
+            }
+            // The angle between two vectors is about π times the fraction of
+            // differing sign bits
+            Storage::Binary { .. } => {
+                let distance = hamming(self.bits_row(a), self.bits_row(b));
+                (std::f32::consts::PI * distance as f32 / dim.max(1) as f32).cos()
+            }
+        }
+    }
+
+    /// Score every row against `query`, calling `visit(row, score)` in row order.
+    pub fn for_each_score(&self, query: &[f32], mut visit: impl FnMut(usize, f32)) {
+        match &self.storage {
+            Storage::F32(data) => {
+                kernel::for_each_score(data.as_slice().expect("standard layout"), query, visit)
+            }
+            _ => {
+                for row in 0..self.rows {
+                    visit(row, self.dense_score(query, row));
+                }
+            }
+        }
+    }
+
+    /// For binary matrices, the `n` rows (from `allowed`, if set) whose sign
+    /// bits are closest to the query's in Hamming distance, closest first.
+    /// `None` for other precisions.
+    pub fn hamming_nearest(
+        &self,
+        query: &[f32],
+        n: usize,
+        allowed: Option<&[usize]>,
+    ) -> Option<Vec<usize>> {
+        let Storage::Binary { bits, .. } = &self.storage else {
+            return None;
+        };
+        let words = words_per_row(self.dim);
+        let query_bits: Vec<u64> = sign_bits(query).collect();
+        let distance = |row: usize| hamming(&query_bits, &bits[row * words..(row + 1) * words]);
+
+        let mut top = TopK::new(n);
+        match allowed {
+            Some(rows) => rows
+                .iter()
+                .for_each(|&row| top.push(row, -(distance(row) as f32))),
+            None => (0..self.rows).for_each(|row| top.push(row, -(distance(row) as f32))),
+        }
+        Some(top.into_sorted_vec().into_iter().map(|r| r.0).collect())
+    }
+
+    /// Mean fraction of the `exact` top rows of each query found by an exact
+    /// scan of this matrix; `None` without queries.
+    fn recall(&self, queries: &[Vec<f32>], exact: &[Vec<usize>]) -> Option<f32> {
+        let mut found = 0;
+        let mut total = 0;
+        for (query, expected) in queries.iter().zip(exact) {
+            let query = Array1::from_vec(query.clone());
+            let results = FlatIndex.search(self, &query, expected.len(), None);
+            found += results.iter().filter(|r| expected.contains(&r.0)).count();
+            total += expected.len();
+        }
+        (total > 0).then(|| found as f32 / total as f32)
+    }
+
+    fn f32_row(&self, row: usize) -> &[f32] {
+        match &self.storage {
+            Storage::F32(data) => {
+                &data.as_slice().expect("standard layout")[row * self.dim..(row + 1) * self.dim]
+            }
+            _ => unreachable!("f32_row on a quantized matrix"),
+        }
+    }
+
+    #[inline]
+    fn dense_score(&self, query: &[f32], row: usize) -> f32 {
+        let dim = self.dim;
+        match &self.storage {
+            Storage::F32(_) => kernel::dot(self.f32_row(row), query),
+            Storage::F16(values) => {
+                let table = f16_table();
+                kernel::dot_mapped(&values[row * dim..(row + 1) * dim], query, |h| {
+                    table[h as usize]
+                })
+            }
+            Storage::Int8 { values, scales } => {
+                kernel::dot_mapped(&values[row * dim..(row + 1) * dim], query, |v| v as f32)
+                    * scales[row]
+            }
+            // Each dimension contributes its query value, signed by the row's bit
+            Storage::Binary { .. } => {
+                let words = self.bits_row(row);
+                let dot: f32 = query
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &q)| q * sign(words, j))
+                    .sum();
+                dot / (dim as f32).sqrt()
+            }
+        }
+    }
+
+    /// Sign bits of `row`, for binary matrices.
+    fn bits_row(&self, row: usize) -> &[u64] {
+        match &self.storage {
+            Storage::Binary { bits, .. } => {
+                let words = words_per_row(self.dim);
+                &bits[row * words..(row + 1) * words]
+            }
+            _ => unreachable!("bits_row on a non-binary matrix"),
+        }
+    }
+
+    /// Write the stored values (little-endian) as laid out in the embeddings
+    /// cache: f32 and f16 rows as-is; int8 rows preceded by all row scales;
+    /// binary as its sign bits followed by the f32 rows.
+    pub(crate) fn write_payload(&self, writer: &mut impl Write) -> io::Result<()> {
+        match &self.storage {
+            Storage::F32(data) => {
+                for &value in data.iter() {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            Storage::F16(data) => {
+                for &value in data {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            Storage::Int8 { values, scales } => {
+                for &scale in scales {
+                    writer.write_all(&scale.to_le_bytes())?;
+                }
+                for &value in values {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            Storage::Binary { bits, full } => {
+                for &word in bits {
+                    writer.write_all(&word.to_le_bytes())?;
+                }
+                // Row by row, so searches rescoring meanwhile are not held up
+                for row in 0..self.rows {
+                    let mut written = Ok(());
+                    full.read(self.dim, &[row], |_, values| {
+                        written = values
+                            .iter()
+                            .try_for_each(|value| writer.write_all(&value.to_le_bytes()));
+                    })?;
+                    written?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read values written by [`write_payload`](Self::write_payload).
+    pub(crate) fn read_payload(
+        reader: &mut impl Read,
+        precision: Precision,
+        rows: usize,
+        dim: usize,
+    ) -> io::Result<Self> {
+        let storage = match precision {
+            Precision::F32 => {
+                let data = read_values(reader, rows * dim, f32::from_le_bytes)?;
+                Storage::F32(
+                    Array2::from_shape_vec((rows, dim), data)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+                )
+            }
+            Precision::F16 => Storage::F16(read_values(reader, rows * dim, u16::from_le_bytes)?),
+            Precision::Int8 => {
+                let scales = read_values(reader, rows, f32::from_le_bytes)?;
+                let values = read_values(reader, rows * dim, i8::from_le_bytes)?;
+                Storage::Int8 { values, scales }
+            }
+            Precision::Binary => {
+                let bits = read_values(reader, rows * words_per_row(dim), u64::from_le_bytes)?;
+                let data = read_values(reader, rows * dim, f32::from_le_bytes)?;
+                let full = FullRows::spill_or_keep(rows, dim, |row, values| {
+                    values.copy_from_slice(&data[row * dim..(row + 1) * dim]);
+                    Ok(())
+                })?;
+                Storage::Binary {
+                    bits,
+                    full: Arc::new(full),
+                }
+            }
+        };
+        Ok(Self { rows, dim, storage })
+    }
+}
+
+impl FullRows {
+    /// Write `rows` rows of `dim` values, each filled in by `fill(row, values)`,
+    /// to a new anonymous temporary file.
+    fn spill(
+        rows: usize,
+        dim: usize,
+        mut fill: impl FnMut(usize, &mut [f32]) -> io::Result<()>,
+    ) -> io::Result<Self> {
+        let mut writer = BufWriter::new(tempfile::tempfile()?);
+        let mut values = vec![0.0; dim];
+        for row in 0..rows {
+            fill(row, &mut values)?;
+            for value in &values {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        Ok(Self::File(Mutex::new(file)))
+    }
+
+    /// [`spill`](Self::spill), falling back to memory if the temporary file
+    /// cannot be written; fails only when `fill` does.
+    fn spill_or_keep(
+        rows: usize,
+        dim: usize,
+        mut fill: impl FnMut(usize, &mut [f32]) -> io::Result<()>,
+    ) -> io::Result<Self> {
+        match Self::spill(rows, dim, &mut fill) {
+            Ok(spilled) => Ok(spilled),
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    "Failed to spill binary rescoring rows to disk, keeping them in memory"
+                );
+                let mut data = vec![0.0; rows * dim];
+                for (row, values) in data.chunks_exact_mut(dim.max(1)).enumerate() {
+                    fill(row, values)?;
+                }
+                Ok(Self::Memory(data))
+            }
+        }
+    }
+
+    /// Call `visit(row, values)` for each of `rows`, in order.
+    fn read(
+        &self,
+        dim: usize,
+        rows: &[usize],
+        mut visit: impl FnMut(usize, &[f32]),
+    ) -> io::Result<()> {
+        match self {
+            Self::Memory(data) => {
+                for &row in rows {
+                    visit(row, &data[row * dim..(row + 1) * dim]);
+                }
+            }
+            Self::File(file) => {
+                // Seek and read must not interleave with another reader's
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                let mut bytes = vec![0u8; dim * 4];
+                let mut values = vec![0.0; dim];
+                for &row in rows {
+                    file.seek(SeekFrom::Start((row * dim * 4) as u64))?;
+                    file.read_exact(&mut bytes)?;
+                    for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(4)) {
+                        *value = f32::from_le_bytes(chunk.try_into().expect("4-byte chunks"));
+                    }
+                    visit(row, &values);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every value, row-major.
+    fn to_vec(&self) -> io::Result<Vec<f32>> {
+        match self {
+            Self::Memory(data) => Ok(data.clone()),
+            Self::File(file) => {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                let mut bytes = Vec::new();
+                file.seek(SeekFrom::Start(0))?;
+                file.read_to_end(&mut bytes)?;
+                Ok(bytes
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes(chunk.try_into().expect("4-byte chunks")))
+                    .collect())
+            }
+        }
+    }
+}
+
+impl PartialEq for FullRows {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.to_vec(), other.to_vec()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+fn read_values<T, const N: usize>(
+    reader: &mut impl Read,
+    count: usize,
+    decode: fn([u8; N]) -> T,
+) -> io::Result<Vec<T>> {
+    let mut values = Vec::with_capacity(count);
+    let mut bytes = [0u8; N];
+    for _ in 0..count {
+        reader.read_exact(&mut bytes)?;
+        values.push(decode(bytes));
+    }
+    Ok(values)
+}
+
+fn standard_layout(embeddings: Array2<f32>) -> Array2<f32> {
+    if embeddings.is_standard_layout() {
+        embeddings
+    } else {
+        embeddings.as_standard_layout().into_owned()
+    }
+}
+
+/// Symmetric int8 quantization of each row: `values ≈ x / scales[row]`.
+fn int8_rows(embeddings: &Array2<f32>) -> (Vec<i8>, Vec<f32>) {
+    let mut values = Vec::with_capacity(embeddings.len());
+    let mut scales = Vec::with_capacity(embeddings.nrows());
+    for row in embeddings.outer_iter() {
+        let max = row.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+        let scale = max / 127.0;
+        let inverse = if scale > 0.0 { 1.0 / scale } else { 0.0 };
+        values.extend(
+            row.iter()
+                .map(|&x| (x * inverse).round().clamp(-127.0, 127.0) as i8),
+        );
+        scales.push(scale);
+    }
+    (values, scales)
+}
+
+fn words_per_row(dim: usize) -> usize {
+    dim.div_ceil(64)
+}
+
+/// Sign bits of `values`, packed 64 to a word.
+fn sign_bits(values: &[f32]) -> impl Iterator<Item = u64> + '_ {
+    values.chunks(64).map(|chunk| {
+        chunk
+            .iter()
+            .enumerate()
+            .fold(0u64, |word, (bit, &x)| word | (((x > 0.0) as u64) << bit))
+    })
+}
+
+/// `1.0` if bit `j` of `words` is set, otherwise `-1.0`.
+#[inline]
+fn sign(words: &[u64], j: usize) -> f32 {
+    if words[j / 64] >> (j % 64) & 1 == 1 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn hamming(a: &[u64], b: &[u64]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+/// Up to [`RECALL_QUERIES`] rows spread evenly over `rows`.
+fn sample_rows(rows: usize) -> Vec<usize> {
+    let step = rows.div_ceil(RECALL_QUERIES).max(1);
+    (0..rows).step_by(step).collect()
+}
+
+fn exact_top_k(embeddings: &Array2<f32>, query: &[f32], k: usize) -> Vec<usize> {
+    let mut top = TopK::new(k.min(embeddings.nrows()));
+    for (row, doc) in embeddings.outer_iter().enumerate() {
+        let doc = doc.as_slice().expect("standard layout rows are contiguous");
+        top.push(row, kernel::dot(doc, query));
+    }
+    top.into_sorted_vec().into_iter().map(|r| r.0).collect()
+}
+
+/// f32 value of every half-float bit pattern.
+fn f16_table() -> &'static [f32] {
+    static TABLE: OnceLock<Vec<f32>> = OnceLock::new();
+    TABLE.get_or_init(|| (0..=u16::MAX).map(f16_to_f32).collect())
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    match exponent {
+        0 => {
+            // Zero or subnormal: mantissa × 2^-24
+            let magnitude = mantissa as f32 / (1u32 << 24) as f32;
+            if sign != 0 {
+                -magnitude
+            } else {
+                magnitude
+            }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 112) << 23) | (mantissa << 13)),
+    }
+}
+
+/// Nearest half float to `value` (ties to even).
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        // Subnormal: shift the mantissa (with its implicit bit) into place
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        return sign | round_shift(mantissa, shift) as u16;
+    }
+    // A carry out of the mantissa correctly bumps the exponent
+    sign | round_shift(((half_exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+/// `value >> shift`, rounded to nearest with ties to even.
+fn round_shift(value: u32, shift: u32) -> u32 {
+    let truncated = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::random_embeddings;
+
+    #[test]
+    fn test_f16_conversion() {
+        for value in [0.0, -0.0, 1.0, -2.5, 0.333_333, 65504.0, 6.0e-8, -1.0e-5] {
+            let converted = f16_to_f32(f32_to_f16(value));
+            let tolerance = value.abs() * 1e-3 + 6.0e-8;
+            assert!((converted - value).abs() <= tolerance, "{}", value);
+        }
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(1.0e6), 0x7c00);
+        // 1 + 2^-11 is halfway between 1 and the next half float: ties to even
+        assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0), 0x3c00);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+    }
+
+    #[test]
+    fn test_quantized_scores_approximate_f32() {
+        let embeddings = random_embeddings(20, 96, 4);
+        let query = embeddings.row(3).to_vec();
+        for (precision, tolerance) in [(Precision::F16, 1e-3), (Precision::Int8, 2e-2)] {
+            let matrix = EmbeddingMatrix::quantize(embeddings.clone(), precision);
+            assert_eq!(matrix.precision(), precision);
+            for row in 0..20 {
+                let exact = kernel::dot(embeddings.row(row).as_slice().unwrap(), &query);
+                assert!(
+                    (matrix.score(&query, row) - exact).abs() < tolerance,
+                    "{} row {}",
+                    precision,
+                    row
+                );
+                let reconstructed = matrix.row(row);
+                assert!(
+                    (matrix.similarity(3, row) - reconstructed.dot(&matrix.row(3))).abs() < 1e-3
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_precision_memory_and_recall() {
+        let embeddings = random_embeddings(400, 128, 8);
+        let f32_bytes = 400 * 128 * 4;
+        let queries: Vec<Vec<f32>> = random_embeddings(30, 128, 9)
+            .outer_iter()
+            .map(|q| q.to_vec())
+            .collect();
+        let exact: Vec<Vec<usize>> = queries
+            .iter()
+            .map(|q| exact_top_k(&embeddings, q, 10))
+            .collect();
+
+        for (precision, max_bytes, min_recall) in [
+            (Precision::F16, f32_bytes / 2, 0.99),
+            (Precision::Int8, f32_bytes / 4 + 400 * 4, 0.9),
+            (Precision::Binary, f32_bytes / 32, 0.65),
+        ] {
+            let matrix = EmbeddingMatrix::compress(embeddings.clone(), precision);
+            assert_eq!(matrix.memory_bytes(), max_bytes, "{}", precision);
+            let recall = matrix.recall(&queries, &exact).unwrap();
+            assert!(recall >= min_recall, "{} recall {}", precision, recall);
+        }
+    }
+
+    #[test]
+    fn test_gather_and_payload_round_trip() {
+        let embeddings = random_embeddings(6, 70, 2);
+        for precision in [
+            Precision::F32,
+            Precision::F16,
+            Precision::Int8,
+            Precision::Binary,
+        ] {
+            let matrix = EmbeddingMatrix::quantize(embeddings.clone(), precision);
+            let gathered =
+                EmbeddingMatrix::gather(precision, 70, [(&matrix, 4), (&matrix, 1)]).unwrap();
+            assert_eq!(gathered.nrows(), 2);
+            assert_eq!(gathered.row(0), matrix.row(4));
+            assert_eq!(gathered.row(1), matrix.row(1));
+
+            let mut bytes = Vec::new();
+            matrix.write_payload(&mut bytes).unwrap();
+            let read =
+                EmbeddingMatrix::read_payload(&mut bytes.as_slice(), precision, 6, 70).unwrap();
+            assert_eq!(read, matrix);
+        }
+    }
+
+    #[test]
+    fn test_binary_rescoring_reads_exact_rows() {
+        let embeddings = random_embeddings(20, 96, 4);
+        let matrix = EmbeddingMatrix::quantize(embeddings.clone(), Precision::Binary);
+        let query = embeddings.row(3).to_vec();
+
+        let rescored = matrix.rescore(&query, &[7, 3, 12]);
+        let rows: Vec<usize> = rescored.iter().map(|r| r.0).collect();
+        assert_eq!(rows, vec![7, 3, 12]);
+        for (row, score) in rescored {
+            let exact = kernel::dot(embeddings.row(row).as_slice().unwrap(), &query);
+            assert!((score - exact).abs() < 1e-5, "row {}", row);
+            assert_eq!(matrix.row(row), embeddings.row(row));
+        }
+        // The estimates from the sign bits still rank the query's own row first
+        let best = (0..20)
+            .max_by(|&a, &b| matrix.score(&query, a).total_cmp(&matrix.score(&query, b)))
+            .unwrap();
+        assert_eq!(best, 3);
+        assert!((matrix.similarity(3, 3) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_binary_rescoring_beats_hamming_order() {
+        let embeddings = random_embeddings(400, 128, 8);
+        let matrix = EmbeddingMatrix::quantize(embeddings.clone(), Precision::Binary);
+        let queries = random_embeddings(30, 128, 9);
+
+        let (mut hamming_found, mut rescored_found) = (0, 0);
+        for query in queries.outer_iter() {
+            let query = query.to_vec();
+            let exact = exact_top_k(&embeddings, &query, 10);
+            let hamming = matrix.hamming_nearest(&query, 10, None).unwrap();
+            let rescored = FlatIndex.search(&matrix, &Array1::from_vec(query), 10, None);
+            hamming_found += hamming.iter().filter(|r| exact.contains(r)).count();
+            rescored_found += rescored.iter().filter(|r| exact.contains(&r.0)).count();
+        }
+        let hamming_recall = hamming_found as f32 / 300.0;
+        let rescored_recall = rescored_found as f32 / 300.0;
+        assert!(
+            rescored_recall > hamming_recall + 0.2,
+            "hamming {} rescored {}",
+            hamming_recall,
+            rescored_recall
+        );
+    }
+
+    #[test]
+    fn test_hamming_prefilter_respects_allowed_rows() {
+        let embeddings = random_embeddings(50, 64, 6);
+        let matrix = EmbeddingMatrix::quantize(embeddings.clone(), Precision::Binary);
+        let query = embeddings.row(17).to_vec();
+
+        let nearest = matrix.hamming_nearest(&query, 5, None).unwrap();
+        assert_eq!(nearest[0], 17);
+        let nearest = matrix.hamming_nearest(&query, 5, Some(&[2, 9])).unwrap();
+        assert_eq!(nearest.len(), 2);
+        assert!(EmbeddingMatrix::from(embeddings)
+            .hamming_nearest(&query, 5, None)
+            .is_none());
+    }
+}
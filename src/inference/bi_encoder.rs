@@ -15,9 +15,12 @@ use ort::{
     session::{builder::GraphOptimizationLevel, Session},
     value::Tensor,
 };
+use sha2::{Digest, Sha256};
 use std::cell::UnsafeCell;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 use tokenizers::Tokenizer;
 
 /// Bi-encoder model pool for generating text embeddings with concurrent access.
@@ -36,10 +39,17 @@ pub struct BiEncoderModel {
     sessions: Vec<UnsafeCell<Session>>,
     /// Lock-free queue of available session indices
     available: Arc<ArrayQueue<usize>>,
+    /// Callers blocked in `acquire_session_timeout`
+    waiters: AtomicUsize,
+    /// Signalled (under `release_lock`) when a session is released while someone waits
+    released: Condvar,
+    release_lock: Mutex<()>,
     /// Shared tokenizer (thread-safe for encode operations)
     tokenizer: Tokenizer,
     max_length: usize,
     embedding_dim: usize,
+    /// SHA256 of the model, tokenizer and max length (identifies its embeddings)
+    fingerprint: [u8; 32],
 }
 
 impl BiEncoderModel {
@@ -66,9 +76,19 @@ impl BiEncoderModel {
         let model_bytes = std::fs::read(model_path)
             .map_err(|e| AppError::ModelError(format!("Failed to read bi-encoder model: {}", e)))?;
 
+        // Same model, tokenizer and truncation produce the same embeddings
+        let tokenizer_bytes = std::fs::read(tokenizer_path)
+            .map_err(|e| AppError::ModelError(format!("Failed to read bi-encoder tokenizer: {}", e)))?;
+        let mut hasher = Sha256::new();
+        hasher.update(&model_bytes);
+        hasher.update(&tokenizer_bytes);
+        hasher.update((max_length as u64).to_le_bytes());
+        let fingerprint = hasher.finalize().into();
+
         // Create pool of sessions
         let mut sessions = Vec::with_capacity(pool_size);
         let available = Arc::new(ArrayQueue::new(pool_size));
+        let mut declared_dim = None;
 
         for i in 0..pool_size {
             let session = Session::builder()
@@ -81,6 +101,9 @@ impl BiEncoderModel {
                 .map_err(|e| AppError::ModelError(e.to_string()))?
                 .commit_from_memory(&model_bytes)
                 .map_err(|e: ort::Error| AppError::ModelError(e.to_string()))?;
+            if i == 0 {
+                declared_dim = declared_hidden_size(&session);
+            }
 
             sessions.push(UnsafeCell::new(session));
             available
@@ -97,13 +120,24 @@ impl BiEncoderModel {
             "Bi-encoder session pool loaded"
         );
 
-        Ok(Self {
+        let mut model = Self {
             sessions,
             available,
+            waiters: AtomicUsize::new(0),
+            released: Condvar::new(),
+            release_lock: Mutex::new(()),
             tokenizer,
             max_length,
-            embedding_dim: 768, // BGE-base embedding dimension
-        })
+            embedding_dim: declared_dim.unwrap_or(0),
+            fingerprint,
+        };
+        // Models exported with a dynamic hidden size are measured instead
+        if declared_dim.is_none() {
+            model.embedding_dim = model.encode_batch(&["dimension probe".to_string()])?.ncols();
+        }
+        tracing::debug!(embedding_dim = model.embedding_dim, "Bi-encoder embedding dimension");
+
+        Ok(model)
     }
 
     /// Legacy single-session load (for batch encoding at startup).
@@ -114,6 +148,16 @@ impl BiEncoderModel {
         Self::load_pool(model_path, tokenizer_path, max_length, 1, 4)
     }
 
+    /// Size of the embeddings this model produces (768 for BGE-base).
+    pub fn dim(&self) -> usize {
+        self.embedding_dim
+    }
+
+    /// Identifies the embeddings this model produces (for query cache snapshots).
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.fingerprint
+    }
+
     /// Acquire a session from the pool for exclusive use.
     ///
     /// Returns the session index, which MUST be released via `release_session()`.
@@ -123,9 +167,46 @@ impl BiEncoderModel {
             .ok_or_else(|| AppError::ResourceError("No available bi-encoder sessions".into()))
     }
 
+    /// Acquire a session, waiting up to `timeout` for one to be released.
+    ///
+    /// For catalog writes, which should queue behind searches rather than
+    /// fail while every session is busy.
+    pub fn acquire_session_timeout(&self, timeout: Duration) -> Result<usize> {
+        if let Some(index) = self.available.pop() {
+            return Ok(index);
+        }
+        let deadline = Instant::now() + timeout;
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let mut guard = self.release_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let acquired = loop {
+            // Checked under the lock, so a release cannot slip in before the wait
+            if let Some(index) = self.available.pop() {
+                break Some(index);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break None;
+            }
+            guard = self
+                .released
+                .wait_timeout(guard, remaining)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        };
+        drop(guard);
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+        acquired.ok_or_else(|| {
+            AppError::ResourceError("Timed out waiting for a bi-encoder session".into())
+        })
+    }
+
     /// Release a session back to the pool.
     pub fn release_session(&self, index: usize) {
         let _ = self.available.push(index);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            let _guard = self.release_lock.lock().unwrap_or_else(|e| e.into_inner());
+            self.released.notify_one();
+        }
     }
 
     /// Encode a single text into an embedding vector using a specific session.
@@ -262,6 +343,14 @@ impl BiEncoderModel {
         result
     }
 
+    /// Encode a batch of texts, waiting up to `timeout` for a free session.
+    pub fn encode_batch_waiting(&self, texts: &[String], timeout: Duration) -> Result<Array2<f32>> {
+        let session_idx = self.acquire_session_timeout(timeout)?;
+        let result = self.encode_batch_with_session(session_idx, texts);
+        self.release_session(session_idx);
+        result
+    }
+
     /// Compute cosine similarity between a query embedding and multiple document embeddings.
     ///
     /// # Arguments
@@ -274,11 +363,9 @@ impl BiEncoderModel {
         query_embedding: &Array1<f32>,
         doc_embeddings: &Array2<f32>,
     ) -> Vec<f32> {
-        // Since embeddings are L2-normalized, cosine similarity = dot product
-        doc_embeddings
-            .outer_iter()
-            .map(|doc| query_embedding.dot(&doc))
-            .collect()
+        // Since embeddings are L2-normalized, cosine similarity = dot product,
+        // computed for every document as one matrix-vector product
+        doc_embeddings.dot(query_embedding).to_vec()
     }
 }
 
@@ -287,6 +374,17 @@ impl BiEncoderModel {
 // - ArrayQueue::pop() returns each index to at most one caller at a time
 // - ArrayQueue::push() returns the index to the pool for reuse
 // - Between pop and push, only one thread can access each UnsafeCell<Session>
+// - The waiter count, Condvar and Mutex only signal releases; they guard no session
 // - Tokenizer's encode methods are thread-safe
 unsafe impl Send for BiEncoderModel {}
 unsafe impl Sync for BiEncoderModel {}
+
+/// Hidden size of the model's `last_hidden_state` output, if the graph fixes it.
+fn declared_hidden_size(session: &Session) -> Option<usize> {
+    let output = session
+        .outputs()
+        .iter()
+        .find(|o| o.name() == "last_hidden_state")?;
+    let dim = *output.dtype().tensor_shape()?.last()?;
+    usize::try_from(dim).ok().filter(|&d| d > 0)
+}
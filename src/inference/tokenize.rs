@@ -1,4 +1,5 @@
 use crate::error::{AppError, Result};
+use crate::ingestion::TokenCounter;
 use ndarray::Array2;
 use std::path::Path;
 use tokenizers::Tokenizer;
@@ -79,4 +80,19 @@ impl TokenizerWrapper {
 
         Ok((input_ids, attention_mask, token_type_ids))
     }
+
+    /// Number of tokens the model sees for a single document, including
+    /// special tokens. Returns `usize::MAX` if the text cannot be encoded.
+    pub fn count_tokens(&self, text: &str) -> usize {
+        self.tokenizer
+            .encode(text, true)
+            .map(|e| e.get_ids().len())
+            .unwrap_or(usize::MAX)
+    }
+}
+
+impl TokenCounter for TokenizerWrapper {
+    fn count_tokens(&self, text: &str) -> usize {
+        TokenizerWrapper::count_tokens(self, text)
+    }
 }
@@ -3,29 +3,120 @@
 //! This module provides the core functionality to parse MCP `list_tools` JSON-RPC
 //! responses and transform them into `EncapureTool` records optimized for
 //! semantic search via the reranker.
+//!
+//! Besides MCP, tool lists in OpenAI function-calling and Anthropic tool-use
+//! shapes (and plain arrays of tool objects) are accepted; see [`ToolFormat`].
+//! OpenAPI 3.x specifications are routed to [`crate::ingestion::openapi`].
 
 use crate::error::AppError;
-use crate::ingestion::types::EncapureTool;
-use serde_json::Value;
-use std::collections::HashSet;
+use crate::ingestion::identity::{qualified_id, DuplicatePolicy, DEFAULT_ID_SEPARATOR};
+use crate::ingestion::items::{atomize_item_sections, has_item_sections};
+use crate::ingestion::metadata::{definition_metadata, definition_tags, MetadataManifest};
+use crate::ingestion::openapi::{is_openapi_document, openapi_operations};
+use crate::ingestion::schema::{SchemaLimits, SchemaWalker};
+use crate::ingestion::template::{humanize_name, ViewFields, ViewTemplate};
+use crate::ingestion::truncate::{grapheme_prefix, shrink_to_fit, truncate_chars, TokenBudget};
+use crate::ingestion::types::{EncapureTool, ItemKind, ToolAnnotations};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::str::FromStr;
+use std::sync::Arc;
 
 /// Result type for atomizer operations
 pub type AtomizerResult<T> = std::result::Result<T, AppError>;
 
-/// Maximum description length before truncation
+/// Maximum description length (in chars) before truncation
 const MAX_DESCRIPTION_LENGTH: usize = 500;
 
-/// Maximum parameter description length for the summary
+/// Maximum parameter description length (in chars) for the summary
 const MAX_PARAM_DESC_LENGTH: usize = 50;
 
+/// Parameter summary length after which remaining parameters are elided
+const MAX_PARAM_SUMMARY_LENGTH: usize = 1000;
+
+/// Shape of a tool definition document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolFormat {
+    /// Detect the format from the document and each tool's fields.
+    #[default]
+    Auto,
+    /// MCP `list_tools` response: `{"result": {"tools": [{name, description, inputSchema}]}}`
+    Mcp,
+    /// OpenAI function calling: `[{"type": "function", "function": {name, description, parameters}}]`
+    OpenAi,
+    /// Anthropic tool use: `[{name, description, input_schema}]`
+    Anthropic,
+    /// Plain array of tool objects (`inputSchema`, `input_schema` or `parameters`)
+    Array,
+    /// OpenAPI 3.x specification: one tool per HTTP operation
+    OpenApi,
+}
+
+impl FromStr for ToolFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "" | "auto" => Ok(Self::Auto),
+            "mcp" => Ok(Self::Mcp),
+            "openai" | "open-ai" | "function" => Ok(Self::OpenAi),
+            "anthropic" | "claude" => Ok(Self::Anthropic),
+            "array" | "bare" | "plain" => Ok(Self::Array),
+            "openapi" | "swagger" => Ok(Self::OpenApi),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown tool format '{}' (expected auto, mcp, openai, anthropic, array or openapi)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options controlling how tool documents are atomized.
+#[derive(Debug, Clone)]
+pub struct AtomizeOptions {
+    /// Document format; `Auto` detects it per document and per tool
+    pub format: ToolFormat,
+    /// Template for the stage-1 (bi-encoder) view stored in `embedding_view`
+    pub embedding_template: ViewTemplate,
+    /// Template for the stage-2 (cross-encoder) view stored in `inference_view`
+    pub rerank_template: ViewTemplate,
+    /// Optional token budget for the rendered `inference_view`; the description,
+    /// then the parameter summary, are shortened until the view fits
+    pub token_budget: Option<TokenBudget>,
+    /// Separator between server and name in each tool's qualified `id`
+    pub id_separator: String,
+    /// How tools sharing a qualified id are resolved when a catalog is merged
+    pub duplicate_policy: DuplicatePolicy,
+    /// Sidecar tags and metadata merged into every item
+    pub metadata: Option<Arc<MetadataManifest>>,
+}
+
+impl Default for AtomizeOptions {
+    fn default() -> Self {
+        Self {
+            format: ToolFormat::default(),
+            embedding_template: ViewTemplate::default(),
+            rerank_template: ViewTemplate::default(),
+            token_budget: None,
+            id_separator: DEFAULT_ID_SEPARATOR.to_string(),
+            duplicate_policy: DuplicatePolicy::default(),
+            metadata: None,
+        }
+    }
+}
+
 /// Transform an MCP list_tools JSON-RPC response into EncapureTool records.
 ///
+/// Equivalent to [`atomize_tools_with_options`] with format auto-detection,
+/// so OpenAI, Anthropic and bare-array tool lists are accepted as well.
+///
 /// # Arguments
-/// * `json` - The full JSON-RPC response (must contain `result.tools` array)
+/// * `json` - The full JSON-RPC response (or any supported tool list)
 /// * `server_name` - The origin MCP server name (used in inference_view)
 ///
 /// # Errors
-/// Returns `AppError::AtomizerError` if the JSON is not a valid MCP response.
+/// Returns `AppError::AtomizerError` if the JSON is not a recognized tool document.
 /// Individual malformed tools are logged and skipped (partial success model).
 ///
 /// # Example
@@ -37,158 +128,485 @@ const MAX_PARAM_DESC_LENGTH: usize = 50;
 /// let tools = atomize_tools(&response, "filesystem")?;
 /// ```
 pub fn atomize_tools(json: &Value, server_name: &str) -> AtomizerResult<Vec<EncapureTool>> {
-    // Navigate to result.tools array
-    let tools_array = extract_tools_array(json)?;
+    atomize_tools_with_options(json, server_name, &AtomizeOptions::default())
+}
 
-    // Pre-allocate with capacity for efficiency
-    let mut results = Vec::with_capacity(tools_array.len());
-
-    // Process each tool with error tolerance
-    for (idx, tool_value) in tools_array.iter().enumerate() {
-        match normalize_tool(tool_value, server_name) {
-            Ok(tool) => results.push(tool),
-            Err(e) => {
-                // Log and skip malformed tools (partial success model)
-                tracing::warn!(
-                    index = idx,
-                    error = %e,
-                    "Skipping malformed tool definition"
-                );
-            }
+/// Transform a tool document into EncapureTool records using explicit options.
+///
+/// All formats normalize into the same `inference_view`; the original tool
+/// object is kept unchanged in `raw_definition`.
+pub fn atomize_tools_with_options(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    atomize_document(json, server_name, options)?.into_items(server_name)
+}
+
+/// A tool definition that could not be atomized.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedTool {
+    /// Kind of the rejected item
+    pub kind: ItemKind,
+    /// Position of the definition in its list (`tools`, `prompts`, ...)
+    pub index: usize,
+    /// Tool name, if the definition has one
+    pub name: Option<String>,
+    /// Why the definition was rejected
+    pub reason: String,
+}
+
+/// Every outcome of atomizing one document: parsed tools, skipped
+/// definitions and non-fatal warnings.
+#[derive(Debug, Default)]
+pub struct AtomizedDocument {
+    /// Number of definitions (tools, prompts, resources or OpenAPI operations) in the document
+    pub total: usize,
+    pub tools: Vec<EncapureTool>,
+    pub skipped: Vec<SkippedTool>,
+    pub warnings: Vec<String>,
+}
+
+impl AtomizedDocument {
+    /// Append the outcome of atomizing another section of the same document.
+    pub fn extend(&mut self, other: AtomizedDocument) {
+        self.total += other.total;
+        self.tools.extend(other.tools);
+        self.skipped.extend(other.skipped);
+        self.warnings.extend(other.warnings);
+    }
+
+    /// Log skipped definitions and return the parsed items.
+    ///
+    /// # Errors
+    /// Returns `AppError::AtomizerError` if every definition failed to parse.
+    pub fn into_items(self, server_name: &str) -> AtomizerResult<Vec<EncapureTool>> {
+        // Log and skip malformed items (partial success model)
+        for skipped in &self.skipped {
+            tracing::warn!(
+                kind = skipped.kind.as_str(),
+                index = skipped.index,
+                error = %skipped.reason,
+                "Skipping malformed definition"
+            );
+        }
+
+        if self.tools.is_empty() && self.total > 0 {
+            // All definitions failed - likely a structural problem
+            return Err(AppError::AtomizerError(
+                "All tool definitions failed to parse".into(),
+            ));
         }
+
+        tracing::debug!(
+            total = self.total,
+            parsed = self.tools.len(),
+            server = server_name,
+            "Tool atomization complete"
+        );
+
+        Ok(self.tools)
     }
+}
 
-    if results.is_empty() && !tools_array.is_empty() {
-        // All tools failed - likely a structural problem
-        return Err(AppError::AtomizerError(
-            "All tool definitions failed to parse".into(),
+/// Atomize a tool document without logging or failing on skipped tools.
+///
+/// # Errors
+/// Returns `AppError::AtomizerError` only if the document itself is not a
+/// recognized tool document.
+pub fn atomize_document(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<AtomizedDocument> {
+    // OpenAPI specs describe operations rather than a tool array
+    if options.format == ToolFormat::OpenApi
+        || (options.format == ToolFormat::Auto && is_openapi_document(json))
+    {
+        let operations = openapi_operations(json)?;
+        return Ok(atomize_definitions(
+            &operations,
+            server_name,
+            ToolFormat::Mcp,
+            options,
         ));
     }
 
-    tracing::debug!(
-        total = tools_array.len(),
-        parsed = results.len(),
-        server = server_name,
-        "Tool atomization complete"
-    );
+    // Locate the tools array for the requested (or detected) document shape
+    let mut document = match extract_tools_array(json, options.format) {
+        Ok(tools_array) => atomize_definitions(tools_array, server_name, options.format, options),
+        // Auto-detected documents may hold only prompts and/or resources
+        Err(_) if options.format == ToolFormat::Auto && has_item_sections(json) => {
+            AtomizedDocument::default()
+        }
+        Err(e) => return Err(e),
+    };
+
+    if options.format == ToolFormat::Auto {
+        document.extend(atomize_item_sections(json, server_name, options));
+    }
 
-    Ok(results)
+    Ok(document)
 }
 
-/// Extract the tools array from an MCP JSON-RPC response.
+/// Normalize each tool definition, collecting failures instead of aborting.
+pub(crate) fn atomize_definitions(
+    definitions: &[Value],
+    server_name: &str,
+    format: ToolFormat,
+    options: &AtomizeOptions,
+) -> AtomizedDocument {
+    // Pre-allocate with capacity for efficiency
+    let mut document = AtomizedDocument {
+        total: definitions.len(),
+        tools: Vec::with_capacity(definitions.len()),
+        ..Default::default()
+    };
+
+    for (idx, tool_value) in definitions.iter().enumerate() {
+        match normalize_tool(
+            tool_value,
+            server_name,
+            format,
+            options,
+            &mut document.warnings,
+        ) {
+            Ok(tool) => document.tools.push(tool),
+            Err(e) => document.skipped.push(SkippedTool {
+                kind: ItemKind::Tool,
+                index: idx,
+                name: tool_parts(tool_value, format)
+                    .0
+                    .get("name")
+                    .map(|n| n.as_str().map_or_else(|| n.to_string(), String::from)),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    document
+}
+
+/// Extract the tools array from a tool document.
 ///
-/// Navigates the path: root -> result -> tools
-fn extract_tools_array(json: &Value) -> AtomizerResult<&Vec<Value>> {
-    json.get("result")
-        .and_then(|r| r.get("tools"))
-        .and_then(|t| t.as_array())
-        .ok_or_else(|| {
+/// - MCP: root -> result -> tools
+/// - Other formats: a top-level array, or a top-level `tools` array
+/// - Auto: any of the above
+fn extract_tools_array(json: &Value, format: ToolFormat) -> AtomizerResult<&Vec<Value>> {
+    let mcp_tools = || {
+        json.get("result")
+            .and_then(|r| r.get("tools"))
+            .and_then(|t| t.as_array())
+    };
+    let list_tools = || {
+        json.as_array()
+            .or_else(|| json.get("tools").and_then(|t| t.as_array()))
+    };
+
+    match format {
+        ToolFormat::Mcp => mcp_tools().ok_or_else(|| {
             AppError::AtomizerError("Expected 'result.tools' array in MCP response".into())
-        })
+        }),
+        ToolFormat::Auto => mcp_tools().or_else(list_tools).ok_or_else(|| {
+            AppError::AtomizerError(
+                "Expected 'result.tools' array in MCP response, a 'tools' array, or a top-level tool array"
+                    .into(),
+            )
+        }),
+        _ => list_tools().ok_or_else(|| {
+            AppError::AtomizerError("Expected a top-level tool array or a 'tools' array".into())
+        }),
+    }
+}
+
+/// Detect the format of a single tool object from its distinguishing fields.
+fn detect_tool_format(tool_value: &Value) -> ToolFormat {
+    if tool_value.get("function").is_some_and(|f| f.is_object()) {
+        ToolFormat::OpenAi
+    } else if tool_value.get("input_schema").is_some() {
+        ToolFormat::Anthropic
+    } else if tool_value.get("inputSchema").is_some() {
+        ToolFormat::Mcp
+    } else {
+        ToolFormat::Array
+    }
+}
+
+/// Locate the name-carrying object and input schema for a tool in the given format.
+pub(crate) fn tool_parts(tool_value: &Value, format: ToolFormat) -> (&Value, Option<&Value>) {
+    let format = match format {
+        ToolFormat::Auto => detect_tool_format(tool_value),
+        other => other,
+    };
+
+    match format {
+        ToolFormat::OpenAi => {
+            // Accept both the wrapped form and a bare legacy `functions` entry
+            let function = tool_value
+                .get("function")
+                .filter(|f| f.is_object())
+                .unwrap_or(tool_value);
+            (function, function.get("parameters"))
+        }
+        ToolFormat::Anthropic => (tool_value, tool_value.get("input_schema")),
+        ToolFormat::Mcp => (tool_value, tool_value.get("inputSchema")),
+        _ => (
+            tool_value,
+            tool_value
+                .get("inputSchema")
+                .or_else(|| tool_value.get("input_schema"))
+                .or_else(|| tool_value.get("parameters")),
+        ),
+    }
 }
 
 /// Transform a single tool definition into an EncapureTool.
-fn normalize_tool(tool_value: &Value, server_name: &str) -> AtomizerResult<EncapureTool> {
+///
+/// Non-fatal issues (missing description, truncation) are appended to `warnings`.
+fn normalize_tool(
+    tool_value: &Value,
+    server_name: &str,
+    format: ToolFormat,
+    options: &AtomizeOptions,
+    warnings: &mut Vec<String>,
+) -> AtomizerResult<EncapureTool> {
+    let (definition, input_schema) = tool_parts(tool_value, format);
+
     // Extract name (required field)
-    let name = tool_value
+    let name = definition
         .get("name")
         .and_then(|v| v.as_str())
         .ok_or_else(|| {
             AppError::AtomizerError(format!(
                 "Tool missing required 'name' field: {:?}",
-                tool_value.get("name")
+                definition.get("name")
             ))
         })?;
 
     // Extract description (optional, default to empty string)
-    let description = tool_value
+    let description = definition
         .get("description")
         .and_then(|v| v.as_str())
         .unwrap_or("");
 
-    // Truncate long descriptions for the inference view
-    let truncated_desc = truncate_description(description);
+    // Behavior hints; malformed annotations are ignored rather than dropping the tool
+    let annotations = match definition.get("annotations") {
+        Some(value) => {
+            serde_json::from_value::<ToolAnnotations>(value.clone()).unwrap_or_else(|e| {
+                warnings.push(format!("Tool '{}' has invalid annotations: {}", name, e));
+                ToolAnnotations::default()
+            })
+        }
+        None => ToolAnnotations::default(),
+    };
 
-    // Build parameter summary from inputSchema
-    let param_summary = build_param_summary(tool_value.get("inputSchema"));
+    // Optional display fields: MCP `title` (or `annotations.title`) and `tags`
+    let title = definition
+        .get("title")
+        .or_else(|| definition.get("annotations").and_then(|a| a.get("title")))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let mut tags = definition_tags(definition);
+    let metadata = definition_metadata(definition, &mut tags);
 
-    // Construct the inference view string
-    let inference_view = build_inference_view(name, server_name, &truncated_desc, &param_summary);
+    let mut tool = build_item(
+        ItemParts {
+            kind: ItemKind::Tool,
+            name,
+            key: name,
+            title,
+            description,
+            // Build parameter summary from the input schema
+            params: build_param_summary(input_schema),
+            tags,
+            metadata,
+        },
+        server_name,
+        tool_value,
+        options,
+        warnings,
+    );
+    tool.annotations = annotations;
 
-    Ok(EncapureTool::new(
-        name.to_string(),
-        server_name.to_string(),
-        inference_view,
-        tool_value.clone(),
-    ))
+    Ok(tool)
+}
+
+/// Fields extracted from one MCP item, before rendering.
+pub(crate) struct ItemParts<'a> {
+    pub kind: ItemKind,
+    pub name: &'a str,
+    /// Unique key within the server (tool/prompt name, resource URI)
+    pub key: &'a str,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub params: String,
+    pub tags: Vec<String>,
+    pub metadata: Map<String, Value>,
 }
 
-/// Truncate description to MAX_DESCRIPTION_LENGTH with ellipsis.
+/// Render the per-stage views for an item and build its record.
 ///
-/// Attempts to truncate at a word boundary when possible.
-fn truncate_description(desc: &str) -> String {
-    if desc.len() <= MAX_DESCRIPTION_LENGTH {
-        return desc.to_string();
+/// Descriptions are truncated, and the rerank view is fitted to the token
+/// budget if one is configured. Non-fatal issues are appended to `warnings`.
+pub(crate) fn build_item(
+    parts: ItemParts,
+    server_name: &str,
+    raw_definition: &Value,
+    options: &AtomizeOptions,
+    warnings: &mut Vec<String>,
+) -> EncapureTool {
+    let ItemParts {
+        kind,
+        name,
+        key,
+        title,
+        description,
+        params: param_summary,
+        mut tags,
+        mut metadata,
+    } = parts;
+
+    // Manifest entries are merged first so that `{tags}` renders every tag
+    let id = qualified_id(server_name, &kind.id_key(key), &options.id_separator);
+    if let Some(manifest) = &options.metadata {
+        manifest.apply(server_name, name, &id, &mut tags, &mut metadata);
+    }
+    let joined_tags = tags.join(", ");
+
+    if description.trim().is_empty() {
+        warnings.push(format!("{} '{}' has no description", kind.label(), name));
     }
 
-    let truncated = &desc[..MAX_DESCRIPTION_LENGTH];
+    // Truncate long descriptions for the inference view
+    let truncated_desc = truncate_description(description);
+    if truncated_desc != description {
+        warnings.push(format!(
+            "{} '{}' description truncated to {} chars",
+            kind.label(),
+            name,
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+
+    let humanized_name = humanize_name(name);
+
+    let fields = ViewFields {
+        name,
+        humanized_name: &humanized_name,
+        server: server_name,
+        title,
+        description: &truncated_desc,
+        params: &param_summary,
+        tags: &joined_tags,
+        kind: kind.as_str(),
+    };
 
-    // Try to truncate at a word boundary
-    match truncated.rfind(' ') {
-        Some(pos) if pos > MAX_DESCRIPTION_LENGTH - 50 => {
-            format!("{}...", &truncated[..pos])
+    // Shorten description, then params, until the rerank view fits the budget
+    let (fitted_desc, fitted_params);
+    let fields = match &options.token_budget {
+        Some(budget) if !budget.fits(&options.rerank_template.render(&fields)) => {
+            let fits = |description: &str, params: &str| {
+                budget.fits(&options.rerank_template.render(&ViewFields {
+                    description,
+                    params,
+                    ..fields
+                }))
+            };
+            fitted_desc = shrink_to_fit(&truncated_desc, |d| fits(d, &param_summary));
+            fitted_params = if fits(&fitted_desc, &param_summary) {
+                param_summary.clone()
+            } else {
+                shrink_to_fit(&param_summary, |p| fits(&fitted_desc, p))
+            };
+            warnings.push(format!(
+                "{} '{}' inference view shortened to fit the {}-token budget",
+                kind.label(),
+                name,
+                budget.max_tokens()
+            ));
+            ViewFields {
+                description: &fitted_desc,
+                params: &fitted_params,
+                ..fields
+            }
         }
-        _ => format!("{}...", truncated),
+        _ => fields,
+    };
+
+    // Construct the per-stage view strings
+    let mut item = EncapureTool::new(
+        name.to_string(),
+        server_name.to_string(),
+        options.rerank_template.render(&fields),
+        raw_definition.clone(),
+    );
+    item.kind = kind;
+    item.id = id;
+    item.title = Some(title.to_string()).filter(|t| !t.is_empty());
+    item.tags = tags;
+    item.metadata = metadata;
+    if options.embedding_template != options.rerank_template {
+        item.embedding_view = options.embedding_template.render(&fields);
     }
+
+    item
+}
+
+/// Truncate description to MAX_DESCRIPTION_LENGTH chars with ellipsis.
+///
+/// Attempts to truncate at a word boundary when possible; never splits a
+/// multi-byte character or grapheme cluster.
+fn truncate_description(desc: &str) -> String {
+    truncate_chars(desc, MAX_DESCRIPTION_LENGTH)
 }
 
 /// Build parameter summary from inputSchema.properties.
 ///
 /// Format: "param1*: type (desc), param2: type"
-/// Required parameters are marked with an asterisk (*).
-fn build_param_summary(input_schema: Option<&Value>) -> String {
+/// Required parameters are marked with an asterisk (*). Types are derived by
+/// walking the schema (see [`SchemaWalker`]): `$ref`s are resolved, unions
+/// and nullable types are flattened, enum values and nested fields are listed.
+pub(crate) fn build_param_summary(input_schema: Option<&Value>) -> String {
     let Some(schema) = input_schema else {
         return "none".to_string();
     };
 
-    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
-        return "none".to_string();
-    };
+    let walker = SchemaWalker::new(schema, SchemaLimits::default());
+    let (properties, required) = walker.object_fields(schema);
 
     if properties.is_empty() {
         return "none".to_string();
     }
 
-    // Get required params set for marking
-    let required: HashSet<&str> = schema
-        .get("required")
-        .and_then(|r| r.as_array())
-        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect())
-        .unwrap_or_default();
-
-    // Build "name: type (description)" entries
-    let params: Vec<String> = properties
-        .iter()
-        .map(|(name, prop)| format_param(name, prop, required.contains(name.as_str())))
-        .collect();
+    // Build "name: type (description)" entries while they fit the summary budget
+    let mut params: Vec<String> = Vec::with_capacity(properties.len());
+    let mut used = 0;
+    for (name, prop) in &properties {
+        let entry = format_param(name, prop, required.contains(name), &walker);
+        if used + entry.len() > MAX_PARAM_SUMMARY_LENGTH {
+            params.push(format!("+{} more", properties.len() - params.len()));
+            break;
+        }
+        used += entry.len() + 2;
+        params.push(entry);
+    }
 
     params.join(", ")
 }
 
 /// Format a single parameter for the summary.
-fn format_param(name: &str, prop: &Value, is_required: bool) -> String {
-    let param_type = prop.get("type").and_then(|t| t.as_str()).unwrap_or("any");
+fn format_param(name: &str, prop: &Value, is_required: bool, walker: &SchemaWalker) -> String {
+    let param_type = walker.type_label(prop);
 
+    // Prefer the description next to the $ref, then the referenced schema's own
     let brief_desc = prop
         .get("description")
+        .or_else(|| walker.resolve(prop).and_then(|p| p.get("description")))
         .and_then(|d| d.as_str())
         .map(|d| {
             // Take first sentence or first MAX_PARAM_DESC_LENGTH chars
-            let end = d
-                .find('.')
-                .unwrap_or(MAX_PARAM_DESC_LENGTH)
-                .min(MAX_PARAM_DESC_LENGTH);
-            &d[..end.min(d.len())]
+            let sentence = d.find(['.', '。']).map_or(d, |end| &d[..end]);
+            grapheme_prefix(sentence, MAX_PARAM_DESC_LENGTH)
         })
         .unwrap_or("");
 
@@ -201,16 +619,6 @@ fn format_param(name: &str, prop: &Value, is_required: bool) -> String {
     }
 }
 
-/// Construct the inference view string for reranker embedding.
-///
-/// Format: "TOOL: <name> | CONTEXT: <server_name> | FUNC: <description> | INPUTS: <param_summary>"
-fn build_inference_view(name: &str, server: &str, desc: &str, params: &str) -> String {
-    format!(
-        "TOOL: {} | CONTEXT: {} | FUNC: {} | INPUTS: {}",
-        name, server, desc, params
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,6 +696,85 @@ mod tests {
         assert_eq!(tools[0].name, "valid_tool");
     }
 
+    #[test]
+    fn test_atomize_document_reports_skipped_and_warnings() {
+        let response = json!({
+            "result": {
+                "tools": [
+                    { "description": "No name here" },
+                    { "name": 42 },
+                    { "name": "valid_tool" }
+                ]
+            }
+        });
+
+        let document = atomize_document(&response, "server", &AtomizeOptions::default()).unwrap();
+        assert_eq!(document.total, 3);
+        assert_eq!(document.tools.len(), 1);
+        assert_eq!(document.skipped.len(), 2);
+        assert_eq!(document.skipped[0].index, 0);
+        assert_eq!(document.skipped[0].name, None);
+        assert_eq!(document.skipped[1].name.as_deref(), Some("42"));
+        assert!(document.skipped[1].reason.contains("'name'"));
+        assert_eq!(
+            document.warnings,
+            vec!["Tool 'valid_tool' has no description"]
+        );
+    }
+
+    #[test]
+    fn test_annotations_and_title_are_parsed() {
+        let response = json!({
+            "result": {
+                "tools": [
+                    {
+                        "name": "read_file",
+                        "title": "Read File",
+                        "annotations": { "readOnlyHint": true, "openWorldHint": false }
+                    },
+                    {
+                        "name": "delete_file",
+                        "annotations": { "title": "Delete File", "destructiveHint": true }
+                    },
+                    {
+                        "name": "append_log",
+                        "annotations": { "readOnlyHint": false, "destructiveHint": false }
+                    },
+                    { "name": "mystery" }
+                ]
+            }
+        });
+
+        let tools = atomize_tools(&response, "fs").unwrap();
+
+        assert_eq!(tools[0].title.as_deref(), Some("Read File"));
+        assert_eq!(tools[0].annotations.read_only_hint, Some(true));
+        assert_eq!(tools[0].annotations.open_world_hint, Some(false));
+        assert!(tools[0].is_read_only() && !tools[0].is_destructive());
+
+        assert_eq!(tools[1].title.as_deref(), Some("Delete File"));
+        assert!(tools[1].is_destructive());
+
+        assert!(!tools[2].is_read_only() && !tools[2].is_destructive());
+
+        // Undeclared hints fall back to the MCP defaults
+        assert_eq!(tools[3].title, None);
+        assert!(!tools[3].is_read_only() && tools[3].is_destructive());
+    }
+
+    #[test]
+    fn test_invalid_annotations_keep_tool() {
+        let response = json!([{ "name": "x", "annotations": { "readOnlyHint": "yes" } }]);
+
+        let document = atomize_document(&response, "s", &AtomizeOptions::default()).unwrap();
+        assert_eq!(document.tools.len(), 1);
+        assert_eq!(document.tools[0].annotations, ToolAnnotations::default());
+        assert!(document
+            .warnings
+            .iter()
+            .any(|w| w.contains("invalid annotations")));
+    }
+
     #[test]
     fn test_truncate_long_description() {
         let long_desc = "A".repeat(600);
@@ -297,6 +784,78 @@ mod tests {
         assert!(truncated.ends_with("..."));
     }
 
+    #[test]
+    fn test_truncate_multibyte_description() {
+        let hebrew = "כלי לקריאת קבצים מהדיסק ".repeat(40);
+        let japanese = "ファイルを読み込みます。".repeat(60);
+
+        for desc in [hebrew, japanese] {
+            let truncated = truncate_description(&desc);
+            assert!(truncated.chars().count() <= MAX_DESCRIPTION_LENGTH + 3);
+            assert!(truncated.ends_with("..."));
+        }
+    }
+
+    #[test]
+    fn test_param_description_multibyte() {
+        let schema = json!({
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "読み込むファイルのパス。相対パスも使用できます"
+                },
+                "name": { "type": "string", "description": "שם ".repeat(30) }
+            }
+        });
+
+        let summary = build_param_summary(Some(&schema));
+        assert!(summary.contains("path: string (読み込むファイルのパス)"));
+        assert!(summary.contains("name: string (שם"));
+    }
+
+    #[test]
+    fn test_token_budget_shortens_description_then_params() {
+        use crate::ingestion::truncate::TokenCounter;
+        use std::sync::Arc;
+
+        struct WordCounter;
+        impl TokenCounter for WordCounter {
+            fn count_tokens(&self, text: &str) -> usize {
+                text.split_whitespace().count()
+            }
+        }
+
+        let tool = json!({
+            "name": "read_file",
+            "description": "Read the complete contents of a file from disk as text",
+            "inputSchema": {
+                "properties": {
+                    "path": { "type": "string" },
+                    "encoding": { "type": "string" }
+                }
+            }
+        });
+        let with_budget = |max_tokens| AtomizeOptions {
+            token_budget: Some(TokenBudget::new(Arc::new(WordCounter), max_tokens)),
+            ..Default::default()
+        };
+
+        // Default view has 24 words; the description is shortened first
+        let tools = atomize_tools_with_options(&json!([tool]), "fs", &with_budget(18)).unwrap();
+        let view = &tools[0].inference_view;
+        assert!(view.split_whitespace().count() <= 18);
+        assert!(view.contains("FUNC: Read the complete contents of... |"));
+        assert!(view.ends_with("INPUTS: encoding: string, path: string"));
+
+        // A tighter budget drops the description and shortens the params
+        let tools = atomize_tools_with_options(&json!([tool]), "fs", &with_budget(10)).unwrap();
+        let view = &tools[0].inference_view;
+        assert!(view.split_whitespace().count() <= 10);
+        assert!(view.contains("FUNC:  |"));
+        assert!(view.contains("INPUTS: encoding:"));
+        assert!(!view.contains("path"));
+    }
+
     #[test]
     fn test_truncate_short_description() {
         let short_desc = "Short description";
@@ -336,7 +895,13 @@ mod tests {
 
     #[test]
     fn test_inference_view_format() {
-        let view = build_inference_view("my_tool", "my_server", "Does things", "x: int, y: str");
+        let view = ViewTemplate::default().render(&ViewFields {
+            name: "my_tool",
+            server: "my_server",
+            description: "Does things",
+            params: "x: int, y: str",
+            ..Default::default()
+        });
         assert_eq!(
             view,
             "TOOL: my_tool | CONTEXT: my_server | FUNC: Does things | INPUTS: x: int, y: str"
@@ -371,6 +936,222 @@ mod tests {
         assert!(tools[0].inference_view.contains("items: array"));
     }
 
+    #[test]
+    fn test_atomize_openai_function_list() {
+        let tools_json = json!([{
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "description": "Get the weather for a city.",
+                "parameters": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"]
+                }
+            }
+        }]);
+
+        let tools = atomize_tools(&tools_json, "weather").unwrap();
+
+        assert_eq!(tools[0].name, "get_weather");
+        assert_eq!(
+            tools[0].inference_view,
+            "TOOL: get_weather | CONTEXT: weather | FUNC: Get the weather for a city. | INPUTS: city*: string"
+        );
+        assert_eq!(tools[0].raw_definition["type"], "function");
+    }
+
+    #[test]
+    fn test_atomize_anthropic_tool_list() {
+        let tools_json = json!({
+            "tools": [{
+                "name": "get_weather",
+                "description": "Get the weather for a city.",
+                "input_schema": {
+                    "type": "object",
+                    "properties": { "city": { "type": "string" } },
+                    "required": ["city"]
+                }
+            }]
+        });
+
+        let tools = atomize_tools(&tools_json, "weather").unwrap();
+
+        assert!(tools[0].inference_view.contains("INPUTS: city*: string"));
+        assert!(tools[0].raw_definition.get("input_schema").is_some());
+    }
+
+    #[test]
+    fn test_formats_produce_identical_views() {
+        let schema = json!({ "properties": { "q": { "type": "string" } } });
+        let mcp = json!({ "result": { "tools": [
+            { "name": "search", "description": "Search.", "inputSchema": schema }
+        ] } });
+        let openai = json!([{ "type": "function", "function":
+            { "name": "search", "description": "Search.", "parameters": schema } }]);
+        let anthropic = json!([
+            { "name": "search", "description": "Search.", "input_schema": schema }
+        ]);
+        let bare = json!([
+            { "name": "search", "description": "Search.", "parameters": schema }
+        ]);
+
+        let view = |doc: &Value| atomize_tools(doc, "s").unwrap()[0].inference_view.clone();
+        let expected = view(&mcp);
+        assert_eq!(view(&openai), expected);
+        assert_eq!(view(&anthropic), expected);
+        assert_eq!(view(&bare), expected);
+    }
+
+    #[test]
+    fn test_explicit_format_override() {
+        let bare = json!([{ "name": "search", "parameters": { "properties": { "q": {} } } }]);
+
+        // MCP format requires the JSON-RPC envelope
+        let mcp = AtomizeOptions {
+            format: ToolFormat::Mcp,
+            ..Default::default()
+        };
+        assert!(atomize_tools_with_options(&bare, "s", &mcp).is_err());
+
+        // Anthropic format only reads `input_schema`
+        let anthropic = AtomizeOptions {
+            format: ToolFormat::Anthropic,
+            ..Default::default()
+        };
+        let tools = atomize_tools_with_options(&bare, "s", &anthropic).unwrap();
+        assert!(tools[0].inference_view.ends_with("INPUTS: none"));
+    }
+
+    #[test]
+    fn test_separate_stage_templates() {
+        let response = json!({ "result": { "tools": [{
+            "name": "createIssue",
+            "title": "Create Issue",
+            "description": "Open a new issue.",
+            "tags": ["issues", "write"]
+        }] } });
+        let options = AtomizeOptions {
+            embedding_template: ViewTemplate::parse("{humanized_name}: {description} [{tags}]")
+                .unwrap(),
+            rerank_template: ViewTemplate::parse("{title} ({server}) - {description}").unwrap(),
+            ..Default::default()
+        };
+
+        let tools = atomize_tools_with_options(&response, "github", &options).unwrap();
+
+        assert_eq!(
+            tools[0].embedding_view,
+            "create issue: Open a new issue. [issues, write]"
+        );
+        assert_eq!(
+            tools[0].inference_view,
+            "Create Issue (github) - Open a new issue."
+        );
+    }
+
+    #[test]
+    fn test_tags_and_metadata_merge_with_manifest() {
+        let response = json!([{
+            "name": "query",
+            "description": "Run a SQL query",
+            "tags": ["sql"],
+            "metadata": { "owner": "data", "environment": "staging" }
+        }]);
+        let manifest = MetadataManifest {
+            tools: [(
+                "warehouse::query".to_string(),
+                json!({ "environment": "prod", "tags": ["pii"] })
+                    .as_object()
+                    .cloned()
+                    .unwrap(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+        let options = AtomizeOptions {
+            embedding_template: ViewTemplate::parse("{description} [{tags}]").unwrap(),
+            metadata: Some(Arc::new(manifest)),
+            ..Default::default()
+        };
+
+        let tools = atomize_tools_with_options(&response, "warehouse", &options).unwrap();
+
+        assert_eq!(tools[0].tags, vec!["sql", "pii"]);
+        assert_eq!(tools[0].metadata["owner"], "data");
+        assert_eq!(tools[0].metadata["environment"], "prod");
+        assert_eq!(tools[0].embedding_view, "Run a SQL query [sql, pii]");
+    }
+
+    #[test]
+    fn test_default_embedding_view_matches_inference_view() {
+        let response = json!({ "result": { "tools": [{ "name": "t", "description": "d" }] } });
+        let tools = atomize_tools(&response, "s").unwrap();
+        assert_eq!(tools[0].embedding_view, tools[0].inference_view);
+    }
+
+    #[test]
+    fn test_tool_format_from_str() {
+        assert_eq!("OpenAI".parse::<ToolFormat>().unwrap(), ToolFormat::OpenAi);
+        assert_eq!("".parse::<ToolFormat>().unwrap(), ToolFormat::Auto);
+        assert!("yaml".parse::<ToolFormat>().is_err());
+    }
+
+    #[test]
+    fn test_param_summary_walks_schema() {
+        let schema = json!({
+            "type": "object",
+            "$defs": {
+                "Region": { "type": "string", "enum": ["us-east-1", "us-west-2"] }
+            },
+            "properties": {
+                "region": { "$ref": "#/$defs/Region", "description": "AWS region." },
+                "name": { "type": ["string", "null"] },
+                "size": { "anyOf": [{ "type": "integer" }, { "type": "string" }] },
+                "spec": {
+                    "type": "object",
+                    "properties": { "cpu": { "type": "integer" }, "labels": { "type": "object" } }
+                }
+            },
+            "required": ["region"]
+        });
+
+        let summary = build_param_summary(Some(&schema));
+        assert!(summary.contains("region*: string [us-east-1|us-west-2] (AWS region)"));
+        assert!(summary.contains("name: string|null"));
+        assert!(summary.contains("size: integer|string"));
+        assert!(summary.contains("spec: object {cpu: integer, labels: object}"));
+    }
+
+    #[test]
+    fn test_param_summary_budget_elides_remaining_params() {
+        let properties: serde_json::Map<String, Value> = (0..200)
+            .map(|i| (format!("parameter_{:03}", i), json!({ "type": "string" })))
+            .collect();
+        let schema = json!({ "properties": properties });
+
+        let summary = build_param_summary(Some(&schema));
+        assert!(summary.len() < MAX_PARAM_SUMMARY_LENGTH + 100);
+        assert!(summary.ends_with(" more"));
+    }
+
+    #[test]
+    fn test_param_summary_budget_counts_long_entries() {
+        let values: Vec<String> = (0..6)
+            .map(|i| format!("{}{}", "region_".repeat(10), i))
+            .collect();
+        let prop = json!({ "type": "string", "enum": values, "description": "x".repeat(80) });
+        let properties: serde_json::Map<String, Value> = (0..20)
+            .map(|i| (format!("parameter_{:02}", i), prop.clone()))
+            .collect();
+        let schema = json!({ "properties": properties });
+
+        let summary = build_param_summary(Some(&schema));
+        let (entries, elided) = summary.rsplit_once(", ").unwrap();
+        assert!(entries.len() <= MAX_PARAM_SUMMARY_LENGTH);
+        assert!(elided.starts_with('+') && elided.ends_with(" more"));
+    }
+
     #[test]
     fn test_param_description_truncation() {
         let schema = json!({
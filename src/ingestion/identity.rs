@@ -0,0 +1,194 @@
+//! Qualified tool identities and duplicate handling.
+//!
+//! A tool's `name` is only unique within its server, so a merged catalog keys
+//! every tool on a qualified id: `<server><separator><name>`, e.g.
+//! `github::create_issue`. Two tools with the same qualified id are duplicates
+//! and are resolved according to a [`DuplicatePolicy`].
+
+use crate::error::AppError;
+use crate::ingestion::atomizer::AtomizerResult;
+use crate::ingestion::types::EncapureTool;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+/// Separator between server and tool name used when none is configured.
+pub const DEFAULT_ID_SEPARATOR: &str = "::";
+
+/// Build the qualified id for a tool.
+pub fn qualified_id(server: &str, name: &str, separator: &str) -> String {
+    format!("{}{}{}", server, separator, name)
+}
+
+/// What to do when two tools share a qualified id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DuplicatePolicy {
+    /// Keep every tool; later duplicates get a `#2`, `#3`, ... id suffix.
+    #[default]
+    KeepAll,
+    /// Keep the first tool loaded and drop later duplicates.
+    FirstWins,
+    /// Fail catalog loading.
+    Error,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "" | "keep-all" | "keep" => Ok(Self::KeepAll),
+            "first-wins" | "first" => Ok(Self::FirstWins),
+            "error" | "fail" => Ok(Self::Error),
+            other => Err(AppError::ValidationError(format!(
+                "Unknown duplicate tool policy '{}' (expected keep-all, first-wins or error)",
+                other
+            ))),
+        }
+    }
+}
+
+impl DuplicatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::KeepAll => "keep-all",
+            Self::FirstWins => "first-wins",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Apply `policy` to tools sharing a qualified id, preserving load order.
+///
+/// # Errors
+/// Returns `AppError::ValidationError` on the first duplicate under
+/// [`DuplicatePolicy::Error`].
+pub fn resolve_duplicates(
+    tools: Vec<EncapureTool>,
+    policy: DuplicatePolicy,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    let mut seen: HashSet<String> = HashSet::with_capacity(tools.len());
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut resolved = Vec::with_capacity(tools.len());
+
+    for mut tool in tools {
+        if seen.insert(tool.id.clone()) {
+            resolved.push(tool);
+            continue;
+        }
+
+        match policy {
+            DuplicatePolicy::Error => {
+                return Err(AppError::ValidationError(format!(
+                    "Duplicate tool id '{}'",
+                    tool.id
+                )));
+            }
+            DuplicatePolicy::FirstWins => {
+                tracing::warn!(id = %tool.id, "Dropping duplicate tool (first wins)");
+            }
+            DuplicatePolicy::KeepAll => {
+                let count = occurrences.entry(tool.id.clone()).or_insert(1);
+                let original = tool.id.clone();
+                // Skip suffixes already taken by a tool literally named "x#2"
+                loop {
+                    *count += 1;
+                    tool.id = format!("{}#{}", original, count);
+                    if seen.insert(tool.id.clone()) {
+                        break;
+                    }
+                }
+                tracing::warn!(
+                    original = %original,
+                    id = %tool.id,
+                    "Duplicate tool id, keeping both"
+                );
+                resolved.push(tool);
+            }
+        }
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(server: &str, name: &str, desc: &str) -> EncapureTool {
+        EncapureTool::new(
+            name.to_string(),
+            server.to_string(),
+            desc.to_string(),
+            json!({ "name": name }),
+        )
+    }
+
+    fn ids(tools: &[EncapureTool]) -> Vec<&str> {
+        tools.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_same_name_on_different_servers_is_not_a_duplicate() {
+        let tools = vec![
+            tool("github", "create_issue", "a"),
+            tool("jira", "create_issue", "b"),
+        ];
+
+        let resolved = resolve_duplicates(tools, DuplicatePolicy::Error).unwrap();
+        assert_eq!(
+            ids(&resolved),
+            vec!["github::create_issue", "jira::create_issue"]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let tools = || {
+            vec![
+                tool("github", "create_issue", "first"),
+                tool("github", "create_issue", "second"),
+                tool("github", "create_issue", "third"),
+            ]
+        };
+
+        let kept = resolve_duplicates(tools(), DuplicatePolicy::KeepAll).unwrap();
+        assert_eq!(
+            ids(&kept),
+            vec![
+                "github::create_issue",
+                "github::create_issue#2",
+                "github::create_issue#3"
+            ]
+        );
+        assert!(kept.iter().all(|t| t.name == "create_issue"));
+
+        let first = resolve_duplicates(tools(), DuplicatePolicy::FirstWins).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].inference_view, "first");
+
+        assert!(resolve_duplicates(tools(), DuplicatePolicy::Error).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_policy_from_str() {
+        assert_eq!(
+            "first_wins".parse::<DuplicatePolicy>().unwrap(),
+            DuplicatePolicy::FirstWins
+        );
+        assert_eq!(
+            "".parse::<DuplicatePolicy>().unwrap(),
+            DuplicatePolicy::KeepAll
+        );
+        assert!("newest".parse::<DuplicatePolicy>().is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Atomization of MCP prompts and resources.
+//!
+//! Prompts (`prompts/list`), resources (`resources/list`) and resource
+//! templates (`resources/templates/list`) are indexed next to tools so agents
+//! can discover them with the same search. Each item is rendered through the
+//! same view templates; `{params}` holds the prompt arguments or the resource
+//! URI.
+
+use crate::error::AppError;
+use crate::ingestion::atomizer::{
+    build_item, build_param_summary, AtomizeOptions, AtomizedDocument, AtomizerResult, ItemParts,
+    SkippedTool,
+};
+use crate::ingestion::metadata::{definition_metadata, definition_tags};
+use crate::ingestion::types::{EncapureTool, ItemKind};
+use serde_json::{json, Map, Value};
+
+/// List key holding each non-tool item kind, as returned by the MCP list methods.
+const ITEM_SECTIONS: [(&str, ItemKind); 3] = [
+    ("prompts", ItemKind::Prompt),
+    ("resources", ItemKind::Resource),
+    ("resourceTemplates", ItemKind::ResourceTemplate),
+];
+
+/// Transform an MCP `prompts/list` response into searchable records.
+///
+/// # Errors
+/// Returns `AppError::AtomizerError` if the document has no `prompts` array
+/// (at `result.prompts` or top level), or if every prompt fails to parse.
+pub fn atomize_prompts(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    let prompts = list_section(json, "prompts").ok_or_else(|| {
+        AppError::AtomizerError("Expected 'result.prompts' array in MCP response".into())
+    })?;
+
+    atomize_section(prompts, ItemKind::Prompt, server_name, options).into_items(server_name)
+}
+
+/// Transform an MCP `resources/list` or `resources/templates/list` response
+/// into searchable records.
+///
+/// # Errors
+/// Returns `AppError::AtomizerError` if the document has neither a `resources`
+/// nor a `resourceTemplates` array, or if every resource fails to parse.
+pub fn atomize_resources(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    let mut document = AtomizedDocument::default();
+    let mut found = false;
+
+    // Everything but prompts
+    for &(key, kind) in &ITEM_SECTIONS[1..] {
+        if let Some(items) = list_section(json, key) {
+            found = true;
+            document.extend(atomize_section(items, kind, server_name, options));
+        }
+    }
+
+    if !found {
+        return Err(AppError::AtomizerError(
+            "Expected 'result.resources' or 'result.resourceTemplates' array in MCP response"
+                .into(),
+        ));
+    }
+
+    document.into_items(server_name)
+}
+
+/// Whether a document holds any prompt or resource list.
+pub(crate) fn has_item_sections(json: &Value) -> bool {
+    ITEM_SECTIONS
+        .iter()
+        .any(|(key, _)| list_section(json, key).is_some())
+}
+
+/// Atomize every prompt and resource list present in a document.
+pub(crate) fn atomize_item_sections(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizedDocument {
+    let mut document = AtomizedDocument::default();
+    for (key, kind) in ITEM_SECTIONS {
+        if let Some(items) = list_section(json, key) {
+            document.extend(atomize_section(items, kind, server_name, options));
+        }
+    }
+    document
+}
+
+/// Locate a list at `result.<key>` (JSON-RPC response) or `<key>` (bare result).
+fn list_section<'a>(json: &'a Value, key: &str) -> Option<&'a Vec<Value>> {
+    json.get("result")
+        .and_then(|r| r.get(key))
+        .or_else(|| json.get(key))
+        .and_then(|v| v.as_array())
+}
+
+fn atomize_section(
+    items: &[Value],
+    kind: ItemKind,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizedDocument {
+    let mut document = AtomizedDocument {
+        total: items.len(),
+        tools: Vec::with_capacity(items.len()),
+        ..Default::default()
+    };
+
+    for (idx, value) in items.iter().enumerate() {
+        let result = match kind {
+            ItemKind::Prompt => {
+                normalize_prompt(value, server_name, options, &mut document.warnings)
+            }
+            _ => normalize_resource(value, kind, server_name, options, &mut document.warnings),
+        };
+        match result {
+            Ok(item) => document.tools.push(item),
+            Err(e) => document.skipped.push(SkippedTool {
+                kind,
+                index: idx,
+                name: value.get("name").and_then(|n| n.as_str()).map(String::from),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    document
+}
+
+/// Transform a single prompt definition.
+///
+/// Arguments are summarized like tool parameters (all prompt arguments are strings).
+fn normalize_prompt(
+    value: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+    warnings: &mut Vec<String>,
+) -> AtomizerResult<EncapureTool> {
+    let name = value.get("name").and_then(|v| v.as_str()).ok_or_else(|| {
+        AppError::AtomizerError(format!(
+            "Prompt missing required 'name' field: {:?}",
+            value.get("name")
+        ))
+    })?;
+
+    // Express arguments as an object schema so they share the parameter summary format
+    let arguments = value
+        .get("arguments")
+        .and_then(|a| a.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for argument in arguments {
+        let Some(arg_name) = argument.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        let mut schema = json!({ "type": "string" });
+        if let Some(desc) = argument.get("description") {
+            schema["description"] = desc.clone();
+        }
+        properties.insert(arg_name.to_string(), schema);
+        if argument.get("required").and_then(|r| r.as_bool()) == Some(true) {
+            required.push(arg_name);
+        }
+    }
+    let schema = json!({ "properties": properties, "required": required });
+    let mut tags = definition_tags(value);
+    let metadata = definition_metadata(value, &mut tags);
+
+    Ok(build_item(
+        ItemParts {
+            kind: ItemKind::Prompt,
+            name,
+            key: name,
+            title: str_field(value, "title"),
+            description: str_field(value, "description"),
+            params: build_param_summary(Some(&schema)),
+            tags,
+            metadata,
+        },
+        server_name,
+        value,
+        options,
+        warnings,
+    ))
+}
+
+/// Transform a single resource or resource template definition.
+///
+/// Resources are keyed by URI. A missing `name` falls back to the URI.
+fn normalize_resource(
+    value: &Value,
+    kind: ItemKind,
+    server_name: &str,
+    options: &AtomizeOptions,
+    warnings: &mut Vec<String>,
+) -> AtomizerResult<EncapureTool> {
+    let uri_field = match kind {
+        ItemKind::ResourceTemplate => "uriTemplate",
+        _ => "uri",
+    };
+    let uri = value
+        .get(uri_field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            AppError::AtomizerError(format!(
+                "{} missing required '{}' field",
+                kind.label(),
+                uri_field
+            ))
+        })?;
+    let name = value.get("name").and_then(|v| v.as_str()).unwrap_or(uri);
+
+    let mut params = format!("uri: {}", uri);
+    if let Some(mime) = value.get("mimeType").and_then(|v| v.as_str()) {
+        params.push_str(&format!(", mimeType: {}", mime));
+    }
+    let mut tags = definition_tags(value);
+    let metadata = definition_metadata(value, &mut tags);
+
+    Ok(build_item(
+        ItemParts {
+            kind,
+            name,
+            key: uri,
+            title: str_field(value, "title"),
+            description: str_field(value, "description"),
+            params,
+            tags,
+            metadata,
+        },
+        server_name,
+        value,
+        options,
+        warnings,
+    ))
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::atomizer::atomize_tools;
+
+    #[test]
+    fn test_atomize_prompts() {
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "result": {
+                "prompts": [{
+                    "name": "code_review",
+                    "title": "Request Code Review",
+                    "description": "Asks the LLM to analyze code quality",
+                    "arguments": [
+                        { "name": "code", "description": "The code to review", "required": true },
+                        { "name": "language" }
+                    ]
+                }]
+            }
+        });
+
+        let prompts = atomize_prompts(&response, "devtools", &AtomizeOptions::default()).unwrap();
+
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].kind, ItemKind::Prompt);
+        assert_eq!(prompts[0].id, "devtools::prompt:code_review");
+        assert_eq!(prompts[0].title.as_deref(), Some("Request Code Review"));
+        assert_eq!(
+            prompts[0].inference_view,
+            "TOOL: code_review | CONTEXT: devtools | FUNC: Asks the LLM to analyze code quality | INPUTS: code*: string (The code to review), language: string"
+        );
+        assert_eq!(prompts[0].raw_definition["arguments"][0]["name"], "code");
+        assert!(prompts[0].is_read_only());
+    }
+
+    #[test]
+    fn test_atomize_resources_and_templates() {
+        let resources = json!({
+            "result": {
+                "resources": [{
+                    "uri": "file:///logs/app.log",
+                    "name": "app.log",
+                    "description": "Application log",
+                    "mimeType": "text/plain"
+                }]
+            }
+        });
+        let templates = json!({
+            "result": {
+                "resourceTemplates": [{
+                    "uriTemplate": "file:///logs/{date}.log",
+                    "name": "daily_log",
+                    "description": "Log for a given day"
+                }]
+            }
+        });
+
+        let items = atomize_resources(&resources, "fs", &AtomizeOptions::default()).unwrap();
+        assert_eq!(items[0].kind, ItemKind::Resource);
+        assert_eq!(items[0].id, "fs::resource:file:///logs/app.log");
+        assert_eq!(items[0].name, "app.log");
+        assert!(items[0]
+            .inference_view
+            .ends_with("INPUTS: uri: file:///logs/app.log, mimeType: text/plain"));
+
+        let items = atomize_resources(&templates, "fs", &AtomizeOptions::default()).unwrap();
+        assert_eq!(items[0].kind, ItemKind::ResourceTemplate);
+        assert_eq!(items[0].id, "fs::resource_template:file:///logs/{date}.log");
+    }
+
+    #[test]
+    fn test_resource_without_uri_is_skipped() {
+        let response = json!({ "resources": [{ "name": "nowhere" }, { "uri": "mem://a" }] });
+
+        let items = atomize_resources(&response, "s", &AtomizeOptions::default()).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "mem://a");
+    }
+
+    #[test]
+    fn test_mixed_document_in_catalog() {
+        let document = json!({
+            "tools": [{ "name": "review", "description": "Run a review tool" }],
+            "prompts": [{ "name": "review", "description": "Review prompt" }],
+            "resources": [{ "uri": "docs://style-guide", "name": "style_guide" }]
+        });
+
+        let items = atomize_tools(&document, "devtools").unwrap();
+        let ids: Vec<&str> = items.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "devtools::review",
+                "devtools::prompt:review",
+                "devtools::resource:docs://style-guide"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prompts_only_document_is_auto_detected() {
+        let document = json!({ "result": { "prompts": [{ "name": "summarize" }] } });
+
+        let items = atomize_tools(&document, "s").unwrap();
+        assert_eq!(items[0].kind, ItemKind::Prompt);
+    }
+}
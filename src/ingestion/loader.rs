@@ -0,0 +1,364 @@
+//! Catalog loading from one or more tool definition files.
+//!
+//! A catalog entry can be a single file, a directory (every `*.json`, `*.yaml`
+//! and `*.yml` file inside it), or a glob pattern. Each file is atomized under
+//! its own server name (the file stem) and the results are merged into one
+//! catalog. YAML files are expected to hold OpenAPI specifications. Tools that
+//! end up with the same qualified id are resolved by the configured
+//! [`DuplicatePolicy`](crate::ingestion::identity::DuplicatePolicy).
+
+use crate::error::AppError;
+use crate::ingestion::atomizer::{atomize_tools_with_options, AtomizeOptions, AtomizerResult};
+use crate::ingestion::identity::resolve_duplicates;
+use crate::ingestion::openapi::parse_spec;
+use crate::ingestion::types::EncapureTool;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Per-server summary produced while loading a catalog.
+#[derive(Debug, Clone)]
+pub struct ServerSummary {
+    /// Server name assigned to every tool from this source
+    pub server: String,
+    /// File or MCP server command the tools were loaded from
+    pub source: String,
+    /// Number of tools successfully atomized
+    pub tool_count: usize,
+}
+
+/// A server whose tools could not be loaded.
+#[derive(Debug, Clone)]
+pub struct ServerFailure {
+    pub server: String,
+    /// MCP server command or URL
+    pub source: String,
+    pub error: String,
+}
+
+/// A merged tool catalog built from several MCP servers.
+#[derive(Debug, Default)]
+pub struct LoadedCatalog {
+    /// All tools, in file order then tool order
+    pub tools: Vec<EncapureTool>,
+    /// One entry per loaded file or MCP server
+    pub servers: Vec<ServerSummary>,
+    /// Live MCP servers that were skipped (file errors abort loading instead)
+    pub failures: Vec<ServerFailure>,
+}
+
+/// Load and merge tools from every file matched by `entries`.
+///
+/// # Arguments
+/// * `entries` - Files, directories or glob patterns (e.g. `servers/*.json`)
+/// * `options` - Atomizer options applied to every file
+///
+/// # Errors
+/// Returns an error if an entry matches no files, if any matched file
+/// cannot be read or atomized, or on a duplicate id under the `error` policy.
+pub fn load_catalog(
+    entries: &[PathBuf],
+    options: &AtomizeOptions,
+) -> AtomizerResult<LoadedCatalog> {
+    let files = resolve_catalog_files(entries)?;
+    let mut catalog = LoadedCatalog::default();
+
+    for path in files {
+        let server = server_name_for(&path);
+        let tools = load_tools_file(&path, &server, options)?;
+
+        tracing::info!(
+            server = %server,
+            path = %path.display(),
+            count = tools.len(),
+            "Loaded tools from server"
+        );
+
+        catalog.servers.push(ServerSummary {
+            server,
+            source: path.display().to_string(),
+            tool_count: tools.len(),
+        });
+        catalog.tools.extend(tools);
+    }
+
+    catalog.tools = resolve_duplicates(catalog.tools, options.duplicate_policy)?;
+
+    Ok(catalog)
+}
+
+/// Expand catalog entries into a sorted, de-duplicated list of files.
+///
+/// Directories contribute their `*.json`, `*.yaml` and `*.yml` files (non-recursive). Entries that
+/// contain glob metacharacters are expanded with the `glob` crate.
+pub fn resolve_catalog_files(entries: &[PathBuf]) -> AtomizerResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in entries {
+        let matched = if is_glob_pattern(entry) {
+            expand_glob(entry)?
+        } else if entry.is_dir() {
+            list_catalog_files(entry)?
+        } else {
+            vec![entry.clone()]
+        };
+
+        if matched.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "Tools path '{}' did not match any files",
+                entry.display()
+            )));
+        }
+
+        // Sort within each entry so load order is deterministic across platforms
+        let mut matched = matched;
+        matched.sort();
+        for path in matched {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Path, modification time and size of every file matched by `entries`.
+///
+/// Cheap enough to poll: a different fingerprint means a file was added,
+/// removed or rewritten. Entries that match nothing yield an empty list.
+pub fn catalog_fingerprint(entries: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>, u64)> {
+    resolve_catalog_files(entries)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(&path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map(|m| m.len()).unwrap_or(0);
+            (path, modified, len)
+        })
+        .collect()
+}
+
+/// Load and atomize tools from a single JSON or YAML file.
+///
+/// # Arguments
+/// * `path` - Path to the file containing a tool document (see `ToolFormat`)
+/// * `server_name` - Server name recorded as `server_origin` on every tool
+/// * `options` - Atomizer options (format override, etc.)
+pub fn load_tools_file(
+    path: &Path,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::ValidationError(format!(
+            "Failed to read tools file '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let json: serde_json::Value = if is_yaml_file(path) {
+        parse_spec(&content).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Invalid YAML in tools file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?
+    } else {
+        serde_json::from_str(&content).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Invalid JSON in tools file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?
+    };
+
+    atomize_tools_with_options(&json, server_name, options)
+}
+
+/// Derive a server name from a file path.
+///
+/// Example: "servers/github.json" -> "github"
+pub fn server_name_for(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy()
+        .chars()
+        .any(|c| matches!(c, '*' | '?' | '['))
+}
+
+fn expand_glob(pattern: &Path) -> AtomizerResult<Vec<PathBuf>> {
+    let pattern_str = pattern.to_string_lossy();
+    let paths = glob::glob(&pattern_str).map_err(|e| {
+        AppError::ValidationError(format!("Invalid tools glob '{}': {}", pattern_str, e))
+    })?;
+
+    Ok(paths
+        .filter_map(|p| p.ok())
+        .filter(|p| p.is_file())
+        .collect())
+}
+
+fn is_yaml_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml")
+}
+
+fn list_catalog_files(dir: &Path) -> AtomizerResult<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        AppError::ValidationError(format!(
+            "Failed to read tools directory '{}': {}",
+            dir.display(),
+            e
+        ))
+    })?;
+
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file() && (p.extension().is_some_and(|ext| ext == "json") || is_yaml_file(p))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn write_server(dir: &Path, file: &str, tool_names: &[&str]) -> PathBuf {
+        let tools: Vec<_> = tool_names
+            .iter()
+            .map(|n| json!({ "name": n, "description": format!("{} tool", n) }))
+            .collect();
+        let path = dir.join(file);
+        std::fs::write(&path, json!({ "result": { "tools": tools } }).to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_directory_uses_file_stem_per_server() {
+        let dir = tempdir().unwrap();
+        write_server(dir.path(), "github.json", &["create_issue", "list_repos"]);
+        write_server(dir.path(), "jira.json", &["create_issue"]);
+        std::fs::write(dir.path().join("notes.txt"), "not a catalog").unwrap();
+
+        let catalog =
+            load_catalog(&[dir.path().to_path_buf()], &AtomizeOptions::default()).unwrap();
+
+        assert_eq!(catalog.tools.len(), 3);
+        assert_eq!(catalog.servers.len(), 2);
+        assert_eq!(catalog.servers[0].server, "github");
+        assert_eq!(catalog.servers[0].tool_count, 2);
+        assert_eq!(catalog.servers[1].server, "jira");
+        assert_eq!(catalog.tools[2].server_origin, "jira");
+        assert!(catalog.tools[2].inference_view.contains("CONTEXT: jira"));
+    }
+
+    #[test]
+    fn test_load_glob_and_explicit_files_deduplicates() {
+        let dir = tempdir().unwrap();
+        let slack = write_server(dir.path(), "slack.json", &["send_message"]);
+        write_server(dir.path(), "email.json", &["send_email"]);
+
+        let pattern = dir.path().join("*.json");
+        let catalog = load_catalog(&[pattern, slack], &AtomizeOptions::default()).unwrap();
+
+        let servers: Vec<&str> = catalog.servers.iter().map(|s| s.server.as_str()).collect();
+        assert_eq!(servers, vec!["email", "slack"]);
+        assert_eq!(catalog.tools.len(), 2);
+    }
+
+    #[test]
+    fn test_load_directory_with_openapi_yaml() {
+        let dir = tempdir().unwrap();
+        write_server(dir.path(), "slack.json", &["send_message"]);
+        std::fs::write(
+            dir.path().join("billing.yaml"),
+            "openapi: 3.0.0\npaths:\n  /invoices:\n    get:\n      operationId: listInvoices\n",
+        )
+        .unwrap();
+
+        let catalog =
+            load_catalog(&[dir.path().to_path_buf()], &AtomizeOptions::default()).unwrap();
+
+        assert_eq!(catalog.servers[0].server, "billing");
+        assert_eq!(catalog.tools[0].name, "listInvoices");
+        assert_eq!(catalog.tools[1].server_origin, "slack");
+    }
+
+    #[test]
+    fn test_same_server_in_two_directories_is_deduplicated() {
+        use crate::ingestion::identity::DuplicatePolicy;
+
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a")).unwrap();
+        std::fs::create_dir_all(dir.path().join("b")).unwrap();
+        write_server(&dir.path().join("a"), "github.json", &["create_issue"]);
+        write_server(
+            &dir.path().join("b"),
+            "github.json",
+            &["create_issue", "close_issue"],
+        );
+        let entries = [dir.path().join("a"), dir.path().join("b")];
+
+        let options = AtomizeOptions {
+            duplicate_policy: DuplicatePolicy::FirstWins,
+            id_separator: "/".to_string(),
+            ..Default::default()
+        };
+        let catalog = load_catalog(&entries, &options).unwrap();
+        let ids: Vec<&str> = catalog.tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["github/create_issue", "github/close_issue"]);
+
+        let options = AtomizeOptions {
+            duplicate_policy: DuplicatePolicy::Error,
+            ..Default::default()
+        };
+        assert!(load_catalog(&entries, &options).is_err());
+    }
+
+    #[test]
+    fn test_entry_matching_nothing_is_an_error() {
+        let dir = tempdir().unwrap();
+        let result = load_catalog(&[dir.path().join("*.json")], &AtomizeOptions::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_file_is_an_error() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("broken.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        assert!(load_catalog(&[path], &AtomizeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_added_and_rewritten_files() {
+        let dir = tempdir().unwrap();
+        let entries = [dir.path().join("*.json")];
+        assert!(catalog_fingerprint(&entries).is_empty());
+
+        let github = write_server(dir.path(), "github.json", &["create_issue"]);
+        let first = catalog_fingerprint(&entries);
+        assert_eq!(first.len(), 1);
+        assert_eq!(catalog_fingerprint(&entries), first);
+
+        write_server(dir.path(), "github.json", &["create_issue", "close_issue"]);
+        assert_ne!(catalog_fingerprint(&entries), first);
+
+        std::fs::remove_file(github).unwrap();
+        assert!(catalog_fingerprint(&entries).is_empty());
+    }
+}
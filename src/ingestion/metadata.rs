@@ -0,0 +1,204 @@
+//! Tool tags and metadata.
+//!
+//! Every catalog entry carries free-form `tags` and a `metadata` object
+//! (owner, environment, cost tier, data sensitivity, ...) that search filter
+//! expressions can match on. Both are read from the tool definition itself
+//! (`tags`, `_meta` and `metadata`) and can be extended by a sidecar
+//! [`MetadataManifest`], so catalogs exported from third-party servers can be
+//! labelled without editing them.
+//!
+//! # Manifest format
+//! ```yaml
+//! servers:
+//!   github: { environment: prod, tags: [vcs] }
+//! tools:
+//!   read_file: { owner: platform }                      # bare name, any server
+//!   github::delete_repo: { sensitivity: high, tags: [admin] }
+//! ```
+//! Entries are applied in order of specificity: server, bare name, then
+//! qualified id. `tags` are added to the tool's tags; every other key
+//! overrides the tool's metadata.
+
+use crate::error::AppError;
+use crate::ingestion::atomizer::AtomizerResult;
+use crate::ingestion::openapi::parse_spec;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tags and metadata added to tools by a sidecar file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataManifest {
+    /// Entries applied to every tool of a server
+    #[serde(default)]
+    pub servers: HashMap<String, Map<String, Value>>,
+    /// Entries keyed by qualified id or bare tool name
+    #[serde(default)]
+    pub tools: HashMap<String, Map<String, Value>>,
+}
+
+impl MetadataManifest {
+    /// Read a manifest from a JSON or YAML file.
+    pub fn load(path: &Path) -> AtomizerResult<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Failed to read metadata manifest '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let value = parse_spec(&content).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Invalid metadata manifest '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_value(value).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Invalid metadata manifest '{}': {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Merge the entries matching a tool into its `tags` and `metadata`.
+    pub fn apply(
+        &self,
+        server: &str,
+        name: &str,
+        id: &str,
+        tags: &mut Vec<String>,
+        metadata: &mut Map<String, Value>,
+    ) {
+        let entries = [
+            self.servers.get(server),
+            self.tools.get(name),
+            self.tools.get(id).filter(|_| id != name),
+        ];
+        for entry in entries.into_iter().flatten() {
+            merge_entry(entry, tags, metadata);
+        }
+    }
+}
+
+/// Tags declared by a definition (`tags` array of strings).
+pub(crate) fn definition_tags(definition: &Value) -> Vec<String> {
+    let mut tags = Vec::new();
+    if let Some(values) = definition.get("tags").and_then(|t| t.as_array()) {
+        add_tags(values, &mut tags);
+    }
+    tags
+}
+
+/// Metadata declared by a definition: MCP `_meta`, overridden by `metadata`.
+///
+/// Nested `tags` arrays are moved into `tags` rather than kept as metadata.
+pub(crate) fn definition_metadata(
+    definition: &Value,
+    tags: &mut Vec<String>,
+) -> Map<String, Value> {
+    let mut metadata = Map::new();
+    for key in ["_meta", "metadata"] {
+        if let Some(entry) = definition.get(key).and_then(|m| m.as_object()) {
+            merge_entry(entry, tags, &mut metadata);
+        }
+    }
+    metadata
+}
+
+fn merge_entry(
+    entry: &Map<String, Value>,
+    tags: &mut Vec<String>,
+    metadata: &mut Map<String, Value>,
+) {
+    for (key, value) in entry {
+        match (key.as_str(), value) {
+            ("tags", Value::Array(values)) => add_tags(values, tags),
+            _ => {
+                metadata.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn add_tags(values: &[Value], tags: &mut Vec<String>) {
+    for tag in values.iter().filter_map(|v| v.as_str()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    #[test]
+    fn test_definition_tags_and_metadata() {
+        let definition = json!({
+            "name": "query_db",
+            "tags": ["sql", "read"],
+            "_meta": { "owner": "data", "environment": "staging" },
+            "metadata": { "environment": "prod", "tags": ["read", "billing"] }
+        });
+        let mut tags = definition_tags(&definition);
+        let metadata = definition_metadata(&definition, &mut tags);
+
+        assert_eq!(tags, vec!["sql", "read", "billing"]);
+        assert_eq!(metadata["owner"], "data");
+        assert_eq!(metadata["environment"], "prod");
+        assert!(metadata.get("tags").is_none());
+    }
+
+    #[test]
+    fn test_manifest_applies_most_specific_entry_last() {
+        let mut file = tempfile::NamedTempFile::with_suffix(".yaml").unwrap();
+        write!(
+            file,
+            "servers:\n  github: {{ environment: prod, cost_tier: low, tags: [vcs] }}\n\
+             tools:\n  delete_repo: {{ cost_tier: medium }}\n  \
+             github::delete_repo: {{ cost_tier: high, tags: [admin] }}\n"
+        )
+        .unwrap();
+        let manifest = MetadataManifest::load(file.path()).unwrap();
+
+        let mut tags = vec!["repos".to_string()];
+        let mut metadata = Map::new();
+        manifest.apply(
+            "github",
+            "delete_repo",
+            "github::delete_repo",
+            &mut tags,
+            &mut metadata,
+        );
+        assert_eq!(tags, vec!["repos", "vcs", "admin"]);
+        assert_eq!(metadata["environment"], "prod");
+        assert_eq!(metadata["cost_tier"], "high");
+
+        // Bare names apply on every server
+        let mut tags = Vec::new();
+        let mut metadata = Map::new();
+        manifest.apply(
+            "gitlab",
+            "delete_repo",
+            "gitlab::delete_repo",
+            &mut tags,
+            &mut metadata,
+        );
+        assert!(tags.is_empty());
+        assert_eq!(metadata["cost_tier"], "medium");
+    }
+
+    #[test]
+    fn test_manifest_rejects_unknown_sections() {
+        let mut file = tempfile::NamedTempFile::with_suffix(".json").unwrap();
+        write!(file, r#"{{ "tool": {{}} }}"#).unwrap();
+        assert!(MetadataManifest::load(file.path()).is_err());
+    }
+}
@@ -1,10 +1,31 @@
 //! Ingestion module for processing external tool definitions.
 //!
-//! This module transforms MCP (Model Context Protocol) tool definitions
-//! into structured records suitable for semantic search via the reranker.
+//! This module transforms MCP (Model Context Protocol) tool definitions,
+//! prompts and resources, and OpenAPI operations into structured records
+//! suitable for semantic search via the reranker.
 
 pub mod atomizer;
+pub mod identity;
+pub mod items;
+pub mod loader;
+pub mod metadata;
+pub mod openapi;
+pub mod report;
+pub mod schema;
+pub mod template;
+pub mod truncate;
 pub mod types;
 
-pub use atomizer::atomize_tools;
-pub use types::EncapureTool;
+pub use atomizer::{
+    atomize_document, atomize_tools, atomize_tools_with_options, AtomizeOptions, AtomizedDocument,
+    SkippedTool, ToolFormat,
+};
+pub use identity::{qualified_id, resolve_duplicates, DuplicatePolicy, DEFAULT_ID_SEPARATOR};
+pub use items::{atomize_prompts, atomize_resources};
+pub use loader::{catalog_fingerprint, load_catalog, LoadedCatalog, ServerFailure, ServerSummary};
+pub use metadata::MetadataManifest;
+pub use openapi::atomize_openapi;
+pub use report::{validate_document, ToolReport, ValidationReport};
+pub use template::ViewTemplate;
+pub use truncate::{TokenBudget, TokenCounter};
+pub use types::{EncapureTool, ItemKind, ToolAnnotations};
@@ -0,0 +1,549 @@
+//! OpenAPI 3.x ingestion: turns each HTTP operation into a searchable tool.
+//!
+//! Every operation under `paths` becomes one `EncapureTool`. The operation is
+//! first converted into an MCP-style tool definition (`name`, `description`,
+//! `inputSchema`) so it shares the exact same inference view format as tools
+//! ingested from MCP servers.
+
+use crate::error::AppError;
+use crate::ingestion::atomizer::{atomize_definitions, AtomizeOptions, AtomizerResult, ToolFormat};
+use crate::ingestion::schema::resolve_pointer;
+use crate::ingestion::types::EncapureTool;
+use serde_json::{json, Map, Value};
+
+/// HTTP methods that may appear as operations in an OpenAPI path item.
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Maximum `$ref` nesting depth inlined when building input schemas.
+/// Deeper (or cyclic) references are replaced by an empty schema.
+const MAX_REF_DEPTH: usize = 8;
+
+/// Maximum number of JSON values inlined into one operation's input schema.
+/// References met once it is spent are replaced by an empty schema, so specs
+/// whose schemas reference each other many times over stay small.
+const MAX_INLINED_VALUES: usize = 4096;
+
+/// Check whether a JSON document is an OpenAPI 3.x specification.
+///
+/// The version may be a number too: unquoted YAML like `openapi: 3.0` parses
+/// as a float.
+pub fn is_openapi_document(json: &Value) -> bool {
+    let is_v3 = match json.get("openapi") {
+        Some(Value::String(v)) => v.starts_with('3'),
+        Some(Value::Number(v)) => v.as_f64().is_some_and(|v| (3.0..4.0).contains(&v)),
+        _ => false,
+    };
+    is_v3 && json.get("paths").is_some_and(|p| p.is_object())
+}
+
+/// Parse an OpenAPI specification from JSON or YAML text.
+pub fn parse_spec(content: &str) -> AtomizerResult<Value> {
+    serde_json::from_str(content).or_else(|_| {
+        serde_yaml::from_str(content)
+            .map_err(|e| AppError::AtomizerError(format!("Invalid OpenAPI document: {}", e)))
+    })
+}
+
+/// Transform an OpenAPI 3.x specification into EncapureTool records.
+///
+/// # Arguments
+/// * `spec` - The parsed OpenAPI document
+/// * `server_name` - The API name recorded as `server_origin` (used in inference_view)
+///
+/// # Naming
+/// Tools are named from `operationId`, or from method + path when it is
+/// missing (e.g. `GET /pets/{petId}` -> `get_pets_petId`).
+///
+/// # Errors
+/// Returns `AppError::AtomizerError` if the document is not an OpenAPI 3.x spec.
+/// Individual operations that cannot be converted are logged and skipped.
+pub fn atomize_openapi(spec: &Value, server_name: &str) -> AtomizerResult<Vec<EncapureTool>> {
+    atomize_openapi_with_options(spec, server_name, &AtomizeOptions::default())
+}
+
+/// Transform an OpenAPI 3.x specification using explicit atomizer options
+/// (view templates; the format option is ignored).
+pub fn atomize_openapi_with_options(
+    spec: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<Vec<EncapureTool>> {
+    let operations = openapi_operations(spec)?;
+    atomize_definitions(&operations, server_name, ToolFormat::Mcp, options).into_items(server_name)
+}
+
+/// Convert every operation in an OpenAPI document into an MCP-style tool definition.
+///
+/// The original operation is preserved under `x-openapi.operation`, together
+/// with the HTTP method and path needed to invoke it.
+pub fn openapi_operations(spec: &Value) -> AtomizerResult<Vec<Value>> {
+    if !is_openapi_document(spec) {
+        return Err(AppError::AtomizerError(
+            "Expected an OpenAPI 3.x document with 'openapi' and 'paths'".into(),
+        ));
+    }
+
+    let mut operations = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(|p| p.as_object()) else {
+        return Ok(operations);
+    };
+
+    for (path, path_item) in paths {
+        let path_item = resolve_ref(spec, path_item, 0);
+        let shared_params = path_item
+            .get("parameters")
+            .and_then(|p| p.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        for method in HTTP_METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+            operations.push(operation_to_tool(
+                spec,
+                path,
+                method,
+                operation,
+                &shared_params,
+            ));
+        }
+    }
+
+    Ok(operations)
+}
+
+/// Build an MCP-style tool definition for one operation.
+fn operation_to_tool(
+    spec: &Value,
+    path: &str,
+    method: &str,
+    operation: &Value,
+    shared_params: &[Value],
+) -> Value {
+    let name = operation
+        .get("operationId")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| operation_name(method, path));
+
+    let description = [operation.get("summary"), operation.get("description")]
+        .into_iter()
+        .filter_map(|v| v.and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect::<Vec<_>>()
+        .join(" - ");
+
+    let mut tool = json!({
+        "name": name,
+        "description": description,
+        "inputSchema": build_input_schema(spec, operation, shared_params),
+        "annotations": method_annotations(method),
+        "x-openapi": {
+            "method": method.to_uppercase(),
+            "path": path,
+            "operation": operation,
+        }
+    });
+    // Operation tags group endpoints by resource; keep them as tool tags
+    if let Some(tags) = operation.get("tags").filter(|t| t.is_array()) {
+        tool["tags"] = tags.clone();
+    }
+    tool
+}
+
+/// MCP behavior hints implied by the HTTP method semantics.
+///
+/// Safe methods are read-only; PUT and DELETE are idempotent and destructive.
+/// POST and PATCH declare nothing, so the MCP defaults (destructive) apply.
+fn method_annotations(method: &str) -> Value {
+    match method {
+        "get" | "head" | "options" | "trace" => json!({ "readOnlyHint": true }),
+        "put" | "delete" => json!({
+            "readOnlyHint": false,
+            "destructiveHint": true,
+            "idempotentHint": true,
+        }),
+        _ => json!({ "readOnlyHint": false }),
+    }
+}
+
+/// Derive a tool name from method and path.
+///
+/// Example: ("get", "/pets/{petId}") -> "get_pets_petId"
+fn operation_name(method: &str, path: &str) -> String {
+    let mut name = method.to_string();
+    for segment in path.split('/').filter(|s| !s.is_empty()) {
+        let cleaned: String = segment
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let cleaned = cleaned.trim_matches('_');
+        if !cleaned.is_empty() {
+            name.push('_');
+            name.push_str(cleaned);
+        }
+    }
+    name
+}
+
+/// Merge path, query, header and body parameters into one JSON Schema object.
+///
+/// Operation-level parameters override path-level ones with the same
+/// name and location. Object request bodies contribute their properties
+/// directly; any other body schema becomes a single `body` property.
+fn build_input_schema(spec: &Value, operation: &Value, shared_params: &[Value]) -> Value {
+    let mut inliner = RefInliner::new(spec);
+    let mut properties = Map::new();
+    let mut required: Vec<Value> = Vec::new();
+
+    let op_params = operation
+        .get("parameters")
+        .and_then(|p| p.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut params: Vec<Value> = Vec::new();
+    for param in shared_params.iter().chain(op_params) {
+        let param = resolve_ref(spec, param, 0);
+        let key = (param.get("name").cloned(), param.get("in").cloned());
+        params.retain(|p| (p.get("name").cloned(), p.get("in").cloned()) != key);
+        params.push(param);
+    }
+
+    for param in params {
+        let Some(name) = param.get("name").and_then(|n| n.as_str()) else {
+            continue;
+        };
+        // Cookie parameters are transport details, not useful retrieval signals
+        if param.get("in").and_then(|v| v.as_str()) == Some("cookie") {
+            continue;
+        }
+
+        let mut schema = param
+            .get("schema")
+            .map(|s| inliner.inline(s))
+            .unwrap_or_else(|| json!({}));
+        if let (Some(obj), Some(desc)) = (schema.as_object_mut(), param.get("description")) {
+            obj.entry("description").or_insert_with(|| desc.clone());
+        }
+
+        if param.get("required").and_then(|r| r.as_bool()) == Some(true) {
+            required.push(Value::String(name.to_string()));
+        }
+        properties.insert(name.to_string(), schema);
+    }
+
+    if let Some(body) = operation.get("requestBody") {
+        let body = resolve_ref(spec, body, 0);
+        let body_required = body.get("required").and_then(|r| r.as_bool()) == Some(true);
+
+        if let Some(schema) = request_body_schema(&body) {
+            let schema = inliner.inline(schema);
+            match schema.get("properties").and_then(|p| p.as_object()) {
+                Some(body_props) => {
+                    for (name, prop) in body_props {
+                        properties
+                            .entry(name.clone())
+                            .or_insert_with(|| prop.clone());
+                    }
+                    if body_required {
+                        if let Some(body_req) = schema.get("required").and_then(|r| r.as_array()) {
+                            required.extend(body_req.iter().cloned());
+                        }
+                    }
+                }
+                None => {
+                    properties.insert("body".to_string(), schema);
+                    if body_required {
+                        required.push(Value::String("body".to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Pick the request body schema, preferring JSON content.
+fn request_body_schema(body: &Value) -> Option<&Value> {
+    let content = body.get("content")?.as_object()?;
+    content
+        .get("application/json")
+        .or_else(|| {
+            content
+                .iter()
+                .find(|(media, _)| media.ends_with("+json"))
+                .map(|(_, v)| v)
+        })
+        .or_else(|| content.values().next())
+        .and_then(|media| media.get("schema"))
+}
+
+/// Resolve a single level of `$ref` (following chained references).
+fn resolve_ref(spec: &Value, value: &Value, depth: usize) -> Value {
+    match value.get("$ref").and_then(|r| r.as_str()) {
+        Some(reference) if depth < MAX_REF_DEPTH => match resolve_pointer(spec, reference) {
+            Some(target) => resolve_ref(spec, target, depth + 1),
+            None => json!({}),
+        },
+        Some(_) => json!({}),
+        None => value.clone(),
+    }
+}
+
+/// Inlines the local `$ref`s of an operation's schemas.
+///
+/// A reference is replaced by an empty schema when it is already being
+/// inlined (a cycle), is nested [`MAX_REF_DEPTH`] deep, or is met after
+/// [`MAX_INLINED_VALUES`] values were produced.
+struct RefInliner<'a> {
+    spec: &'a Value,
+    /// References being inlined, outermost first
+    stack: Vec<String>,
+    /// Values that may still be produced
+    budget: usize,
+}
+
+impl<'a> RefInliner<'a> {
+    fn new(spec: &'a Value) -> Self {
+        Self {
+            spec,
+            stack: Vec::new(),
+            budget: MAX_INLINED_VALUES,
+        }
+    }
+
+    /// `value` with every local `$ref` inlined.
+    fn inline(&mut self, value: &Value) -> Value {
+        self.budget = self.budget.saturating_sub(1);
+        match value {
+            Value::Object(obj) => {
+                if let Some(reference) = obj.get("$ref").and_then(|r| r.as_str()) {
+                    let expand = self.budget > 0
+                        && self.stack.len() < MAX_REF_DEPTH
+                        && !self.stack.iter().any(|r| r == reference);
+                    let spec = self.spec;
+                    return match resolve_pointer(spec, reference).filter(|_| expand) {
+                        Some(target) => {
+                            self.stack.push(reference.to_string());
+                            let inlined = self.inline(target);
+                            self.stack.pop();
+                            inlined
+                        }
+                        None => json!({}),
+                    };
+                }
+                Value::Object(
+                    obj.iter()
+                        .map(|(k, v)| (k.clone(), self.inline(v)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.inline(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn petstore() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "info": { "title": "Petstore", "version": "1.0" },
+            "paths": {
+                "/pets": {
+                    "get": {
+                        "operationId": "listPets",
+                        "summary": "List all pets",
+                        "tags": ["pets"],
+                        "parameters": [
+                            { "name": "limit", "in": "query", "schema": { "type": "integer" },
+                              "description": "How many items to return" }
+                        ]
+                    },
+                    "post": {
+                        "summary": "Create a pet",
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/json": {
+                                "schema": { "$ref": "#/components/schemas/NewPet" }
+                            } }
+                        }
+                    }
+                },
+                "/pets/{petId}": {
+                    "parameters": [ { "$ref": "#/components/parameters/PetId" } ],
+                    "get": {
+                        "description": "Info for a specific pet"
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "PetId": { "name": "petId", "in": "path", "required": true,
+                               "schema": { "type": "string" } }
+                },
+                "schemas": {
+                    "NewPet": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "tag": { "type": "string" }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_openapi_operations_become_tools() {
+        let tools = atomize_openapi(&petstore(), "petstore").unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(names, vec!["listPets", "post_pets", "get_pets_petId"]);
+        assert_eq!(
+            tools[0].inference_view,
+            "TOOL: listPets | CONTEXT: petstore | FUNC: List all pets | INPUTS: limit: integer (How many items to return)"
+        );
+        assert_eq!(tools[0].tags, vec!["pets"]);
+        assert!(tools[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_request_body_ref_is_resolved() {
+        let tools = atomize_openapi(&petstore(), "petstore").unwrap();
+        let create = &tools[1];
+
+        assert!(create.inference_view.contains("name*: string"));
+        assert!(create.inference_view.contains("tag: string"));
+        assert_eq!(create.raw_definition["x-openapi"]["method"], "POST");
+        assert_eq!(create.raw_definition["x-openapi"]["path"], "/pets");
+    }
+
+    #[test]
+    fn test_path_level_parameter_ref_is_resolved() {
+        let tools = atomize_openapi(&petstore(), "petstore").unwrap();
+        assert!(tools[2].inference_view.contains("petId*: string"));
+        assert!(tools[2]
+            .inference_view
+            .contains("FUNC: Info for a specific pet"));
+    }
+
+    #[test]
+    fn test_cyclic_refs_are_bounded() {
+        let spec = json!({
+            "openapi": "3.1.0",
+            "paths": { "/nodes": { "post": {
+                "operationId": "createNode",
+                "requestBody": { "content": { "application/json": {
+                    "schema": { "$ref": "#/components/schemas/Node" }
+                } } }
+            } } },
+            "components": { "schemas": { "Node": {
+                "type": "object",
+                "properties": { "child": { "$ref": "#/components/schemas/Node" } }
+            } } }
+        });
+
+        let tools = atomize_openapi(&spec, "graph").unwrap();
+        let input = &tools[0].raw_definition["inputSchema"];
+        assert_eq!(input["properties"]["child"], json!({}));
+        assert!(tools[0].inference_view.contains("child"));
+    }
+
+    #[test]
+    fn test_repeated_refs_stay_within_budget() {
+        // Each level references the next ten times: 10^8 copies if fully inlined
+        let mut schemas = Map::new();
+        for level in 0..MAX_REF_DEPTH {
+            let properties: Map<String, Value> = (0..10)
+                .map(|i| {
+                    let target = format!("#/components/schemas/Level{}", level + 1);
+                    (format!("field{}", i), json!({ "$ref": target }))
+                })
+                .collect();
+            schemas.insert(
+                format!("Level{}", level),
+                json!({ "type": "object", "properties": properties }),
+            );
+        }
+        schemas.insert(
+            format!("Level{}", MAX_REF_DEPTH),
+            json!({ "type": "string" }),
+        );
+        let spec = json!({
+            "openapi": "3.0.3",
+            "paths": { "/tree": { "post": {
+                "operationId": "plantTree",
+                "requestBody": { "content": { "application/json": {
+                    "schema": { "$ref": "#/components/schemas/Level0" }
+                } } }
+            } } },
+            "components": { "schemas": schemas }
+        });
+
+        let tools = atomize_openapi(&spec, "forest").unwrap();
+        let input = &tools[0].raw_definition["inputSchema"];
+        fn count(value: &Value) -> usize {
+            match value {
+                Value::Object(obj) => 1 + obj.values().map(count).sum::<usize>(),
+                Value::Array(items) => 1 + items.iter().map(count).sum::<usize>(),
+                _ => 1,
+            }
+        }
+        assert!(
+            count(input) <= 2 * MAX_INLINED_VALUES,
+            "{} values",
+            count(input)
+        );
+        assert!(input["properties"]["field0"]["properties"]["field0"].is_object());
+    }
+
+    #[test]
+    fn test_parse_yaml_spec() {
+        let yaml = "openapi: 3.0.0\npaths:\n  /health:\n    get:\n      summary: Health check\n";
+        let spec = parse_spec(yaml).unwrap();
+
+        assert!(is_openapi_document(&spec));
+        let tools = atomize_openapi(&spec, "svc").unwrap();
+        assert_eq!(tools[0].name, "get_health");
+
+        // Unquoted `3.1` is a YAML float
+        let numeric = parse_spec("openapi: 3.1\npaths:\n  /health:\n    get: {}\n").unwrap();
+        assert!(numeric["openapi"].is_number());
+        assert!(is_openapi_document(&numeric));
+        assert_eq!(atomize_openapi(&numeric, "svc").unwrap().len(), 1);
+        assert!(!is_openapi_document(
+            &json!({ "openapi": 2.0, "paths": {} })
+        ));
+    }
+
+    #[test]
+    fn test_http_method_annotations() {
+        let tools = atomize_openapi(&petstore(), "petstore").unwrap();
+        let by_name = |n: &str| tools.iter().find(|t| t.name == n).unwrap();
+
+        assert!(by_name("listPets").is_read_only());
+        assert!(!by_name("post_pets").is_read_only());
+        assert!(by_name("post_pets").is_destructive());
+    }
+
+    #[test]
+    fn test_non_openapi_document_is_rejected() {
+        assert!(atomize_openapi(&json!({ "swagger": "2.0", "paths": {} }), "s").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Dry-run validation of tool documents.
+//!
+//! [`validate_document`] runs the full atomization pipeline on a document and
+//! reports what would be ingested, without touching any loaded catalog.
+
+use crate::ingestion::atomizer::{atomize_document, AtomizeOptions, AtomizerResult, SkippedTool};
+use crate::ingestion::identity::{resolve_duplicates, DuplicatePolicy};
+use crate::ingestion::types::ItemKind;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// A tool that would be ingested, with the views each search stage sees.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport {
+    pub id: String,
+    pub kind: ItemKind,
+    pub name: String,
+    pub server: String,
+    /// Tags and metadata after the metadata manifest is applied
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
+    pub inference_view: String,
+    pub embedding_view: String,
+}
+
+/// Result of validating one tool document.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    /// Server name the tools were atomized under
+    pub server: String,
+    /// Number of tool definitions (or OpenAPI operations) found
+    pub total: usize,
+    /// Tools that would be ingested
+    pub tools: Vec<ToolReport>,
+    /// Definitions that would be dropped, with index and reason
+    pub skipped: Vec<SkippedTool>,
+    /// Non-fatal issues (missing descriptions, truncation, duplicates, ...)
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Whether every definition was ingested without warnings.
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.warnings.is_empty()
+    }
+}
+
+/// Atomize `json` as the catalog loader would and describe the outcome.
+///
+/// Duplicate ids are reported as warnings and resolved with the configured
+/// policy; under [`DuplicatePolicy::Error`] all tools are listed so that every
+/// conflict can be seen at once.
+///
+/// # Errors
+/// Returns `AppError::AtomizerError` if the document is not a recognized tool
+/// document at all.
+pub fn validate_document(
+    json: &Value,
+    server_name: &str,
+    options: &AtomizeOptions,
+) -> AtomizerResult<ValidationReport> {
+    let document = atomize_document(json, server_name, options)?;
+    let mut warnings = document.warnings;
+
+    if document.total == 0 {
+        warnings.push("Document contains no tool definitions".to_string());
+    } else if document.tools.is_empty() {
+        warnings.push("All tool definitions failed to parse".to_string());
+    }
+
+    let mut seen = HashSet::new();
+    for tool in &document.tools {
+        if !seen.insert(tool.id.as_str()) {
+            warnings.push(format!(
+                "Duplicate tool id '{}' (duplicate policy: {})",
+                tool.id, options.duplicate_policy
+            ));
+        }
+    }
+
+    let tools = match options.duplicate_policy {
+        DuplicatePolicy::Error => document.tools,
+        policy => resolve_duplicates(document.tools, policy)?,
+    };
+
+    Ok(ValidationReport {
+        server: server_name.to_string(),
+        total: document.total,
+        tools: tools
+            .into_iter()
+            .map(|t| ToolReport {
+                id: t.id,
+                kind: t.kind,
+                name: t.name,
+                server: t.server_origin,
+                tags: t.tags,
+                metadata: t.metadata,
+                inference_view: t.inference_view,
+                embedding_view: t.embedding_view,
+            })
+            .collect(),
+        skipped: document.skipped,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_report_lists_tools_skipped_and_warnings() {
+        let document = json!([
+            { "name": "create_issue", "description": "Create an issue" },
+            { "description": "missing name" },
+            { "name": "create_issue", "description": "Create an issue again" },
+            { "name": "close_issue" }
+        ]);
+
+        let report = validate_document(&document, "github", &AtomizeOptions::default()).unwrap();
+
+        assert_eq!(report.total, 4);
+        let ids: Vec<&str> = report.tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "github::create_issue",
+                "github::create_issue#2",
+                "github::close_issue"
+            ]
+        );
+        assert!(report.tools[0]
+            .inference_view
+            .starts_with("TOOL: create_issue | CONTEXT: github"));
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].index, 1);
+        assert_eq!(
+            report.warnings,
+            vec![
+                "Tool 'close_issue' has no description",
+                "Duplicate tool id 'github::create_issue' (duplicate policy: keep-all)"
+            ]
+        );
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_clean_document() {
+        let document = json!({ "tools": [{ "name": "ping", "description": "Health check" }] });
+        let report = validate_document(&document, "ops", &AtomizeOptions::default()).unwrap();
+
+        assert!(report.is_clean());
+        assert_eq!(
+            report.tools[0].embedding_view,
+            report.tools[0].inference_view
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_document_is_an_error() {
+        let document = json!({ "not": "a tool document" });
+        assert!(validate_document(&document, "x", &AtomizeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_empty_document_warns() {
+        let report = validate_document(&json!([]), "x", &AtomizeOptions::default()).unwrap();
+        assert_eq!(
+            report.warnings,
+            vec!["Document contains no tool definitions"]
+        );
+    }
+}
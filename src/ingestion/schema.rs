@@ -0,0 +1,395 @@
+//! JSON Schema walking for parameter summaries.
+//!
+//! Produces compact, retrieval-friendly labels for tool input schemas:
+//! `$ref`/`$defs` are resolved, `anyOf`/`oneOf` unions and nullable type
+//! arrays are flattened, enum values are listed, and nested object fields
+//! are surfaced. The walk is bounded by depth and by a node budget so that
+//! deeply nested or cyclic schemas cannot blow up the inference view.
+
+use serde_json::Value;
+
+/// Limits applied while walking a schema.
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaLimits {
+    /// Nesting depth up to which object fields and array items are expanded
+    pub max_depth: usize,
+    /// Maximum nested fields listed per object before "+N more"
+    pub max_fields: usize,
+    /// Maximum enum values listed before "+N more"
+    pub max_enum_values: usize,
+    /// Maximum number of schema nodes visited per parameter
+    pub max_nodes: usize,
+    /// Maximum `$ref` hops followed for a single schema
+    pub max_ref_hops: usize,
+}
+
+impl Default for SchemaLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 2,
+            max_fields: 8,
+            max_enum_values: 6,
+            max_nodes: 48,
+            max_ref_hops: 8,
+        }
+    }
+}
+
+/// Summarizes schemas relative to a root document used for `$ref` resolution.
+pub struct SchemaWalker<'a> {
+    root: &'a Value,
+    limits: SchemaLimits,
+}
+
+impl<'a> SchemaWalker<'a> {
+    /// Create a walker whose `$ref`s resolve against `root` (usually the input schema).
+    pub fn new(root: &'a Value, limits: SchemaLimits) -> Self {
+        Self { root, limits }
+    }
+
+    /// Resolve `$ref` chains, returning the referenced schema.
+    ///
+    /// Unresolvable or too-deep references resolve to `None`.
+    pub fn resolve(&self, schema: &'a Value) -> Option<&'a Value> {
+        let mut current = schema;
+        for _ in 0..=self.limits.max_ref_hops {
+            match current.get("$ref").and_then(|r| r.as_str()) {
+                Some(reference) => current = resolve_pointer(self.root, reference)?,
+                None => return Some(current),
+            }
+        }
+        None
+    }
+
+    /// Collect the properties and required set of an object schema,
+    /// merging `allOf` branches.
+    pub fn object_fields(&self, schema: &'a Value) -> (Vec<(&'a str, &'a Value)>, Vec<&'a str>) {
+        let mut fields: Vec<(&str, &Value)> = Vec::new();
+        let mut required: Vec<&str> = Vec::new();
+        self.collect_fields(schema, &mut fields, &mut required, 0);
+        (fields, required)
+    }
+
+    fn collect_fields(
+        &self,
+        schema: &'a Value,
+        fields: &mut Vec<(&'a str, &'a Value)>,
+        required: &mut Vec<&'a str>,
+        depth: usize,
+    ) {
+        let Some(schema) = self.resolve(schema) else {
+            return;
+        };
+        if depth > self.limits.max_depth {
+            return;
+        }
+
+        if let Some(props) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, prop) in props {
+                if !fields.iter().any(|(n, _)| *n == name) {
+                    fields.push((name.as_str(), prop));
+                }
+            }
+        }
+        if let Some(req) = schema.get("required").and_then(|r| r.as_array()) {
+            required.extend(req.iter().filter_map(|v| v.as_str()));
+        }
+        if let Some(branches) = schema.get("allOf").and_then(|a| a.as_array()) {
+            for branch in branches {
+                self.collect_fields(branch, fields, required, depth + 1);
+            }
+        }
+    }
+
+    /// Build a type label such as `string|null`, `array<string>`,
+    /// `string [us-east-1|us-west-2]` or `object {name*: string, size: integer}`.
+    pub fn type_label(&self, schema: &'a Value) -> String {
+        let mut nodes = 0;
+        self.label(schema, 0, &mut nodes)
+    }
+
+    fn label(&self, schema: &'a Value, depth: usize, nodes: &mut usize) -> String {
+        *nodes += 1;
+        let Some(schema) = self.resolve(schema) else {
+            return "any".to_string();
+        };
+        // Out of budget: fall back to the bare declared type
+        if *nodes > self.limits.max_nodes {
+            return declared_types(schema)
+                .first()
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "any".to_string());
+        }
+
+        // Unions: label each variant and join
+        if let Some(variants) = schema
+            .get("anyOf")
+            .or_else(|| schema.get("oneOf"))
+            .and_then(|v| v.as_array())
+        {
+            let mut labels: Vec<String> = Vec::new();
+            for variant in variants {
+                let label = self.label(variant, depth, nodes);
+                if !labels.contains(&label) {
+                    labels.push(label);
+                }
+            }
+            // Keep "null" last so "string|null" reads naturally
+            labels.sort_by_key(|l| l == "null");
+            return if labels.is_empty() {
+                "any".to_string()
+            } else {
+                labels.join("|")
+            };
+        }
+
+        let types = declared_types(schema);
+        let only_null = !types.is_empty() && types.iter().all(|t| *t == "null");
+        let nullable = types.contains(&"null")
+            || schema.get("nullable").and_then(|n| n.as_bool()) == Some(true);
+        let mut concrete: Vec<&str> = types.into_iter().filter(|t| *t != "null").collect();
+
+        if concrete.is_empty() {
+            if only_null {
+                return "null".to_string();
+            }
+            concrete.push(infer_type(schema));
+        }
+
+        let mut label = concrete
+            .iter()
+            .map(|t| match *t {
+                "object" => self.object_label(schema, depth, nodes),
+                "array" => self.array_label(schema, depth, nodes),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("|");
+
+        if let Some(values) = self.enum_values(schema) {
+            label = format!("{} [{}]", label, values);
+        }
+        if nullable {
+            label.push_str("|null");
+        }
+        label
+    }
+
+    fn object_label(&self, schema: &'a Value, depth: usize, nodes: &mut usize) -> String {
+        let (fields, required) = self.object_fields(schema);
+        if fields.is_empty() || depth >= self.limits.max_depth {
+            return "object".to_string();
+        }
+
+        let shown = fields.len().min(self.limits.max_fields);
+        let mut entries: Vec<String> = Vec::with_capacity(shown + 1);
+        for (name, prop) in fields.iter().take(shown) {
+            let marker = if required.contains(name) { "*" } else { "" };
+            // Leaf level: list field names only
+            if depth + 1 >= self.limits.max_depth {
+                entries.push(format!("{}{}", name, marker));
+            } else {
+                entries.push(format!(
+                    "{}{}: {}",
+                    name,
+                    marker,
+                    self.label(prop, depth + 1, nodes)
+                ));
+            }
+        }
+        if fields.len() > shown {
+            entries.push(format!("+{} more", fields.len() - shown));
+        }
+
+        format!("object {{{}}}", entries.join(", "))
+    }
+
+    fn array_label(&self, schema: &'a Value, depth: usize, nodes: &mut usize) -> String {
+        match schema.get("items") {
+            Some(items) if items.is_object() && depth < self.limits.max_depth => {
+                format!("array<{}>", self.label(items, depth + 1, nodes))
+            }
+            _ => "array".to_string(),
+        }
+    }
+
+    /// Render enum (or const) values, e.g. `us-east-1|us-west-2|+3 more`.
+    fn enum_values(&self, schema: &Value) -> Option<String> {
+        let values: Vec<&Value> = match (schema.get("enum"), schema.get("const")) {
+            (Some(Value::Array(values)), _) => values.iter().collect(),
+            (_, Some(value)) => vec![value],
+            _ => return None,
+        };
+        if values.is_empty() {
+            return None;
+        }
+
+        let shown = values.len().min(self.limits.max_enum_values);
+        let mut rendered: Vec<String> = values
+            .iter()
+            .take(shown)
+            .map(|v| match v {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        if values.len() > shown {
+            rendered.push(format!("+{} more", values.len() - shown));
+        }
+        Some(rendered.join("|"))
+    }
+}
+
+/// Declared `type` as a list (handles both `"string"` and `["string", "null"]`).
+fn declared_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Infer a type for schemas without an explicit `type`.
+fn infer_type(schema: &Value) -> &'static str {
+    if schema.get("properties").is_some() || schema.get("allOf").is_some() {
+        return "object";
+    }
+    if schema.get("items").is_some() {
+        return "array";
+    }
+    let sample = schema
+        .get("enum")
+        .and_then(|e| e.as_array())
+        .and_then(|e| e.first())
+        .or_else(|| schema.get("const"));
+    match sample {
+        Some(Value::String(_)) => "string",
+        Some(Value::Number(n)) if n.is_i64() || n.is_u64() => "integer",
+        Some(Value::Number(_)) => "number",
+        Some(Value::Bool(_)) => "boolean",
+        _ => "any",
+    }
+}
+
+/// Resolve a local JSON pointer reference such as `#/$defs/Region` or
+/// `#/components/schemas/Pet`.
+///
+/// External references (other files or URLs) are not supported and resolve to `None`.
+pub(crate) fn resolve_pointer<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(root);
+    }
+    // JSON pointers escape '~' and '/' as '~0' and '~1'; Value::pointer handles both
+    root.pointer(pointer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn label(schema: &Value) -> String {
+        SchemaWalker::new(schema, SchemaLimits::default()).type_label(schema)
+    }
+
+    #[test]
+    fn test_nullable_type_array() {
+        assert_eq!(label(&json!({ "type": ["string", "null"] })), "string|null");
+    }
+
+    #[test]
+    fn test_any_of_union() {
+        let schema =
+            json!({ "anyOf": [{ "type": "null" }, { "type": "integer" }, { "type": "string" }] });
+        assert_eq!(label(&schema), "integer|string|null");
+    }
+
+    #[test]
+    fn test_enum_values_listed_and_capped() {
+        let schema = json!({ "type": "string", "enum": ["a", "b", "c", "d", "e", "f", "g", "h"] });
+        assert_eq!(label(&schema), "string [a|b|c|d|e|f|+2 more]");
+    }
+
+    #[test]
+    fn test_enum_without_type_is_inferred() {
+        assert_eq!(label(&json!({ "enum": [1, 2] })), "integer [1|2]");
+    }
+
+    #[test]
+    fn test_nested_object_fields() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "limits": { "type": "object", "properties": { "cpu": {}, "memory": {} } }
+            }
+        });
+        assert_eq!(
+            label(&schema),
+            "object {limits: object {cpu, memory}, name*: string}"
+        );
+    }
+
+    #[test]
+    fn test_array_items() {
+        let schema = json!({ "type": "array", "items": { "type": "string", "enum": ["x", "y"] } });
+        assert_eq!(label(&schema), "array<string [x|y]>");
+    }
+
+    #[test]
+    fn test_ref_to_defs() {
+        let root = json!({
+            "$defs": { "Region": { "type": "string", "enum": ["us-east-1", "eu-west-1"] } },
+            "properties": { "region": { "$ref": "#/$defs/Region" } }
+        });
+        let walker = SchemaWalker::new(&root, SchemaLimits::default());
+        assert_eq!(
+            walker.type_label(&root["properties"]["region"]),
+            "string [us-east-1|eu-west-1]"
+        );
+    }
+
+    #[test]
+    fn test_cyclic_ref_is_bounded() {
+        let root = json!({
+            "$defs": { "Node": { "$ref": "#/$defs/Node" } },
+            "properties": { "node": { "$ref": "#/$defs/Node" } }
+        });
+        let walker = SchemaWalker::new(&root, SchemaLimits::default());
+        assert_eq!(walker.type_label(&root["properties"]["node"]), "any");
+    }
+
+    #[test]
+    fn test_all_of_fields_are_merged() {
+        let root = json!({
+            "allOf": [
+                { "properties": { "a": { "type": "string" } }, "required": ["a"] },
+                { "properties": { "b": { "type": "integer" } } }
+            ]
+        });
+        let walker = SchemaWalker::new(&root, SchemaLimits::default());
+        let (fields, required) = walker.object_fields(&root);
+
+        let names: Vec<&str> = fields.iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(required, vec!["a"]);
+    }
+
+    #[test]
+    fn test_node_budget_limits_expansion() {
+        let limits = SchemaLimits {
+            max_nodes: 1,
+            ..SchemaLimits::default()
+        };
+        let schema = json!({
+            "type": "object",
+            "properties": { "a": { "type": "string", "enum": ["p", "q"] } }
+        });
+
+        assert_eq!(label(&schema), "object {a: string [p|q]}");
+        let label = SchemaWalker::new(&schema, limits).type_label(&schema);
+        assert_eq!(label, "object {a: string}");
+    }
+}
@@ -0,0 +1,286 @@
+//! Configurable inference view templates.
+//!
+//! A template is plain text with `{placeholder}` fields, e.g. the default
+//! `TOOL: {name} | CONTEXT: {server} | FUNC: {description} | INPUTS: {params}`.
+//! Literal braces are written as `{{` and `}}`.
+//!
+//! # Placeholders
+//! - `{name}`: tool name as published (`create_issue`)
+//! - `{humanized_name}`: name split into words (`create issue`)
+//! - `{server}`: origin server
+//! - `{title}`: human-readable title, if the tool declares one
+//! - `{description}`: (truncated) description
+//! - `{params}`: parameter summary
+//! - `{tags}`: comma-separated tags
+//! - `{kind}`: item kind (`tool`, `prompt`, `resource`, `resource_template`)
+
+use crate::error::AppError;
+use std::fmt;
+use std::str::FromStr;
+
+/// Template used when none is configured; matches the original fixed format.
+pub const DEFAULT_VIEW_TEMPLATE: &str =
+    "TOOL: {name} | CONTEXT: {server} | FUNC: {description} | INPUTS: {params}";
+
+/// A field that can be substituted into a view template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Name,
+    HumanizedName,
+    Server,
+    Title,
+    Description,
+    Params,
+    Tags,
+    Kind,
+}
+
+impl Placeholder {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Self::Name),
+            "humanized_name" => Some(Self::HumanizedName),
+            "server" => Some(Self::Server),
+            "title" => Some(Self::Title),
+            "description" => Some(Self::Description),
+            "params" => Some(Self::Params),
+            "tags" => Some(Self::Tags),
+            "kind" => Some(Self::Kind),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Placeholder),
+}
+
+/// Values available to a template when rendering one tool.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ViewFields<'a> {
+    pub name: &'a str,
+    pub humanized_name: &'a str,
+    pub server: &'a str,
+    pub title: &'a str,
+    pub description: &'a str,
+    pub params: &'a str,
+    pub tags: &'a str,
+    pub kind: &'a str,
+}
+
+/// A parsed inference view template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl ViewTemplate {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces.
+    pub fn parse(source: &str) -> Result<Self, AppError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => name.push(ch),
+                            None => {
+                                return Err(AppError::ValidationError(format!(
+                                    "Unclosed '{{' in view template '{}'",
+                                    source
+                                )))
+                            }
+                        }
+                    }
+                    let field = Placeholder::parse(name.trim()).ok_or_else(|| {
+                        AppError::ValidationError(format!(
+                            "Unknown placeholder '{{{}}}' in view template (expected name, \
+                             humanized_name, server, title, description, params, tags or kind)",
+                            name
+                        ))
+                    })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                }
+                '}' => {
+                    return Err(AppError::ValidationError(format!(
+                        "Unmatched '}}' in view template '{}'",
+                        source
+                    )))
+                }
+                other => literal.push(other),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// The template text as configured.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Render the template for one tool.
+    pub fn render(&self, fields: &ViewFields) -> String {
+        let mut out = String::with_capacity(self.source.len() + fields.description.len() + 64);
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field(field) => out.push_str(match field {
+                    Placeholder::Name => fields.name,
+                    Placeholder::HumanizedName => fields.humanized_name,
+                    Placeholder::Server => fields.server,
+                    Placeholder::Title => fields.title,
+                    Placeholder::Description => fields.description,
+                    Placeholder::Params => fields.params,
+                    Placeholder::Tags => fields.tags,
+                    Placeholder::Kind => fields.kind,
+                }),
+            }
+        }
+        out
+    }
+}
+
+impl Default for ViewTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_VIEW_TEMPLATE).expect("default view template is valid")
+    }
+}
+
+impl FromStr for ViewTemplate {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for ViewTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Split a tool name into lowercase words.
+///
+/// Handles snake_case, kebab-case, dotted and camelCase names:
+/// `createIssue` -> "create issue", `get_pets_petId` -> "get pets pet id",
+/// `HTTPRequest` -> "http request".
+pub fn humanize_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            // Boundary at "aB" and at the last capital of an acronym ("HTTPRequest")
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> ViewFields<'static> {
+        ViewFields {
+            name: "create_issue",
+            humanized_name: "create issue",
+            server: "github",
+            title: "Create Issue",
+            description: "Open a new issue",
+            params: "title*: string",
+            tags: "issues, write",
+            kind: "tool",
+        }
+    }
+
+    #[test]
+    fn test_default_template_matches_legacy_format() {
+        assert_eq!(
+            ViewTemplate::default().render(&fields()),
+            "TOOL: create_issue | CONTEXT: github | FUNC: Open a new issue | INPUTS: title*: string"
+        );
+    }
+
+    #[test]
+    fn test_custom_template_with_all_placeholders() {
+        let template = ViewTemplate::parse(
+            "{title} ({humanized_name}, {kind}) on {server}: {description}. Args: {params}. Tags: {tags}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.render(&fields()),
+            "Create Issue (create issue, tool) on github: Open a new issue. Args: title*: string. Tags: issues, write"
+        );
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        let template = ViewTemplate::parse("{{{name}}}").unwrap();
+        assert_eq!(template.render(&fields()), "{create_issue}");
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected() {
+        assert!(ViewTemplate::parse("{unknown}").is_err());
+        assert!(ViewTemplate::parse("{name").is_err());
+        assert!(ViewTemplate::parse("name}").is_err());
+    }
+
+    #[test]
+    fn test_humanize_name() {
+        assert_eq!(humanize_name("create_issue"), "create issue");
+        assert_eq!(humanize_name("createIssue"), "create issue");
+        assert_eq!(humanize_name("get_pets_petId"), "get pets pet id");
+        assert_eq!(humanize_name("HTTPRequest"), "http request");
+        assert_eq!(humanize_name("s3-list.buckets"), "s3 list buckets");
+    }
+}
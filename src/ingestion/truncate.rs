@@ -0,0 +1,173 @@
+//! Unicode-safe truncation of tool text.
+//!
+//! Lengths are measured in chars and cuts always land on grapheme cluster
+//! boundaries, so multi-byte scripts (Hebrew, Japanese, emoji sequences) are
+//! never split mid-character. An optional [`TokenBudget`] shrinks text further
+//! until the rendered view fits the model's tokenizer limit.
+
+use std::fmt;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Suffix appended to truncated text.
+const ELLIPSIS: &str = "...";
+
+/// Counts the tokens a model will see for a piece of text.
+pub trait TokenCounter: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// Maximum number of tokens a rendered view may use.
+#[derive(Clone)]
+pub struct TokenBudget {
+    counter: Arc<dyn TokenCounter>,
+    max_tokens: usize,
+}
+
+impl TokenBudget {
+    pub fn new(counter: Arc<dyn TokenCounter>, max_tokens: usize) -> Self {
+        Self {
+            counter,
+            max_tokens,
+        }
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    /// Whether `text` fits within the budget.
+    pub fn fits(&self, text: &str) -> bool {
+        self.counter.count_tokens(text) <= self.max_tokens
+    }
+}
+
+impl fmt::Debug for TokenBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenBudget")
+            .field("max_tokens", &self.max_tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Longest prefix of `text` with at most `max_chars` chars, cut on a grapheme boundary.
+pub fn grapheme_prefix(text: &str, max_chars: usize) -> &str {
+    let mut chars = 0;
+    let mut end = 0;
+    for (idx, grapheme) in text.grapheme_indices(true) {
+        chars += grapheme.chars().count();
+        if chars > max_chars {
+            break;
+        }
+        end = idx + grapheme.len();
+    }
+    &text[..end]
+}
+
+/// Truncate `text` to `max_chars` chars, appending an ellipsis when cut.
+///
+/// Prefers to cut at a word boundary when one falls within the last 50 chars.
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let truncated = grapheme_prefix(text, max_chars);
+
+    // Try to truncate at a word boundary
+    match truncated.rfind(' ') {
+        Some(pos) if truncated[..pos].chars().count() > max_chars.saturating_sub(50) => {
+            format!("{}{}", &truncated[..pos], ELLIPSIS)
+        }
+        _ => format!("{}{}", truncated, ELLIPSIS),
+    }
+}
+
+/// Shrink `text` to the longest grapheme prefix for which `fits` holds.
+///
+/// Binary-searches over grapheme counts, so `fits` is called O(log n) times.
+/// Returns an empty string if not even a one-grapheme prefix fits.
+pub fn shrink_to_fit(text: &str, fits: impl Fn(&str) -> bool) -> String {
+    if fits(text) {
+        return text.to_string();
+    }
+
+    let ends: Vec<usize> = text
+        .grapheme_indices(true)
+        .map(|(idx, g)| idx + g.len())
+        .collect();
+    let candidate = |n: usize| format!("{}{}", text[..ends[n - 1]].trim_end(), ELLIPSIS);
+
+    // Largest n in [1, len) whose prefix fits
+    let (mut lo, mut hi) = (0, ends.len().saturating_sub(1));
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if fits(&candidate(mid)) {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    if lo == 0 {
+        String::new()
+    } else {
+        candidate(lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts whitespace-separated words as tokens.
+    struct WordCounter;
+
+    impl TokenCounter for WordCounter {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    #[test]
+    fn test_grapheme_prefix_multibyte() {
+        assert_eq!(grapheme_prefix("שלום עולם", 4), "שלום");
+        assert_eq!(grapheme_prefix("ファイルを読む", 5), "ファイルを");
+        assert_eq!(grapheme_prefix("abc", 10), "abc");
+    }
+
+    #[test]
+    fn test_grapheme_prefix_keeps_clusters_whole() {
+        // Family emoji is one grapheme of 5 chars (3 people joined by ZWJ)
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("a{}b", family);
+        assert_eq!(grapheme_prefix(&text, 3), "a");
+        assert_eq!(grapheme_prefix(&text, 6), format!("a{}", family));
+    }
+
+    #[test]
+    fn test_truncate_chars_does_not_split_characters() {
+        let hebrew = "קובץ ".repeat(200);
+        let truncated = truncate_chars(&hebrew, 500);
+        assert!(truncated.ends_with("..."));
+        assert!(truncated.chars().count() <= 503);
+
+        let japanese = "ファイルを読み込みます".repeat(60);
+        let truncated = truncate_chars(&japanese, 500);
+        assert_eq!(truncated.chars().count(), 503);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_budget() {
+        let budget = TokenBudget::new(Arc::new(WordCounter), 3);
+        let text = "one two three four five";
+
+        assert_eq!(shrink_to_fit(text, |t| budget.fits(t)), "one two three...");
+        assert_eq!(shrink_to_fit("one two", |t| budget.fits(t)), "one two");
+    }
+
+    #[test]
+    fn test_shrink_to_fit_nothing_fits() {
+        assert_eq!(shrink_to_fit("anything", |t| t.is_empty()), "");
+    }
+}
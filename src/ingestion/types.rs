@@ -1,37 +1,72 @@
 //! Type definitions for the ingestion module.
 
+use crate::ingestion::identity::{qualified_id, DEFAULT_ID_SEPARATOR};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 /// A normalized tool record ready for semantic search indexing.
 ///
 /// This struct represents an atomic tool unit parsed from an MCP server's
-/// `list_tools` response. The `inference_view` field is pre-computed for
+/// `list_tools` response. MCP prompts and resources are indexed with the same
+/// record, distinguished by `kind`. The `inference_view` field is pre-computed for
 /// optimal reranker performance, while `raw_definition` preserves the full
 /// schema for agent invocation.
 ///
 /// # Design Rationale
+/// - `id`: Qualified identity (`server::name`), unique across a merged catalog
+/// - `kind`: Tool, prompt, resource or resource template
 /// - `inference_view`: Pre-computed text representation for the reranker model
+/// - `embedding_view`: Pre-computed text representation for the bi-encoder
 /// - `raw_definition`: Preserved for agent runtime (schema validation, invocation)
 /// - `server_origin`: Enables filtering by source MCP server
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// - `tags` / `metadata`: Labels matched by search filter expressions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EncapureTool {
-    /// Unique tool identifier (from MCP tool.name)
+    /// Qualified identifier: server origin, separator, tool name (e.g. "github::create_issue").
+    /// Non-tool items are prefixed with their kind ("github::prompt:triage").
+    pub id: String,
+
+    /// What kind of MCP item this record describes
+    pub kind: ItemKind,
+
+    /// Tool name as published by its server (from MCP tool.name); unique per server only
     pub name: String,
 
     /// The MCP server that provided this tool (e.g., "filesystem", "github")
     pub server_origin: String,
 
-    /// Pre-formatted text for reranker embedding.
-    /// Format: "TOOL: <name> | CONTEXT: <server_name> | FUNC: <description> | INPUTS: <param_summary>"
+    /// Human-readable display name (MCP `title`, or `annotations.title`)
+    pub title: Option<String>,
+
+    /// Behavior hints declared by the server (MCP `annotations`)
+    pub annotations: ToolAnnotations,
+
+    /// Free-form labels from the definition and the metadata manifest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Arbitrary metadata (owner, environment, cost tier, ...) from the
+    /// definition's `_meta` / `metadata` objects and the metadata manifest
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub metadata: Map<String, Value>,
+
+    /// Pre-formatted text for reranker (stage 2) scoring, rendered from the rerank template.
+    /// Default format: "TOOL: <name> | CONTEXT: <server_name> | FUNC: <description> | INPUTS: <param_summary>"
     pub inference_view: String,
 
-    /// Full JSON schema preserved for agent invocation
+    /// Pre-formatted text for bi-encoder (stage 1) embedding.
+    /// Identical to `inference_view` unless a separate embedding template is configured.
+    pub embedding_view: String,
+
+    /// Original definition (tool schema, prompt or resource) preserved for agent invocation
     pub raw_definition: Value,
 }
 
 impl EncapureTool {
     /// Creates a new EncapureTool with the given parameters.
+    ///
+    /// The id is qualified with the default separator and the embedding view
+    /// defaults to the inference view.
     pub fn new(
         name: String,
         server_origin: String,
@@ -39,10 +74,101 @@ impl EncapureTool {
         raw_definition: Value,
     ) -> Self {
         Self {
+            id: qualified_id(&server_origin, &name, DEFAULT_ID_SEPARATOR),
+            kind: ItemKind::Tool,
             name,
             server_origin,
+            title: None,
+            annotations: ToolAnnotations::default(),
+            tags: Vec::new(),
+            metadata: Map::new(),
+            embedding_view: inference_view.clone(),
             inference_view,
             raw_definition,
         }
     }
+
+    /// Whether the tool declares that it does not modify its environment.
+    ///
+    /// Prompts and resources are only ever read, so they are always read-only.
+    pub fn is_read_only(&self) -> bool {
+        self.kind != ItemKind::Tool || self.annotations.read_only_hint.unwrap_or(false)
+    }
+
+    /// Whether the tool may perform destructive updates.
+    ///
+    /// Follows the MCP defaults: a tool that is not read-only is assumed
+    /// destructive unless it explicitly sets `destructiveHint: false`.
+    pub fn is_destructive(&self) -> bool {
+        !self.is_read_only() && self.annotations.destructive_hint.unwrap_or(true)
+    }
+}
+
+/// Kind of MCP item indexed for search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    /// A callable tool (`tools/list`)
+    #[default]
+    Tool,
+    /// A prompt template (`prompts/list`)
+    Prompt,
+    /// A concrete resource (`resources/list`)
+    Resource,
+    /// A parameterized resource (`resources/templates/list`)
+    ResourceTemplate,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tool => "tool",
+            Self::Prompt => "prompt",
+            Self::Resource => "resource",
+            Self::ResourceTemplate => "resource_template",
+        }
+    }
+
+    /// Capitalized name used in messages ("Tool", "Prompt", ...).
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Tool => "Tool",
+            Self::Prompt => "Prompt",
+            Self::Resource => "Resource",
+            Self::ResourceTemplate => "Resource template",
+        }
+    }
+
+    /// Key qualified into the item id: tool names are used as-is, other kinds
+    /// are prefixed so that a prompt and a tool with the same name never collide.
+    pub fn id_key(&self, key: &str) -> String {
+        match self {
+            Self::Tool => key.to_string(),
+            other => format!("{}:{}", other.as_str(), key),
+        }
+    }
+}
+
+/// MCP tool annotations: hints about a tool's behavior.
+///
+/// All hints are optional; `None` means the server did not declare it and the
+/// MCP defaults apply (not read-only, destructive, not idempotent, open world).
+/// Hints are untrusted metadata supplied by the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The tool does not modify its environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    /// The tool may perform destructive updates (meaningful only when not read-only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    /// Repeated calls with the same arguments have no additional effect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    /// The tool interacts with external entities
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
 }
@@ -3,11 +3,17 @@
 //! This library exposes the core components for the reranking service,
 //! enabling integration tests and potential embedding in other applications.
 
+pub mod cache;
+pub mod catalog;
+pub mod collections;
 pub mod config;
 pub mod error;
+pub mod filter;
 pub mod handlers;
+pub mod index;
 pub mod inference;
 pub mod ingestion;
+pub mod mcp;
 pub mod persistence;
 pub mod state;
 
@@ -1,18 +1,15 @@
-mod config;
-mod error;
-mod handlers;
-mod inference;
-mod ingestion;
-mod persistence;
-mod state;
-
-use crate::config::{Config, OperatingMode};
-use crate::handlers::{health_handler, ready_handler, rerank_handler, search_handler};
-use crate::state::AppState;
+use encapure::config::{Config, OperatingMode};
+use encapure::handlers::{
+    collection_search_handler, create_collection_handler, create_tool_handler,
+    delete_collection_handler, delete_tool_handler, get_tool_handler, health_handler,
+    list_collections_handler, ready_handler, reload_handler, rerank_handler, search_handler,
+    update_tool_handler, validate_handler,
+};
+use encapure::state::AppState;
 
 use axum::{
     extract::DefaultBodyLimit,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 use metrics_exporter_prometheus::PrometheusBuilder;
@@ -72,6 +69,14 @@ async fn main() -> anyhow::Result<()> {
         "State initialized",
     );
 
+    // Keep tools from HTTP MCP servers current (no-op unless any are configured)
+    state.spawn_catalog_refresh();
+
+    // Hot reload: SIGHUP and (if TOOLS_WATCH_INTERVAL is set) changed files under TOOLS_PATH
+    #[cfg(unix)]
+    spawn_sighup_reload(state.clone());
+    state.spawn_tools_watcher();
+
     // Build router
     let app = Router::new()
         // Core endpoints - rerank needs larger body limit for batch requests
@@ -81,6 +86,30 @@ async fn main() -> anyhow::Result<()> {
         )
         // Semantic search endpoint
         .route("/search", post(search_handler))
+        // Named collections (separate catalogs, shared model pools)
+        .route(
+            "/collections",
+            get(list_collections_handler)
+                .post(create_collection_handler)
+                .layer(DefaultBodyLimit::max(10 * 1024 * 1024)),
+        )
+        .route("/collections/:name", delete(delete_collection_handler))
+        .route("/collections/:name/search", post(collection_search_handler))
+        // Ingestion dry-run (never modifies the loaded catalog)
+        .route(
+            "/ingest/validate",
+            post(validate_handler).layer(DefaultBodyLimit::max(10 * 1024 * 1024)),
+        )
+        // Catalog hot reload
+        .route("/admin/reload", post(reload_handler))
+        // Single-tool management (wildcard: ids may contain the '/' separator)
+        .route("/tools", post(create_tool_handler))
+        .route(
+            "/tools/*id",
+            get(get_tool_handler)
+                .put(update_tool_handler)
+                .delete(delete_tool_handler),
+        )
         // Health endpoints
         .route("/health", get(health_handler))
         .route("/ready", get(ready_handler))
@@ -95,7 +124,7 @@ async fn main() -> anyhow::Result<()> {
         // Middleware
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
         // State
-        .with_state(state);
+        .with_state(state.clone());
 
     // Create TCP listener
     let listener = TcpListener::bind(addr).await?;
@@ -106,10 +135,27 @@ async fn main() -> anyhow::Result<()> {
         .with_graceful_shutdown(shutdown_signal(shutdown_timeout))
         .await?;
 
+    // Keep the warm query cache and the latest catalog for the next start
+    state.save_query_cache();
+    state.flush_catalog();
+
     tracing::info!("Server shutdown complete");
     Ok(())
 }
 
+/// Reload the tool catalog every time the process receives SIGHUP.
+#[cfg(unix)]
+fn spawn_sighup_reload(state: Arc<AppState>) {
+    let mut hangup = signal::unix::signal(signal::unix::SignalKind::hangup())
+        .expect("Failed to install SIGHUP handler");
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            // Failures are logged by reload; the current catalog stays live
+            let _ = state.reload("SIGHUP").await;
+        }
+    });
+}
+
 /// Wait for shutdown signal (Ctrl+C or SIGTERM).
 /// After signal, allows `timeout_secs` for in-flight requests to complete.
 async fn shutdown_signal(timeout_secs: u64) {
@@ -0,0 +1,211 @@
+//! Transport-independent MCP client.
+//!
+//! [`McpClient`] implements the protocol steps Encapure needs (the
+//! `initialize` handshake and paginated list methods) on top of whichever
+//! transport the server is configured with.
+
+use crate::error::{AppError, Result};
+use crate::mcp::config::{McpServerConfig, McpTransport};
+use crate::mcp::http::HttpTransport;
+use crate::mcp::stdio::StdioTransport;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Protocol revision sent in the `initialize` request.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Upper bound on `nextCursor` pages, guarding against servers that never stop paging.
+const MAX_PAGES: usize = 1000;
+
+/// A connection to one MCP server.
+pub enum McpClient {
+    Stdio(Box<StdioTransport>),
+    Http(HttpTransport),
+}
+
+impl McpClient {
+    /// Launch or connect to the server described by `config`.
+    ///
+    /// `timeout` bounds every request made on this client.
+    pub fn connect(config: &McpServerConfig, timeout: Duration) -> Result<Self> {
+        match &config.transport {
+            McpTransport::Stdio { command, args, env } => Ok(Self::Stdio(Box::new(
+                StdioTransport::spawn(&config.name, command, args, env, timeout)?,
+            ))),
+            McpTransport::Http { url, headers } => Ok(Self::Http(HttpTransport::new(
+                &config.name,
+                url,
+                headers,
+                timeout,
+            )?)),
+        }
+    }
+
+    /// Perform the `initialize` handshake and send `notifications/initialized`.
+    ///
+    /// Returns the server's `InitializeResult` (protocol version, capabilities,
+    /// server info).
+    pub async fn initialize(&mut self) -> Result<Value> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION")
+                    }
+                }),
+            )
+            .await?;
+        self.notify("notifications/initialized", json!({})).await?;
+
+        tracing::debug!(
+            server = %self.name(),
+            protocol = ?result.get("protocolVersion"),
+            "MCP server initialized"
+        );
+        Ok(result)
+    }
+
+    /// Fetch every tool definition, following `nextCursor` across pages.
+    pub async fn list_tools(&mut self) -> Result<Vec<Value>> {
+        self.list_all("tools/list", "tools").await
+    }
+
+    /// Call a paginated list method and concatenate the `key` array of every page.
+    pub async fn list_all(&mut self, method: &str, key: &str) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let params = match &cursor {
+                Some(c) => json!({ "cursor": c }),
+                None => json!({}),
+            };
+            let mut result = self.request(method, params).await?;
+
+            match result.get_mut(key).map(Value::take) {
+                Some(Value::Array(page)) => items.extend(page),
+                _ => {
+                    return Err(AppError::McpError(format!(
+                        "MCP server '{}' returned no '{}' array from {}",
+                        self.name(),
+                        key,
+                        method
+                    )))
+                }
+            }
+
+            let next = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if next.is_none() || next == cursor {
+                return Ok(items);
+            }
+            cursor = next;
+        }
+
+        Err(AppError::McpError(format!(
+            "MCP server '{}' returned more than {} pages from {}",
+            self.name(),
+            MAX_PAGES,
+            method
+        )))
+    }
+
+    /// Send a request and wait for its result.
+    ///
+    /// # Errors
+    /// Returns `AppError::McpError` on timeout, on a JSON-RPC error response, or
+    /// if the server goes away before answering.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let result = match self {
+            Self::Stdio(t) => t.request(method, params).await,
+            Self::Http(t) => t.request(method, params).await,
+        };
+        result.map_err(|e| match e {
+            AppError::McpError(msg) => AppError::McpError(format!("{} ({})", msg, method)),
+            other => other,
+        })
+    }
+
+    /// Send a notification (no response expected).
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        match self {
+            Self::Stdio(t) => t.notify(method, params).await,
+            Self::Http(t) => t.notify(method, params).await,
+        }
+    }
+
+    /// Close the connection, stopping the server process if there is one.
+    pub async fn shutdown(self) {
+        match self {
+            Self::Stdio(t) => t.shutdown().await,
+            Self::Http(t) => t.shutdown().await,
+        }
+    }
+
+    /// Forward the server's `*/list_changed` notifications to `changed`.
+    ///
+    /// Only HTTP sessions stay open long enough to receive them; returns `None`
+    /// when the transport or server offers no notification stream.
+    pub async fn listen_for_changes(
+        &self,
+        changed: mpsc::Sender<()>,
+    ) -> Result<Option<JoinHandle<()>>> {
+        match self {
+            Self::Stdio(_) => Ok(None),
+            Self::Http(t) => t.listen_for_changes(changed).await,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Stdio(t) => t.name(),
+            Self::Http(t) => t.name(),
+        }
+    }
+}
+
+/// Build a JSON-RPC request message.
+pub(crate) fn request_message(id: u64, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+}
+
+/// Build a JSON-RPC notification message.
+pub(crate) fn notification_message(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// Extract the `result` of a JSON-RPC response, turning `error` into `AppError::McpError`.
+pub(crate) fn response_result(server: &str, message: &Value) -> Result<Value> {
+    if let Some(error) = message.get("error") {
+        return Err(AppError::McpError(format!(
+            "MCP server '{}' returned error {}: {}",
+            server,
+            error.get("code").unwrap_or(&Value::Null),
+            error.get("message").and_then(|m| m.as_str()).unwrap_or("")
+        )));
+    }
+    Ok(message.get("result").cloned().unwrap_or(Value::Null))
+}
+
+/// Reply to a server-to-client request so the server is never left waiting:
+/// `ping` succeeds, anything else is rejected.
+pub(crate) fn reply_to_server_request(request: &Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    if request.get("method").and_then(|m| m.as_str()) == Some("ping") {
+        json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": "Method not found" }
+        })
+    }
+}
@@ -0,0 +1,243 @@
+//! MCP server launch configuration.
+//!
+//! Servers come from the `MCP_SERVERS` JSON array or from an `mcpServers`
+//! client configuration file, the format desktop agent clients use:
+//!
+//! ```json
+//! {
+//!   "mcpServers": {
+//!     "github": { "command": "npx", "args": ["-y", "@modelcontextprotocol/server-github"] },
+//!     "linear": { "url": "https://mcp.linear.app/mcp", "headers": { "Authorization": "Bearer ..." } }
+//!   }
+//! }
+//! ```
+//!
+//! Entries with a `command` are launched over stdio; entries with a `url` are
+//! queried over HTTP. In a config file, each entry's key is the server name.
+
+use crate::error::{AppError, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How to reach one MCP server.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct McpServerConfig {
+    /// Server name, used as `server_origin` and in qualified tool ids
+    #[serde(default)]
+    pub name: String,
+    #[serde(flatten)]
+    pub transport: McpTransport,
+}
+
+/// Transport used to talk to a server.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum McpTransport {
+    /// Launch `command` and speak JSON-RPC over its stdin/stdout
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// Extra environment variables for the process
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
+    /// POST JSON-RPC messages to `url`
+    Http {
+        url: String,
+        /// Extra request headers (e.g. `Authorization`)
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+impl McpServerConfig {
+    /// Human-readable description of where tools came from.
+    pub fn source(&self) -> String {
+        match &self.transport {
+            McpTransport::Stdio { command, args, .. } => std::iter::once(command.as_str())
+                .chain(args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+            McpTransport::Http { url, .. } => url.clone(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let target = match &self.transport {
+            McpTransport::Stdio { command, .. } => command,
+            McpTransport::Http { url, .. } => url,
+        };
+        if target.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "MCP server '{}' needs a non-empty 'command' or 'url'",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Parse the `MCP_SERVERS` JSON array.
+pub fn parse_server_list(json: &str) -> Result<Vec<McpServerConfig>> {
+    let servers: Vec<McpServerConfig> = serde_json::from_str(json)
+        .map_err(|e| AppError::ValidationError(format!("Invalid MCP server list: {}", e)))?;
+
+    for (idx, server) in servers.iter().enumerate() {
+        if server.name.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "MCP server #{} needs a non-empty 'name'",
+                idx
+            )));
+        }
+        server.validate()?;
+    }
+    check_unique_names(&servers)?;
+
+    Ok(servers)
+}
+
+/// Read the servers listed in an `mcpServers` client configuration file.
+///
+/// Entries marked `"disabled": true` are skipped. An entry that is neither a
+/// stdio nor an HTTP server is logged and skipped so one typo does not hide
+/// every other server.
+///
+/// # Errors
+/// Returns `AppError::ValidationError` if the file cannot be read, is not JSON,
+/// or has no `mcpServers` object.
+pub fn load_config_file(path: &Path) -> Result<Vec<McpServerConfig>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        AppError::ValidationError(format!(
+            "Failed to read MCP config '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let json: Value = serde_json::from_str(&content).map_err(|e| {
+        AppError::ValidationError(format!(
+            "Invalid JSON in MCP config '{}': {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let entries = json
+        .get("mcpServers")
+        .and_then(|s| s.as_object())
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "MCP config '{}' has no 'mcpServers' object",
+                path.display()
+            ))
+        })?;
+
+    let mut servers = Vec::with_capacity(entries.len());
+    for (name, entry) in entries {
+        if entry.get("disabled").and_then(|d| d.as_bool()) == Some(true) {
+            tracing::info!(server = %name, "Skipping disabled MCP server");
+            continue;
+        }
+
+        let parsed = McpServerConfig::deserialize(entry)
+            .map_err(|_| {
+                AppError::ValidationError(format!(
+                    "MCP server '{}' needs either a 'command' or a 'url'",
+                    name
+                ))
+            })
+            .map(|server| McpServerConfig {
+                name: name.clone(),
+                ..server
+            })
+            .and_then(|server| server.validate().map(|_| server));
+
+        match parsed {
+            Ok(server) => servers.push(server),
+            Err(e) => tracing::error!(
+                server = %name,
+                path = %path.display(),
+                error = %e,
+                "Skipping invalid MCP server entry"
+            ),
+        }
+    }
+
+    Ok(servers)
+}
+
+/// Reject server lists in which two servers share a name.
+pub fn check_unique_names(servers: &[McpServerConfig]) -> Result<()> {
+    for (idx, server) in servers.iter().enumerate() {
+        if servers[..idx].iter().any(|s| s.name == server.name) {
+            return Err(AppError::ValidationError(format!(
+                "MCP server name '{}' is configured twice",
+                server.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_server_list() {
+        let servers = parse_server_list(
+            r#"[{"name": "github", "command": "npx", "args": ["-y", "server-github"],
+                 "env": {"GITHUB_TOKEN": "x"}},
+                {"name": "linear", "url": "https://mcp.linear.app/mcp"}]"#,
+        )
+        .unwrap();
+        assert_eq!(servers[0].source(), "npx -y server-github");
+        assert!(matches!(
+            &servers[0].transport,
+            McpTransport::Stdio { env, .. } if env["GITHUB_TOKEN"] == "x"
+        ));
+        assert_eq!(servers[1].source(), "https://mcp.linear.app/mcp");
+
+        assert!(parse_server_list(r#"[{"name": "", "command": "x"}]"#).is_err());
+        assert!(parse_server_list(r#"[{"name": "a", "command": ""}]"#).is_err());
+        assert!(parse_server_list(
+            r#"[{"name": "a", "command": "x"}, {"name": "a", "command": "y"}]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_load_config_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"{{
+                "mcpServers": {{
+                    "filesystem": {{ "command": "npx", "args": ["-y", "server-fs", "/tmp"] }},
+                    "linear": {{ "type": "http", "url": "https://mcp.linear.app/mcp",
+                                 "headers": {{ "Authorization": "Bearer t" }} }},
+                    "broken": {{ "args": ["no command"] }},
+                    "old": {{ "command": "legacy", "disabled": true }}
+                }}
+            }}"#
+        )
+        .unwrap();
+
+        let servers = load_config_file(file.path()).unwrap();
+        let names: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["filesystem", "linear"]);
+        assert!(matches!(
+            &servers[1].transport,
+            McpTransport::Http { headers, .. } if headers["Authorization"] == "Bearer t"
+        ));
+    }
+
+    #[test]
+    fn test_config_file_without_servers_is_an_error() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, r#"{{ "servers": {{}} }}"#).unwrap();
+        assert!(load_config_file(file.path()).is_err());
+    }
+}
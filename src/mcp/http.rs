@@ -0,0 +1,355 @@
+//! Streamable HTTP transport for remote MCP servers.
+//!
+//! Every JSON-RPC message is POSTed to the server's URL. The server answers
+//! with either a single JSON message or a `text/event-stream` carrying the
+//! response (possibly preceded by notifications or requests of its own). The
+//! `Mcp-Session-Id` returned by `initialize` is echoed on later requests.
+
+use crate::error::{AppError, Result};
+use crate::mcp::client::{
+    notification_message, reply_to_server_request, request_message, response_result,
+};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+const PROTOCOL_HEADER: &str = "mcp-protocol-version";
+
+/// A session with one MCP server over HTTP.
+pub struct HttpTransport {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+    /// Same headers, no overall timeout: used for the long-lived GET stream
+    stream_client: reqwest::Client,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+    next_id: u64,
+}
+
+impl HttpTransport {
+    /// Prepare a session with the server at `url`; nothing is sent until the first request.
+    ///
+    /// # Errors
+    /// Returns `AppError::ValidationError` if a configured header is not a valid
+    /// HTTP header.
+    pub fn new(
+        name: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut default_headers = HeaderMap::new();
+        default_headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/json, text/event-stream"),
+        );
+        for (key, value) in headers {
+            let header = HeaderName::try_from(key.as_str())
+                .ok()
+                .zip(HeaderValue::try_from(value.as_str()).ok())
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!(
+                        "Invalid header '{}' for MCP server '{}'",
+                        key, name
+                    ))
+                })?;
+            default_headers.insert(header.0, header.1);
+        }
+
+        let build = |builder: reqwest::ClientBuilder| {
+            builder
+                .default_headers(default_headers.clone())
+                .connect_timeout(timeout)
+                .build()
+                .map_err(|e| {
+                    AppError::McpError(format!(
+                        "Failed to create HTTP client for MCP server '{}': {}",
+                        name, e
+                    ))
+                })
+        };
+        let client = build(reqwest::Client::builder().timeout(timeout))?;
+        let stream_client = build(reqwest::Client::builder())?;
+
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            client,
+            stream_client,
+            session_id: None,
+            protocol_version: None,
+            next_id: 1,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Send a request and wait for its response.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let response = self.post(&request_message(id, method, params)).await?;
+        if let Some(session) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            self.session_id = Some(session.to_string());
+        }
+
+        let is_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/event-stream"));
+        let message = if is_stream {
+            self.read_stream_response(response, id).await?
+        } else {
+            let body: Value = response.json().await.map_err(|e| self.http_error(e))?;
+            find_response(body, id).ok_or_else(|| {
+                AppError::McpError(format!(
+                    "MCP server '{}' sent no response to request {}",
+                    self.name, id
+                ))
+            })?
+        };
+
+        let result = response_result(&self.name, &message)?;
+        if method == "initialize" {
+            self.protocol_version = result
+                .get("protocolVersion")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+        }
+        Ok(result)
+    }
+
+    /// Send a notification (the server answers 202 Accepted).
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.post(&notification_message(method, params))
+            .await
+            .map(|_| ())
+    }
+
+    /// Open the GET event stream and forward `*/list_changed` notifications to `changed`.
+    ///
+    /// Bursts of notifications are coalesced: `changed` only needs capacity 1.
+    /// Returns `None` if the server does not offer a stream (HTTP 405).
+    pub async fn listen_for_changes(
+        &self,
+        changed: mpsc::Sender<()>,
+    ) -> Result<Option<JoinHandle<()>>> {
+        let mut request = self
+            .stream_client
+            .get(&self.url)
+            .header(ACCEPT, "text/event-stream");
+        if let Some(session) = &self.session_id {
+            request = request.header(SESSION_HEADER, session);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header(PROTOCOL_HEADER, version);
+        }
+
+        let mut response = request.send().await.map_err(|e| self.http_error(e))?;
+        if response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(AppError::McpError(format!(
+                "MCP server '{}' answered HTTP {} to the event stream request",
+                self.name,
+                response.status()
+            )));
+        }
+
+        let server = self.name.clone();
+        Ok(Some(tokio::spawn(async move {
+            let mut parser = SseParser::default();
+            while let Ok(Some(chunk)) = response.chunk().await {
+                for data in parser.push(&chunk) {
+                    let method = serde_json::from_str::<Value>(&data)
+                        .ok()
+                        .and_then(|m| m.get("method")?.as_str().map(String::from));
+                    if let Some(method) = method.filter(|m| m.ends_with("/list_changed")) {
+                        tracing::debug!(server = %server, method = %method, "MCP list changed");
+                        let _ = changed.try_send(());
+                    }
+                }
+            }
+            tracing::debug!(server = %server, "MCP event stream closed");
+        })))
+    }
+
+    /// End the session, if the server assigned one.
+    pub async fn shutdown(self) {
+        if let Some(session) = &self.session_id {
+            // Servers may not support explicit termination (405); either way we're done
+            let _ = self
+                .client
+                .delete(&self.url)
+                .header(SESSION_HEADER, session)
+                .send()
+                .await;
+        }
+    }
+
+    async fn post(&self, message: &Value) -> Result<reqwest::Response> {
+        let mut request = self.client.post(&self.url).json(message);
+        if let Some(session) = &self.session_id {
+            request = request.header(SESSION_HEADER, session);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header(PROTOCOL_HEADER, version);
+        }
+
+        let response = request.send().await.map_err(|e| self.http_error(e))?;
+        if !response.status().is_success() {
+            return Err(AppError::McpError(format!(
+                "MCP server '{}' answered HTTP {}",
+                self.name,
+                response.status()
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Read SSE events until the response to `id` arrives.
+    ///
+    /// Notifications are ignored and server-to-client requests are answered.
+    async fn read_stream_response(
+        &self,
+        mut response: reqwest::Response,
+        id: u64,
+    ) -> Result<Value> {
+        let mut parser = SseParser::default();
+        loop {
+            let chunk = response.chunk().await.map_err(|e| self.http_error(e))?;
+            let events = match &chunk {
+                Some(bytes) => parser.push(bytes),
+                None => parser.finish().into_iter().collect(),
+            };
+
+            for data in events {
+                let Ok(message) = serde_json::from_str::<Value>(&data) else {
+                    tracing::warn!(server = %self.name, data = %data, "Ignoring non-JSON event");
+                    continue;
+                };
+                if message.get("method").is_some() {
+                    if message.get("id").is_some() {
+                        self.post(&reply_to_server_request(&message)).await?;
+                    }
+                    continue;
+                }
+                if message.get("id").and_then(|v| v.as_u64()) == Some(id) {
+                    return Ok(message);
+                }
+            }
+
+            if chunk.is_none() {
+                return Err(AppError::McpError(format!(
+                    "MCP server '{}' closed the event stream without a response",
+                    self.name
+                )));
+            }
+        }
+    }
+
+    fn http_error(&self, e: reqwest::Error) -> AppError {
+        if e.is_timeout() {
+            AppError::McpError(format!("MCP server '{}' timed out", self.name))
+        } else {
+            AppError::McpError(format!(
+                "HTTP error talking to MCP server '{}': {}",
+                self.name, e
+            ))
+        }
+    }
+}
+
+/// Pick the response to `id` out of a single message or a JSON-RPC batch.
+fn find_response(body: Value, id: u64) -> Option<Value> {
+    match body {
+        Value::Array(messages) => messages
+            .into_iter()
+            .find(|m| m.get("id").and_then(|v| v.as_u64()) == Some(id)),
+        message => Some(message),
+    }
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+///
+/// Only `data:` fields matter for MCP; `event:`, `id:`, `retry:` and comments
+/// are skipped.
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of the body, returning the data of every event it completes.
+    pub(crate) fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&raw);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+
+        events
+    }
+
+    /// Flush an event left unterminated when the stream ends.
+    pub(crate) fn finish(&mut self) -> Option<String> {
+        let mut events = self.push(b"\n\n");
+        events.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_across_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.push(b"event: message\r\nda").is_empty());
+        assert_eq!(
+            parser.push(b"ta: {\"id\":1}\r\n\r\n: keep-alive\n\ndata: a\ndata: b\n\n"),
+            vec!["{\"id\":1}", "a\nb"]
+        );
+        assert!(parser.push(b"data: tail").is_empty());
+        assert_eq!(parser.finish().as_deref(), Some("tail"));
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[test]
+    fn test_find_response_in_batch() {
+        let batch = serde_json::json!([
+            { "jsonrpc": "2.0", "method": "notifications/progress" },
+            { "jsonrpc": "2.0", "id": 7, "result": {} }
+        ]);
+        assert_eq!(find_response(batch, 7).unwrap()["id"], 7);
+    }
+}
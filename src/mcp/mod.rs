@@ -0,0 +1,341 @@
+//! Live tool discovery from MCP servers.
+//!
+//! Configured servers are either started as subprocesses (stdio transport) or
+//! reached by URL (Streamable HTTP transport). After the `initialize`
+//! handshake the client pages through `tools/list` (and `prompts/list` /
+//! `resources/list` when the server advertises them) and atomizes the results
+//! under the server's name, exactly as if they had been exported to a catalog
+//! file.
+
+pub mod client;
+pub mod config;
+pub mod http;
+pub mod refresh;
+pub mod stdio;
+
+pub use client::McpClient;
+pub use config::{
+    check_unique_names, load_config_file, parse_server_list, McpServerConfig, McpTransport,
+};
+pub use refresh::{spawn_watchers, ServerUpdate};
+
+use crate::error::{AppError, Result};
+use crate::ingestion::{
+    atomize_tools_with_options, AtomizeOptions, EncapureTool, LoadedCatalog, ServerFailure,
+    ServerSummary, ToolFormat,
+};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// Start one server, list its tools and atomize them.
+///
+/// The connection is shut down before returning, whether or not listing succeeded.
+pub async fn fetch_server_tools(
+    config: &McpServerConfig,
+    options: &AtomizeOptions,
+    timeout: Duration,
+) -> Result<Vec<EncapureTool>> {
+    let mut client = McpClient::connect(config, timeout)?;
+    let document = match client.initialize().await {
+        Ok(init) => list_server_items(&mut client, &init).await,
+        Err(e) => Err(e),
+    };
+    client.shutdown().await;
+
+    atomize_server_items(&document?, &config.name, options)
+}
+
+/// Atomize a document built by [`list_server_items`].
+pub(crate) fn atomize_server_items(
+    document: &Value,
+    server: &str,
+    options: &AtomizeOptions,
+) -> Result<Vec<EncapureTool>> {
+    // Responses are always MCP list results, whatever TOOLS_FORMAT says about files
+    let options = AtomizeOptions {
+        format: ToolFormat::Auto,
+        ..options.clone()
+    };
+    atomize_tools_with_options(document, server, &options)
+}
+
+/// Query every configured server in turn and merge the results.
+///
+/// A server that cannot be reached, listed or atomized is logged and recorded
+/// in [`LoadedCatalog::failures`]; the remaining servers still load.
+pub async fn load_servers(
+    servers: &[McpServerConfig],
+    options: &AtomizeOptions,
+    timeout: Duration,
+) -> LoadedCatalog {
+    let mut catalog = LoadedCatalog::default();
+
+    for server in servers {
+        let tools = match fetch_server_tools(server, options, timeout).await {
+            Ok(tools) => tools,
+            Err(e) => {
+                tracing::error!(
+                    server = %server.name,
+                    source = %server.source(),
+                    error = %e,
+                    "Failed to load tools from MCP server, skipping it"
+                );
+                catalog.failures.push(ServerFailure {
+                    server: server.name.clone(),
+                    source: server.source(),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        tracing::info!(
+            server = %server.name,
+            count = tools.len(),
+            "Loaded tools from MCP server"
+        );
+
+        catalog.servers.push(ServerSummary {
+            server: server.name.clone(),
+            source: server.source(),
+            tool_count: tools.len(),
+        });
+        catalog.tools.extend(tools);
+    }
+
+    catalog
+}
+
+/// [`load_servers`] for synchronous callers such as `AppState::new`.
+///
+/// Runs on a dedicated thread with its own runtime, so it is safe to call from
+/// inside (or outside) an existing tokio runtime.
+pub fn load_servers_blocking(
+    servers: &[McpServerConfig],
+    options: &AtomizeOptions,
+    timeout: Duration,
+) -> Result<LoadedCatalog> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| AppError::McpError(format!("Failed to start MCP runtime: {}", e)))
+                    .map(|rt| rt.block_on(load_servers(servers, options, timeout)))
+            })
+            .join()
+            .unwrap_or_else(|_| Err(AppError::McpError("MCP loader thread panicked".into())))
+    })
+}
+
+/// Collect every list the server advertises in its `initialize` result into one document.
+pub(crate) async fn list_server_items(client: &mut McpClient, init: &Value) -> Result<Value> {
+    let capabilities = init.get("capabilities").cloned().unwrap_or_default();
+    let mut result = Map::new();
+
+    result.insert("tools".into(), Value::Array(client.list_tools().await?));
+    if capabilities.get("prompts").is_some() {
+        let prompts = client.list_all("prompts/list", "prompts").await?;
+        result.insert("prompts".into(), Value::Array(prompts));
+    }
+    if capabilities.get("resources").is_some() {
+        let resources = client.list_all("resources/list", "resources").await?;
+        let templates = client
+            .list_all("resources/templates/list", "resourceTemplates")
+            .await?;
+        result.insert("resources".into(), Value::Array(resources));
+        result.insert("resourceTemplates".into(), Value::Array(templates));
+    }
+
+    Ok(json!({ "result": result }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestion::ItemKind;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+    use std::io::Write;
+
+    /// Scripted stdio MCP server: two pages of tools, one prompt, plus some
+    /// noise (a log line, a notification and a ping) the client must tolerate.
+    const FAKE_SERVER: &str = r#"
+import json, sys
+
+def send(msg):
+    sys.stdout.write(json.dumps(msg) + "\n")
+    sys.stdout.flush()
+
+pages = {
+    None: ([{"name": "create_issue", "description": "Create an issue"}], "page-2"),
+    "page-2": ([{"name": "list_repos", "description": "List repositories",
+                 "annotations": {"readOnlyHint": True}}], None),
+}
+
+for line in sys.stdin:
+    msg = json.loads(line)
+    method, mid = msg.get("method"), msg.get("id")
+    if method == "initialize":
+        print("starting up")
+        send({"jsonrpc": "2.0", "id": mid, "result": {
+            "protocolVersion": msg["params"]["protocolVersion"],
+            "capabilities": {"tools": {}, "prompts": {}},
+            "serverInfo": {"name": "fake", "version": "0.1"}}})
+    elif method == "notifications/initialized":
+        send({"jsonrpc": "2.0", "method": "notifications/message", "params": {"data": "hi"}})
+        send({"jsonrpc": "2.0", "id": "srv-1", "method": "ping"})
+    elif method == "tools/list":
+        tools, cursor = pages[msg["params"].get("cursor")]
+        result = {"tools": tools}
+        if cursor:
+            result["nextCursor"] = cursor
+        send({"jsonrpc": "2.0", "id": mid, "result": result})
+    elif method == "prompts/list":
+        send({"jsonrpc": "2.0", "id": mid, "result": {"prompts": [{"name": "triage"}]}})
+    elif mid is not None and method is None:
+        sys.stderr.write("client replied: %s\n" % line)
+    else:
+        send({"jsonrpc": "2.0", "id": mid, "error": {"code": -32601, "message": "unknown"}})
+"#;
+
+    fn fake_server(name: &str) -> (tempfile::NamedTempFile, McpServerConfig) {
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+        script.write_all(FAKE_SERVER.as_bytes()).unwrap();
+        let config = McpServerConfig {
+            name: name.to_string(),
+            transport: McpTransport::Stdio {
+                command: "python3".to_string(),
+                args: vec![script.path().display().to_string()],
+                env: HashMap::new(),
+            },
+        };
+        (script, config)
+    }
+
+    /// Streamable HTTP stand-in: plain JSON for `initialize`, an event stream
+    /// (with a notification ahead of the response) for `tools/list`.
+    async fn http_server() -> String {
+        async fn handle(headers: HeaderMap, Json(msg): Json<Value>) -> Response {
+            let id = msg["id"].clone();
+            match msg["method"].as_str().unwrap_or_default() {
+                "initialize" => (
+                    [("mcp-session-id", "session-1")],
+                    Json(json!({ "jsonrpc": "2.0", "id": id, "result": {
+                        "protocolVersion": "2025-06-18",
+                        "capabilities": { "tools": {} }
+                    }})),
+                )
+                    .into_response(),
+                "notifications/initialized" => StatusCode::ACCEPTED.into_response(),
+                "tools/list" if headers.get("mcp-session-id").is_some() => {
+                    let progress = json!({ "jsonrpc": "2.0", "method": "notifications/progress" });
+                    let response = json!({ "jsonrpc": "2.0", "id": id, "result": {
+                        "tools": [{ "name": "search_issues", "description": "Search issues" }]
+                    }});
+                    (
+                        [("content-type", "text/event-stream")],
+                        format!(
+                            "data: {}\n\nevent: message\ndata: {}\n\n",
+                            progress, response
+                        ),
+                    )
+                        .into_response()
+                }
+                _ => StatusCode::BAD_REQUEST.into_response(),
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/mcp", post(handle).delete(|| async { StatusCode::OK }));
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/mcp", addr)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_tools_pages_through_cursor() {
+        let (_script, config) = fake_server("github");
+
+        let tools =
+            fetch_server_tools(&config, &AtomizeOptions::default(), Duration::from_secs(10))
+                .await
+                .unwrap();
+
+        let ids: Vec<&str> = tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "github::create_issue",
+                "github::list_repos",
+                "github::prompt:triage"
+            ]
+        );
+        assert!(tools[1].annotations.read_only_hint == Some(true));
+        assert_eq!(tools[2].kind, ItemKind::Prompt);
+    }
+
+    #[tokio::test]
+    async fn test_error_response_is_reported() {
+        let (_script, config) = fake_server("github");
+        let mut client = McpClient::connect(&config, Duration::from_secs(10)).unwrap();
+        client.initialize().await.unwrap();
+
+        let err = client.request("tools/call", json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("-32601"));
+        client.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_config_file_loads_stdio_and_http_servers() {
+        let (script, _) = fake_server("unused");
+        let url = http_server().await;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        let config = json!({
+            "mcpServers": {
+                "github": { "command": "python3", "args": [script.path()] },
+                "tracker": { "url": url },
+                "ghost": { "command": "/nonexistent/mcp-server" }
+            }
+        });
+        file.write_all(config.to_string().as_bytes()).unwrap();
+
+        let servers = load_config_file(file.path()).unwrap();
+        let catalog = load_servers(
+            &servers,
+            &AtomizeOptions::default(),
+            Duration::from_secs(10),
+        )
+        .await;
+
+        let servers: Vec<&str> = catalog.servers.iter().map(|s| s.server.as_str()).collect();
+        assert_eq!(servers, vec!["github", "tracker"]);
+        assert!(catalog
+            .tools
+            .iter()
+            .any(|t| t.id == "tracker::search_issues" && t.server_origin == "tracker"));
+        assert_eq!(catalog.failures.len(), 1);
+        assert_eq!(catalog.failures[0].server, "ghost");
+        assert!(catalog.failures[0]
+            .error
+            .contains("Failed to start MCP server 'ghost'"));
+    }
+
+    #[test]
+    fn test_blocking_load_from_sync_code() {
+        let (_script, config) = fake_server("github");
+
+        let catalog = load_servers_blocking(
+            &[config],
+            &AtomizeOptions::default(),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(catalog.servers[0].tool_count, 3);
+        assert!(catalog.failures.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+//! Periodic refresh of MCP servers reached over HTTP.
+//!
+//! Each watched server keeps one session open. Its tools are listed again
+//! every refresh interval, and immediately whenever the server sends a
+//! `notifications/tools/list_changed` (or prompts/resources equivalent) on its
+//! event stream. Every successful listing is sent as a [`ServerUpdate`]; the
+//! receiver decides what actually changed.
+
+use crate::ingestion::{AtomizeOptions, EncapureTool};
+use crate::mcp::client::McpClient;
+use crate::mcp::config::McpServerConfig;
+use crate::mcp::{atomize_server_items, list_server_items};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// The current tools of one server.
+#[derive(Debug)]
+pub struct ServerUpdate {
+    pub server: String,
+    pub tools: Vec<EncapureTool>,
+}
+
+/// Start a watcher task per server and return the stream of their updates.
+///
+/// Watchers stop once the returned receiver is dropped.
+pub fn spawn_watchers(
+    servers: Vec<McpServerConfig>,
+    options: AtomizeOptions,
+    interval: Duration,
+    timeout: Duration,
+) -> mpsc::Receiver<ServerUpdate> {
+    let (tx, rx) = mpsc::channel(servers.len().max(1));
+    for server in servers {
+        let tx = tx.clone();
+        let options = options.clone();
+        tokio::spawn(async move { watch_server(server, options, interval, timeout, tx).await });
+    }
+    rx
+}
+
+/// Re-list `config`'s tools on every tick and on every list-changed notification.
+///
+/// The first listing happens one `interval` after the call (the startup load
+/// already covered the current tools) unless the server signals a change
+/// sooner. Failed refreshes are logged and the session is re-established on
+/// the next attempt.
+pub async fn watch_server(
+    config: McpServerConfig,
+    options: AtomizeOptions,
+    interval: Duration,
+    timeout: Duration,
+    updates: mpsc::Sender<ServerUpdate>,
+) {
+    let (changed_tx, mut changed_rx) = mpsc::channel::<()>(1);
+    let mut session: Option<Session> = None;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await;
+
+    loop {
+        if session.is_none() {
+            session = Session::open(&config, timeout, changed_tx.clone()).await;
+        }
+
+        tokio::select! {
+            _ = ticker.tick() => {}
+            Some(()) = changed_rx.recv() => {
+                tracing::info!(server = %config.name, "MCP server reported a list change");
+            }
+            _ = updates.closed() => break,
+        }
+
+        let Some(active) = session.as_mut() else {
+            continue;
+        };
+        let listed = list_server_items(&mut active.client, &active.init)
+            .await
+            .and_then(|document| atomize_server_items(&document, &config.name, &options));
+
+        match listed {
+            Ok(tools) => {
+                let update = ServerUpdate {
+                    server: config.name.clone(),
+                    tools,
+                };
+                if updates.send(update).await.is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    server = %config.name,
+                    error = %e,
+                    "MCP refresh failed, reconnecting on the next attempt"
+                );
+                if let Some(stale) = session.take() {
+                    stale.close().await;
+                }
+            }
+        }
+    }
+
+    if let Some(active) = session {
+        active.close().await;
+    }
+}
+
+/// An initialized connection plus its notification listener.
+struct Session {
+    client: McpClient,
+    init: Value,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl Session {
+    async fn open(
+        config: &McpServerConfig,
+        timeout: Duration,
+        changed: mpsc::Sender<()>,
+    ) -> Option<Self> {
+        let mut client = match McpClient::connect(config, timeout) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(server = %config.name, error = %e, "MCP refresh could not connect");
+                return None;
+            }
+        };
+        let init = match client.initialize().await {
+            Ok(init) => init,
+            Err(e) => {
+                tracing::warn!(server = %config.name, error = %e, "MCP refresh could not initialize");
+                client.shutdown().await;
+                return None;
+            }
+        };
+
+        // Without a notification stream we still refresh on the interval
+        let listener = match client.listen_for_changes(changed).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::debug!(server = %config.name, error = %e, "No MCP notification stream");
+                None
+            }
+        };
+
+        Some(Self {
+            client,
+            init,
+            listener,
+        })
+    }
+
+    async fn close(self) {
+        if let Some(listener) = self.listener {
+            listener.abort();
+        }
+        self.client.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::config::McpTransport;
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::post;
+    use axum::{body::Body, Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+    use tokio_stream::wrappers::ReceiverStream;
+
+    type EventSender = mpsc::Sender<std::result::Result<String, Infallible>>;
+
+    /// HTTP stand-in whose tool list and event stream the test controls.
+    #[derive(Clone, Default)]
+    struct StandIn {
+        tools: Arc<Mutex<Vec<Value>>>,
+        events: Arc<Mutex<Option<EventSender>>>,
+    }
+
+    impl StandIn {
+        async fn notify_list_changed(&self) {
+            let sender = self.events.lock().unwrap().clone().expect("stream open");
+            let event = json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" });
+            sender
+                .send(Ok(format!("data: {}\n\n", event)))
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn handle(State(stand_in): State<StandIn>, Json(msg): Json<Value>) -> Response {
+        let id = msg["id"].clone();
+        let result = match msg["method"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": { "tools": { "listChanged": true } }
+            }),
+            "tools/list" => json!({ "tools": *stand_in.tools.lock().unwrap() }),
+            _ => return StatusCode::ACCEPTED.into_response(),
+        };
+        Json(json!({ "jsonrpc": "2.0", "id": id, "result": result })).into_response()
+    }
+
+    async fn stream(State(stand_in): State<StandIn>) -> Response {
+        let (tx, rx) = mpsc::channel(4);
+        *stand_in.events.lock().unwrap() = Some(tx);
+        (
+            [("content-type", "text/event-stream")],
+            Body::from_stream(ReceiverStream::new(rx)),
+        )
+            .into_response()
+    }
+
+    async fn serve(stand_in: StandIn) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new()
+            .route("/mcp", post(handle).get(stream))
+            .with_state(stand_in);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/mcp", addr)
+    }
+
+    fn tool(name: &str) -> Value {
+        json!({ "name": name, "description": format!("The {} tool", name) })
+    }
+
+    #[tokio::test]
+    async fn test_list_changed_triggers_refresh() {
+        let stand_in = StandIn::default();
+        *stand_in.tools.lock().unwrap() = vec![tool("create_issue")];
+        let config = McpServerConfig {
+            name: "tracker".to_string(),
+            transport: McpTransport::Http {
+                url: serve(stand_in.clone()).await,
+                headers: HashMap::new(),
+            },
+        };
+
+        // Interval far beyond the test: only the notification can trigger a refresh
+        let mut updates = spawn_watchers(
+            vec![config],
+            AtomizeOptions::default(),
+            Duration::from_secs(3600),
+            Duration::from_secs(5),
+        );
+
+        // Wait for the watcher to open its event stream
+        while stand_in.events.lock().unwrap().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        stand_in.tools.lock().unwrap().push(tool("close_issue"));
+        stand_in.notify_list_changed().await;
+
+        let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+            .await
+            .expect("refresh after list_changed")
+            .unwrap();
+        assert_eq!(update.server, "tracker");
+        let ids: Vec<&str> = update.tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["tracker::create_issue", "tracker::close_issue"]);
+    }
+
+    #[tokio::test]
+    async fn test_interval_refresh_without_event_stream() {
+        let stand_in = StandIn::default();
+        *stand_in.tools.lock().unwrap() = vec![tool("search")];
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // POST only: GET answers 405, so the watcher must fall back to polling
+        let app = Router::new()
+            .route("/mcp", post(handle))
+            .with_state(stand_in);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = McpServerConfig {
+            name: "search".to_string(),
+            transport: McpTransport::Http {
+                url: format!("http://{}/mcp", addr),
+                headers: HashMap::new(),
+            },
+        };
+        let mut updates = spawn_watchers(
+            vec![config],
+            AtomizeOptions::default(),
+            Duration::from_millis(50),
+            Duration::from_secs(5),
+        );
+
+        for _ in 0..2 {
+            let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+                .await
+                .expect("periodic refresh")
+                .unwrap();
+            assert_eq!(update.tools[0].id, "search::search");
+        }
+    }
+}
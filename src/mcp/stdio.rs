@@ -0,0 +1,170 @@
+//! stdio transport for MCP servers spawned as subprocesses.
+//!
+//! Messages are newline-delimited JSON on the child's stdin/stdout, as defined
+//! by the MCP stdio transport. The child's stderr is forwarded to tracing.
+
+use crate::error::{AppError, Result};
+use crate::mcp::client::{
+    notification_message, reply_to_server_request, request_message, response_result,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// A running MCP server process.
+pub struct StdioTransport {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    next_id: u64,
+    timeout: Duration,
+}
+
+impl StdioTransport {
+    /// Spawn `command` with `args` and `env` as the server called `name`.
+    pub fn spawn(
+        name: &str,
+        command: &str,
+        args: &[String],
+        env: &HashMap<String, String>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                AppError::McpError(format!(
+                    "Failed to start MCP server '{}' ({}): {}",
+                    name, command, e
+                ))
+            })?;
+
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+        if let Some(stderr) = child.stderr.take() {
+            let server = name.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!(server = %server, "{}", line);
+                }
+            });
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            next_id: 1,
+            timeout,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Send a request and wait for its response.
+    pub async fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.send(&request_message(id, method, params)).await?;
+
+        let timeout = self.timeout;
+        tokio::time::timeout(timeout, self.read_response(id))
+            .await
+            .map_err(|_| {
+                AppError::McpError(format!(
+                    "MCP server '{}' did not answer within {:?}",
+                    self.name, timeout
+                ))
+            })?
+    }
+
+    /// Send a notification (no response expected).
+    pub async fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(&notification_message(method, params)).await
+    }
+
+    /// Close stdin and give the server a moment to exit before killing it.
+    pub async fn shutdown(mut self) {
+        drop(self.stdin);
+        if tokio::time::timeout(Duration::from_secs(2), self.child.wait())
+            .await
+            .is_err()
+        {
+            let _ = self.child.kill().await;
+        }
+    }
+
+    async fn send(&mut self, message: &Value) -> Result<()> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| self.io_error(e))?;
+        self.stdin.flush().await.map_err(|e| self.io_error(e))
+    }
+
+    /// Read messages until the response to `id` arrives.
+    ///
+    /// Notifications are ignored and server-to-client requests are answered.
+    async fn read_response(&mut self, id: u64) -> Result<Value> {
+        loop {
+            let line = self
+                .stdout
+                .next_line()
+                .await
+                .map_err(|e| self.io_error(e))?
+                .ok_or_else(|| {
+                    AppError::McpError(format!("MCP server '{}' closed stdout", self.name))
+                })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(_) => {
+                    tracing::warn!(server = %self.name, line = %line, "Ignoring non-JSON output on stdout");
+                    continue;
+                }
+            };
+
+            match (message.get("id"), message.get("method")) {
+                // Server-to-client request
+                (Some(_), Some(_)) => {
+                    self.send(&reply_to_server_request(&message)).await?;
+                }
+                (Some(response_id), None) if response_id.as_u64() == Some(id) => {
+                    return response_result(&self.name, &message);
+                }
+                (Some(response_id), None) => {
+                    tracing::debug!(server = %self.name, id = %response_id, "Ignoring unexpected response");
+                }
+                (None, _) => {
+                    tracing::trace!(server = %self.name, method = ?message.get("method"), "Ignoring notification");
+                }
+            }
+        }
+    }
+
+    fn io_error(&self, e: std::io::Error) -> AppError {
+        AppError::McpError(format!(
+            "I/O error talking to MCP server '{}': {}",
+            self.name, e
+        ))
+    }
+}
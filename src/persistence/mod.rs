@@ -2,17 +2,32 @@
 //!
 //! This module provides efficient storage and retrieval of tool embeddings,
 //! eliminating the need to keep the bi-encoder model in memory at runtime.
+//! Tools managed through the `/tools` API are persisted by [`overlay`]; live
+//! catalog changes are written in the background by [`writer`].
+
+pub mod overlay;
+pub mod writer;
+
+pub use overlay::{OverlayEntry, OverlayTool, ToolOverlay};
+pub use writer::CatalogWriter;
 
 use crate::error::{AppError, Result};
+use crate::index::{EmbeddingMatrix, Precision};
 use crate::ingestion::EncapureTool;
-use ndarray::Array2;
 use sha2::{Digest, Sha256};
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
 /// Cache file format version. Increment when format changes.
-const CACHE_VERSION: u32 = 1;
+const CACHE_VERSION: u32 = 4;
+
+/// Versions 2 and 3 store binary embeddings without their f32 rescoring rows;
+/// other precisions are laid out as in version 4.
+const CACHE_VERSIONS_NO_F32_RESCORE: [u32; 2] = [2, 3];
+
+/// Version 1 files hold f32 embeddings without a precision tag; still readable.
+const CACHE_VERSION_F32: u32 = 1;
 
 /// Magic bytes to identify valid cache files.
 const CACHE_MAGIC: &[u8; 8] = b"ENCAPURE";
@@ -28,20 +43,23 @@ pub struct EmbeddingsCache {
     /// Embedding dimension (768 for BGE-base)
     pub embedding_dim: usize,
     /// Pre-computed embeddings matrix (num_tools × embedding_dim)
-    pub embeddings: Array2<f32>,
+    pub embeddings: EmbeddingMatrix,
 }
 
 impl EmbeddingsCache {
     /// Compute SHA256 hash of tools for cache invalidation.
     ///
-    /// Hash is based on tool names and inference_views to detect any changes.
+    /// Hash is based on qualified tool ids and embedding_views to detect any
+    /// changes, including tools moving between servers in a merged catalog.
+    /// Because the embedding view is the rendered template, template changes
+    /// invalidate the cache automatically.
     pub fn compute_tools_hash(tools: &[EncapureTool]) -> [u8; 32] {
         let mut hasher = Sha256::new();
 
         for tool in tools {
-            hasher.update(tool.name.as_bytes());
+            hasher.update(tool.id.as_bytes());
             hasher.update(b"|");
-            hasher.update(tool.inference_view.as_bytes());
+            hasher.update(tool.embedding_view.as_bytes());
             hasher.update(b"\n");
         }
 
@@ -49,7 +67,7 @@ impl EmbeddingsCache {
     }
 
     /// Create a new cache from computed embeddings.
-    pub fn new(tools: &[EncapureTool], embeddings: Array2<f32>) -> Self {
+    pub fn new(tools: &[EncapureTool], embeddings: EmbeddingMatrix) -> Self {
         Self {
             version: CACHE_VERSION,
             tools_hash: Self::compute_tools_hash(tools),
@@ -67,7 +85,11 @@ impl EmbeddingsCache {
     /// - 32 bytes: tools_hash
     /// - 8 bytes: num_tools (u64 LE)
     /// - 8 bytes: embedding_dim (u64 LE)
-    /// - N bytes: embeddings data (f32 LE, row-major)
+    /// - 1 byte: precision (0 = f32, 1 = f16, 2 = int8, 3 = binary)
+    /// - N bytes: embeddings data, row-major: f32 or f16 (LE) values; or for
+    ///   int8, one f32 scale per row followed by the i8 values; for binary,
+    ///   the sign bits packed into u64 words (LE, rows padded to whole words)
+    ///   followed by the f32 rows used for rescoring
     pub fn save(&self, path: &Path) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
@@ -106,17 +128,17 @@ impl EmbeddingsCache {
                 AppError::ValidationError(format!("Failed to write embedding_dim: {}", e))
             })?;
 
+        writer
+            .write_all(&[self.embeddings.precision().code()])
+            .map_err(|e| {
+                AppError::ValidationError(format!("Failed to write precision: {}", e))
+            })?;
+
         // Write embeddings data
-        let data = self.embeddings.as_slice().ok_or_else(|| {
-            AppError::ValidationError("Embeddings array not contiguous".to_string())
+        self.embeddings.write_payload(&mut writer).map_err(|e| {
+            AppError::ValidationError(format!("Failed to write embedding data: {}", e))
         })?;
 
-        for &val in data {
-            writer.write_all(&val.to_le_bytes()).map_err(|e| {
-                AppError::ValidationError(format!("Failed to write embedding data: {}", e))
-            })?;
-        }
-
         writer.flush().map_err(|e| {
             AppError::ValidationError(format!("Failed to flush cache file: {}", e))
         })?;
@@ -125,7 +147,8 @@ impl EmbeddingsCache {
             path = %path.display(),
             num_tools = self.num_tools,
             embedding_dim = self.embedding_dim,
-            size_bytes = 8 + 4 + 32 + 8 + 8 + (self.num_tools * self.embedding_dim * 4),
+            precision = %self.embeddings.precision(),
+            size_bytes = 8 + 4 + 32 + 8 + 8 + 1 + self.embeddings.payload_bytes(),
             "Embeddings cache saved"
         );
 
@@ -160,7 +183,10 @@ impl EmbeddingsCache {
         })?;
         let version = u32::from_le_bytes(version_bytes);
 
-        if version != CACHE_VERSION {
+        if version != CACHE_VERSION
+            && version != CACHE_VERSION_F32
+            && !CACHE_VERSIONS_NO_F32_RESCORE.contains(&version)
+        {
             tracing::warn!(
                 path = %path.display(),
                 cache_version = version,
@@ -189,26 +215,41 @@ impl EmbeddingsCache {
         })?;
         let embedding_dim = u64::from_le_bytes(embedding_dim_bytes) as usize;
 
-        // Read embeddings data
-        let total_floats = num_tools * embedding_dim;
-        let mut data = vec![0f32; total_floats];
-
-        for val in &mut data {
-            let mut bytes = [0u8; 4];
-            reader.read_exact(&mut bytes).map_err(|e| {
-                AppError::ValidationError(format!("Failed to read embedding data: {}", e))
+        let precision = if version == CACHE_VERSION_F32 {
+            Precision::F32
+        } else {
+            let mut code = [0u8; 1];
+            reader.read_exact(&mut code).map_err(|e| {
+                AppError::ValidationError(format!("Failed to read precision: {}", e))
             })?;
-            *val = f32::from_le_bytes(bytes);
-        }
+            match Precision::from_code(code[0]) {
+                Some(Precision::Binary) if CACHE_VERSIONS_NO_F32_RESCORE.contains(&version) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        "Binary cache predates f32 rescoring rows, ignoring"
+                    );
+                    return Ok(None);
+                }
+                Some(precision) => precision,
+                None => {
+                    tracing::warn!(path = %path.display(), "Unknown cache precision, ignoring");
+                    return Ok(None);
+                }
+            }
+        };
 
-        let embeddings = Array2::from_shape_vec((num_tools, embedding_dim), data).map_err(|e| {
-            AppError::ValidationError(format!("Failed to reshape embeddings: {}", e))
-        })?;
+        // Read embeddings data
+        let embeddings =
+            EmbeddingMatrix::read_payload(&mut reader, precision, num_tools, embedding_dim)
+                .map_err(|e| {
+                    AppError::ValidationError(format!("Failed to read embedding data: {}", e))
+                })?;
 
         tracing::info!(
             path = %path.display(),
             num_tools,
             embedding_dim,
+            precision = %precision,
             "Embeddings cache loaded"
         );
 
@@ -230,15 +271,35 @@ impl EmbeddingsCache {
 
 /// Try to load embeddings from cache, validating against current tools.
 ///
-/// Returns Some(embeddings) if cache is valid, None if cache miss.
+/// Returns Some(embeddings) stored at `precision` if cache is valid, None if
+/// cache miss. A cache stored at f32 is quantized to `precision`; one stored
+/// at another precision cannot be converted and counts as a miss.
 pub fn try_load_embeddings_cache(
     cache_path: &Path,
     tools: &[EncapureTool],
-) -> Result<Option<Array2<f32>>> {
+    precision: Precision,
+) -> Result<Option<EmbeddingMatrix>> {
     match EmbeddingsCache::load(cache_path)? {
         Some(cache) if cache.is_valid_for(tools) => {
-            tracing::info!("Using cached embeddings (cache hit)");
-            Ok(Some(cache.embeddings))
+            let cached = cache.embeddings.precision();
+            if cached == precision {
+                tracing::info!("Using cached embeddings (cache hit)");
+                return Ok(Some(cache.embeddings));
+            }
+            match cache.embeddings.into_f32() {
+                Some(embeddings) => {
+                    tracing::info!(%precision, "Quantizing cached f32 embeddings (cache hit)");
+                    Ok(Some(EmbeddingMatrix::compress(embeddings, precision)))
+                }
+                None => {
+                    tracing::info!(
+                        cached = %cached,
+                        wanted = %precision,
+                        "Cache precision changed, will recompute"
+                    );
+                    Ok(None)
+                }
+            }
         }
         Some(_) => {
             tracing::info!("Cache invalid (tools changed), will recompute");
@@ -255,7 +316,7 @@ pub fn try_load_embeddings_cache(
 pub fn save_embeddings_cache(
     cache_path: &Path,
     tools: &[EncapureTool],
-    embeddings: &Array2<f32>,
+    embeddings: &EmbeddingMatrix,
 ) -> Result<()> {
     let cache = EmbeddingsCache::new(tools, embeddings.clone());
     cache.save(cache_path)
@@ -264,16 +325,17 @@ pub fn save_embeddings_cache(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ndarray::Array2;
     use serde_json::json;
     use tempfile::tempdir;
 
     fn make_test_tool(name: &str, desc: &str) -> EncapureTool {
-        EncapureTool {
-            name: name.to_string(),
-            server_origin: "test".to_string(),
-            inference_view: format!("TOOL: {} | FUNC: {}", name, desc),
-            raw_definition: json!({"name": name}),
-        }
+        EncapureTool::new(
+            name.to_string(),
+            "test".to_string(),
+            format!("TOOL: {} | FUNC: {}", name, desc),
+            json!({"name": name}),
+        )
     }
 
     #[test]
@@ -287,6 +349,38 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_hash_changes_with_qualified_id() {
+        let tools1 = vec![make_test_tool("tool1", "desc1")];
+        let mut tools2 = tools1.clone();
+        tools2[0].server_origin = "other".to_string();
+        tools2[0].id = "other::tool1".to_string();
+
+        assert_ne!(
+            EmbeddingsCache::compute_tools_hash(&tools1),
+            EmbeddingsCache::compute_tools_hash(&tools2)
+        );
+    }
+
+    #[test]
+    fn test_hash_follows_embedding_view_only() {
+        let tools1 = vec![make_test_tool("tool1", "desc1")];
+
+        let mut rerank_changed = tools1.clone();
+        rerank_changed[0].inference_view = "different rerank view".to_string();
+        assert_eq!(
+            EmbeddingsCache::compute_tools_hash(&tools1),
+            EmbeddingsCache::compute_tools_hash(&rerank_changed)
+        );
+
+        let mut embedding_changed = tools1.clone();
+        embedding_changed[0].embedding_view = "different embedding view".to_string();
+        assert_ne!(
+            EmbeddingsCache::compute_tools_hash(&tools1),
+            EmbeddingsCache::compute_tools_hash(&embedding_changed)
+        );
+    }
+
     #[test]
     fn test_save_and_load() {
         let dir = tempdir().unwrap();
@@ -300,7 +394,7 @@ mod tests {
             .unwrap();
 
         // Save
-        let cache = EmbeddingsCache::new(&tools, embeddings.clone());
+        let cache = EmbeddingsCache::new(&tools, embeddings.clone().into());
         cache.save(&cache_path).unwrap();
 
         // Load
@@ -308,8 +402,78 @@ mod tests {
 
         assert_eq!(loaded.num_tools, 2);
         assert_eq!(loaded.embedding_dim, 4);
-        assert_eq!(loaded.embeddings, embeddings);
         assert!(loaded.is_valid_for(&tools));
+        assert_eq!(loaded.embeddings.into_f32().unwrap(), embeddings);
+    }
+
+    #[test]
+    fn test_cache_stores_precision() {
+        let dir = tempdir().unwrap();
+        let cache_path = dir.path().join("test_cache.bin");
+        let tools = vec![make_test_tool("tool1", "desc1")];
+        let embeddings = Array2::from_shape_vec((1, 4), vec![0.5, -0.5, 0.5, -0.5]).unwrap();
+
+        // An f32 cache is quantized on load
+        save_embeddings_cache(&cache_path, &tools, &embeddings.clone().into()).unwrap();
+        let loaded = try_load_embeddings_cache(&cache_path, &tools, Precision::Int8)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.precision(), Precision::Int8);
+
+        // A quantized cache is reused at its own precision only
+        save_embeddings_cache(&cache_path, &tools, &loaded).unwrap();
+        let reloaded = try_load_embeddings_cache(&cache_path, &tools, Precision::Int8)
+            .unwrap()
+            .unwrap();
+        assert_eq!(reloaded, loaded);
+        assert!(try_load_embeddings_cache(&cache_path, &tools, Precision::F32)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_binary_cache_keeps_f32_rows() {
+        let dir = tempdir().unwrap();
+        let cache_path = dir.path().join("test_cache.bin");
+        let tools = vec![make_test_tool("tool1", "desc1")];
+        let embeddings = Array2::from_shape_vec((1, 4), vec![0.1, -0.7, 0.7, -0.1]).unwrap();
+
+        let binary = EmbeddingMatrix::quantize(embeddings.clone(), Precision::Binary);
+        save_embeddings_cache(&cache_path, &tools, &binary).unwrap();
+        let loaded = try_load_embeddings_cache(&cache_path, &tools, Precision::Binary)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.row(0), embeddings.row(0));
+
+        // Binary caches from before the f32 rows were stored are recomputed
+        let mut bytes = fs::read(&cache_path).unwrap();
+        bytes[8..12].copy_from_slice(&3u32.to_le_bytes());
+        fs::write(&cache_path, bytes).unwrap();
+        assert!(try_load_embeddings_cache(&cache_path, &tools, Precision::Binary)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_version_1_cache_loads_as_f32() {
+        let dir = tempdir().unwrap();
+        let cache_path = dir.path().join("test_cache.bin");
+        let tools = vec![make_test_tool("tool1", "desc1")];
+
+        let mut bytes = CACHE_MAGIC.to_vec();
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&EmbeddingsCache::compute_tools_hash(&tools));
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        for value in [0.25f32, 0.75] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        fs::write(&cache_path, bytes).unwrap();
+
+        let loaded = try_load_embeddings_cache(&cache_path, &tools, Precision::F32)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.row(0).to_vec(), vec![0.25, 0.75]);
     }
 
     #[test]
@@ -322,7 +486,7 @@ mod tests {
         let embeddings = Array2::from_shape_vec((1, 4), vec![1.0, 2.0, 3.0, 4.0]).unwrap();
 
         // Save with tools1
-        let cache = EmbeddingsCache::new(&tools1, embeddings);
+        let cache = EmbeddingsCache::new(&tools1, embeddings.into());
         cache.save(&cache_path).unwrap();
 
         // Load and check - should be invalid for tools2
@@ -0,0 +1,286 @@
+//! Tools registered, replaced or deleted through the `/tools` API.
+//!
+//! The overlay is a small JSON file applied on top of every catalog load
+//! (TOOLS_PATH files and MCP servers), so API changes survive restarts and
+//! reloads and the embeddings cache matches the catalog that is rebuilt.
+//! Entries keep the raw tool definition and are atomized again on each load,
+//! so template changes apply to them like to any other tool.
+
+use crate::error::{AppError, Result};
+use crate::ingestion::{atomize_document, AtomizeOptions, EncapureTool, ToolFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// One tool registered through the API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayTool {
+    /// Server name the tool is registered under
+    pub server: String,
+    /// Shape of `definition` (`auto` detects MCP, OpenAI and Anthropic tools)
+    #[serde(default)]
+    pub format: ToolFormat,
+    /// The tool definition as it was submitted
+    pub definition: Value,
+}
+
+impl OverlayTool {
+    /// Atomize the definition into a single searchable tool.
+    ///
+    /// # Errors
+    /// Returns `AppError::ValidationError` if the definition is not exactly one
+    /// valid tool.
+    pub fn atomize(&self, options: &AtomizeOptions) -> Result<EncapureTool> {
+        if self.server.is_empty() {
+            return Err(AppError::ValidationError(
+                "server cannot be empty".to_string(),
+            ));
+        }
+
+        // The definition is a single tool, never a whole document
+        let format = match self.format {
+            ToolFormat::Mcp => ToolFormat::Array,
+            ToolFormat::OpenApi => {
+                return Err(AppError::ValidationError(
+                    "OpenAPI specs cannot be registered as a single tool".to_string(),
+                ))
+            }
+            other => other,
+        };
+        let options = AtomizeOptions {
+            format,
+            ..options.clone()
+        };
+        let definitions = Value::Array(vec![self.definition.clone()]);
+        let mut document = atomize_document(&definitions, &self.server, &options)?;
+
+        if let Some(skipped) = document.skipped.first() {
+            return Err(AppError::ValidationError(format!(
+                "Invalid tool definition: {}",
+                skipped.reason
+            )));
+        }
+        match document.tools.len() {
+            1 => Ok(document.tools.remove(0)),
+            n => Err(AppError::ValidationError(format!(
+                "Expected one tool definition, found {}",
+                n
+            ))),
+        }
+    }
+}
+
+/// A registered tool and the id it atomized to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlayEntry {
+    pub id: String,
+    #[serde(flatten)]
+    pub tool: OverlayTool,
+}
+
+/// API changes to the catalog, applied after every load.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ToolOverlay {
+    /// Registered or replaced tools, in registration order
+    #[serde(default)]
+    pub tools: Vec<OverlayEntry>,
+    /// Ids of tools deleted through the API
+    #[serde(default)]
+    pub deleted: Vec<String>,
+}
+
+impl ToolOverlay {
+    /// Load the overlay at `path`; a missing file is an empty overlay.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path).map_err(|e| {
+            AppError::ValidationError(format!(
+                "Failed to read tools overlay '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            AppError::ValidationError(format!("Invalid tools overlay '{}': {}", path.display(), e))
+        })
+    }
+
+    /// Write the overlay to `path`, replacing the previous file atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                AppError::ResourceError(format!("Failed to create overlay directory: {}", e))
+            })?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            AppError::ResourceError(format!("Failed to serialize tools overlay: {}", e))
+        })?;
+
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|e| {
+                AppError::ResourceError(format!(
+                    "Failed to write tools overlay '{}': {}",
+                    path.display(),
+                    e
+                ))
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty() && self.deleted.is_empty()
+    }
+
+    /// Record `tool` under `id`, replacing an earlier entry and undoing a deletion.
+    pub fn upsert(&mut self, id: &str, tool: OverlayTool) {
+        self.deleted.retain(|d| d != id);
+        match self.tools.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => entry.tool = tool,
+            None => self.tools.push(OverlayEntry {
+                id: id.to_string(),
+                tool,
+            }),
+        }
+    }
+
+    /// Record that `id` was deleted.
+    pub fn delete(&mut self, id: &str) {
+        self.tools.retain(|entry| entry.id != id);
+        if !self.deleted.iter().any(|d| d == id) {
+            self.deleted.push(id.to_string());
+        }
+    }
+
+    /// Apply the overlay to freshly loaded tools.
+    ///
+    /// Deleted ids are dropped, registered tools replace a loaded tool with
+    /// the same id in place or are appended. With `server` set, only entries
+    /// registered under that server are applied (for single-server refreshes).
+    /// Entries that no longer atomize are logged and skipped.
+    pub fn apply(
+        &self,
+        mut tools: Vec<EncapureTool>,
+        options: &AtomizeOptions,
+        server: Option<&str>,
+    ) -> Vec<EncapureTool> {
+        tools.retain(|t| !self.deleted.contains(&t.id));
+
+        for entry in &self.tools {
+            if server.is_some_and(|s| s != entry.tool.server) {
+                continue;
+            }
+            let tool = match entry.tool.atomize(options) {
+                Ok(tool) => tool,
+                Err(e) => {
+                    tracing::warn!(id = %entry.id, error = %e, "Skipping invalid overlay tool");
+                    continue;
+                }
+            };
+            match tools.iter_mut().find(|t| t.id == tool.id) {
+                Some(existing) => *existing = tool,
+                None => tools.push(tool),
+            }
+        }
+
+        tools
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn entry(server: &str, name: &str, desc: &str) -> OverlayTool {
+        OverlayTool {
+            server: server.to_string(),
+            format: ToolFormat::Auto,
+            definition: json!({ "name": name, "description": desc }),
+        }
+    }
+
+    #[test]
+    fn test_atomize_accepts_one_tool_in_any_format() {
+        let options = AtomizeOptions::default();
+        let tool = entry("github", "create_issue", "Create an issue")
+            .atomize(&options)
+            .unwrap();
+        assert_eq!(tool.id, "github::create_issue");
+
+        let openai = OverlayTool {
+            server: "github".to_string(),
+            format: ToolFormat::Auto,
+            definition: json!({
+                "type": "function",
+                "function": { "name": "close_issue", "description": "Close an issue" }
+            }),
+        };
+        assert_eq!(openai.atomize(&options).unwrap().name, "close_issue");
+
+        let nameless = OverlayTool {
+            definition: json!({ "description": "no name" }),
+            ..entry("github", "", "")
+        };
+        assert!(nameless.atomize(&options).is_err());
+    }
+
+    #[test]
+    fn test_apply_replaces_appends_and_deletes() {
+        let options = AtomizeOptions::default();
+        let loaded = vec![
+            entry("github", "create_issue", "Create an issue")
+                .atomize(&options)
+                .unwrap(),
+            entry("github", "list_repos", "List repositories")
+                .atomize(&options)
+                .unwrap(),
+        ];
+
+        let mut overlay = ToolOverlay::default();
+        overlay.upsert(
+            "github::create_issue",
+            entry("github", "create_issue", "Open a new issue"),
+        );
+        overlay.upsert("slack::send", entry("slack", "send", "Send a message"));
+        overlay.delete("github::list_repos");
+
+        let tools = overlay.apply(loaded.clone(), &options, None);
+        let ids: Vec<&str> = tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["github::create_issue", "slack::send"]);
+        assert!(tools[0].inference_view.contains("Open a new issue"));
+
+        // Only github entries apply to a github refresh
+        let tools = overlay.apply(loaded, &options, Some("github"));
+        let ids: Vec<&str> = tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["github::create_issue"]);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("overlay.json");
+        assert!(ToolOverlay::load(&path).unwrap().is_empty());
+
+        let mut overlay = ToolOverlay::default();
+        overlay.upsert("slack::send", entry("slack", "send", "Send a message"));
+        overlay.delete("github::list_repos");
+        overlay.save(&path).unwrap();
+
+        let loaded = ToolOverlay::load(&path).unwrap();
+        assert_eq!(loaded.tools, overlay.tools);
+        assert_eq!(loaded.deleted, vec!["github::list_repos"]);
+
+        // Re-registering a deleted id clears the deletion
+        let mut loaded = loaded;
+        loaded.upsert(
+            "github::list_repos",
+            entry("github", "list_repos", "List repositories"),
+        );
+        assert!(loaded.deleted.is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+//! Background writes of the catalog's embeddings cache and vector index.
+//!
+//! Saving a catalog rewrites the whole embeddings cache and index graph, which
+//! takes far longer than swapping the snapshot in memory. [`CatalogWriter`]
+//! moves that work to one background thread and coalesces it: while a write is
+//! running, newer snapshots replace each other and only the latest is written
+//! next.
+
+use crate::catalog::Catalog;
+use crate::index::save_index_cache;
+use crate::persistence::save_embeddings_cache;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+/// Writes catalog snapshots to an embeddings cache path, latest first.
+#[derive(Debug)]
+pub struct CatalogWriter {
+    cache_path: PathBuf,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    state: Mutex<WriterState>,
+    /// Signalled when the writer thread runs out of snapshots
+    idle: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct WriterState {
+    /// Latest snapshot not yet written
+    pending: Option<Arc<Catalog>>,
+    /// Whether a writer thread is running
+    writing: bool,
+}
+
+impl CatalogWriter {
+    pub fn new(cache_path: PathBuf) -> Self {
+        Self {
+            cache_path,
+            shared: Arc::new(Shared::default()),
+        }
+    }
+
+    /// Queue `catalog` to be written, replacing any snapshot still waiting.
+    ///
+    /// Returns immediately; a writer thread is started if none is running.
+    pub fn schedule(&self, catalog: Arc<Catalog>) {
+        let mut state = self.shared.lock();
+        state.pending = Some(catalog);
+        if state.writing {
+            return;
+        }
+        state.writing = true;
+        drop(state);
+
+        let shared = Arc::clone(&self.shared);
+        let cache_path = self.cache_path.clone();
+        let spawned = std::thread::Builder::new()
+            .name("catalog-writer".to_string())
+            .spawn(move || shared.drain(&cache_path));
+        if let Err(e) = spawned {
+            tracing::warn!(error = %e, "Failed to start catalog writer, saving inline");
+            self.shared.drain(&self.cache_path);
+        }
+    }
+
+    /// Block until every scheduled snapshot has been written.
+    ///
+    /// Called on shutdown so the last catalog change is not lost.
+    pub fn flush(&self) {
+        let state = self.shared.lock();
+        let _idle = self
+            .shared
+            .idle
+            .wait_while(state, |state| state.writing)
+            .unwrap_or_else(|e| e.into_inner());
+    }
+}
+
+impl Shared {
+    /// Write pending snapshots until none is left.
+    fn drain(&self, cache_path: &Path) {
+        loop {
+            let catalog = {
+                let mut state = self.lock();
+                match state.pending.take() {
+                    Some(catalog) => catalog,
+                    None => {
+                        state.writing = false;
+                        self.idle.notify_all();
+                        return;
+                    }
+                }
+            };
+            write_catalog(cache_path, &catalog);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, WriterState> {
+        // The state is two fields assigned together; a panic cannot tear it
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Persist `catalog`'s embeddings and index; failures are logged.
+fn write_catalog(cache_path: &Path, catalog: &Catalog) {
+    if let Err(e) = save_embeddings_cache(cache_path, &catalog.tools, &catalog.embeddings) {
+        tracing::warn!(error = %e, "Failed to save embeddings cache (non-fatal)");
+    }
+    if let Err(e) = save_index_cache(cache_path, &catalog.tools, catalog.index.as_ref()) {
+        tracing::warn!(error = %e, "Failed to save vector index (non-fatal)");
+    }
+    tracing::debug!(
+        version = catalog.version,
+        tools = catalog.tools.len(),
+        "Catalog caches saved"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Precision;
+    use crate::ingestion::EncapureTool;
+    use crate::persistence::try_load_embeddings_cache;
+    use ndarray::Array2;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn catalog(num_tools: usize) -> Arc<Catalog> {
+        let tools: Vec<EncapureTool> = (0..num_tools)
+            .map(|i| {
+                let name = format!("tool_{}", i);
+                EncapureTool::new(
+                    name.clone(),
+                    "test".to_string(),
+                    format!("Tool number {}", i),
+                    json!({ "name": name }),
+                )
+            })
+            .collect();
+        let embeddings = Array2::from_shape_fn((num_tools, 4), |(i, j)| (i + j) as f32);
+        Arc::new(Catalog::new(tools, embeddings.into()))
+    }
+
+    #[test]
+    fn test_flush_writes_the_latest_snapshot() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("embeddings.bin");
+        let writer = CatalogWriter::new(path.clone());
+
+        let snapshots: Vec<Arc<Catalog>> = (1..=5).map(catalog).collect();
+        for snapshot in &snapshots {
+            writer.schedule(Arc::clone(snapshot));
+        }
+        writer.flush();
+
+        let latest = snapshots.last().unwrap();
+        let cached = try_load_embeddings_cache(&path, &latest.tools, Precision::F32)
+            .unwrap()
+            .expect("the latest snapshot is cached");
+        assert_eq!(cached.nrows(), 5);
+        assert!(!writer.shared.lock().writing);
+    }
+}
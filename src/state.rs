@@ -1,13 +1,34 @@
+use crate::cache::{QueryEmbeddingCache, ResponseCache};
+use crate::catalog::{Catalog, CatalogChanges};
+use crate::collections::Collections;
 use crate::config::Config;
 use crate::error::{AppError, Result};
+use crate::handlers::search::{SearchCacheKey, SearchResponse};
+use crate::index::EmbeddingMatrix;
 use crate::inference::{BiEncoderModel, RerankerModel, TokenizerWrapper};
-use crate::ingestion::{atomize_tools, EncapureTool};
-use crate::persistence::{save_embeddings_cache, try_load_embeddings_cache};
+use crate::ingestion::{
+    catalog_fingerprint, load_catalog, resolve_duplicates, AtomizeOptions, EncapureTool,
+    MetadataManifest,
+};
+use crate::mcp::{
+    check_unique_names, load_config_file, load_servers_blocking, spawn_watchers, McpServerConfig,
+    McpTransport,
+};
+use crate::persistence::{
+    save_embeddings_cache, try_load_embeddings_cache, CatalogWriter, OverlayTool, ToolOverlay,
+};
 use ndarray::Array2;
-use std::path::Path;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
 use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Longest a catalog write waits for a free bi-encoder session; searches keep
+/// priority, but a busy pool no longer fails the write outright.
+const WRITE_SESSION_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Application state shared across all request handlers.
 /// Uses Arc for zero-copy sharing - Session and Tokenizer are thread-safe.
@@ -18,14 +39,27 @@ pub struct AppState {
     /// Flag indicating the service is ready (model loaded and warmed up)
     pub ready: AtomicBool,
     pub config: Arc<Config>,
-    /// Pre-loaded tools for semantic routing (empty if no TOOLS_PATH configured)
-    pub tools: Arc<Vec<EncapureTool>>,
+    /// Current tools and embeddings for semantic routing (empty if no TOOLS_PATH
+    /// or MCP servers are configured). Swapped as a whole when the catalog changes.
+    catalog: RwLock<Arc<Catalog>>,
+    /// Tools overlay from the `/tools` API. Its lock also serializes catalog
+    /// updates so concurrent writers never drop each other's changes.
+    overlay: Mutex<ToolOverlay>,
+    /// Saves swapped-in catalogs to the embeddings cache off the update lock
+    catalog_writer: CatalogWriter,
+    /// Named collections, each searched independently of the default catalog
+    pub collections: Collections,
+    /// Live MCP servers the catalog was loaded from (stdio and HTTP)
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Atomizer options the catalog was loaded with (reused by ingestion dry-runs)
+    pub atomize_options: AtomizeOptions,
     /// Bi-encoder session pool for concurrent query embedding.
     /// Uses lock-free ArrayQueue for session management (no Mutex serialization).
     pub bi_encoder: Arc<BiEncoderModel>,
-    /// Pre-computed tool embeddings for cosine similarity search
-    /// Shape: (num_tools, embedding_dim) - computed at startup or loaded from cache
-    pub tool_embeddings: Arc<Array2<f32>>,
+    /// Recent query embeddings, so repeated queries skip the bi-encoder
+    pub query_cache: QueryEmbeddingCache,
+    /// Recent search responses, keyed on the catalog snapshot they came from
+    pub search_cache: ResponseCache<SearchCacheKey, SearchResponse>,
 }
 
 impl AppState {
@@ -107,17 +141,34 @@ impl AppState {
         // Load model pool and tokenizer
         let model =
             RerankerModel::load_pool(&config.model_path, physical_cores, config.intra_threads)?;
-        let tokenizer = TokenizerWrapper::load(&config.tokenizer_path, config.max_sequence_length)?;
+        let tokenizer = Arc::new(TokenizerWrapper::load(
+            &config.tokenizer_path,
+            config.max_sequence_length,
+        )?);
 
         // Load tools for semantic routing (optional)
-        let (tools, tool_embeddings, bi_encoder) = if let Some(ref tools_path) = config.tools_path {
-            tracing::info!(path = %tools_path.display(), "Loading tools for semantic routing");
-            let loaded = load_tools_from_file(tools_path)?;
-            tracing::info!(count = loaded.len(), "Tools loaded successfully");
+        let mut atomize_options = config.atomize_options(tokenizer.clone());
+        if let Some(path) = &config.tools_metadata_path {
+            atomize_options.metadata = Some(Arc::new(MetadataManifest::load(path)?));
+        }
+        let mut mcp_servers = config.mcp_servers.clone();
+        if let Some(path) = &config.mcp_config_path {
+            mcp_servers.extend(load_config_file(path)?);
+            check_unique_names(&mcp_servers)?;
+        }
+        let overlay = ToolOverlay::load(&config.tools_overlay_path)?;
+
+        let (tools, tool_embeddings, bi_encoder) = if !config.tools_paths.is_empty()
+            || !mcp_servers.is_empty()
+            || !overlay.is_empty()
+        {
+            let loaded = load_tools(&config, &mcp_servers, &overlay, &atomize_options, &[])?;
 
             // Try to load embeddings from cache first
             let cache_path = &config.embeddings_cache_path;
-            if let Some(cached_embeddings) = try_load_embeddings_cache(cache_path, &loaded)? {
+            if let Some(cached_embeddings) =
+                try_load_embeddings_cache(cache_path, &loaded, config.vector_index.precision)?
+            {
                 // Cache hit! Load bi-encoder pool for concurrent query embedding
                 tracing::info!(
                     num_tools = loaded.len(),
@@ -145,9 +196,12 @@ impl AppState {
                 )?;
 
                 tracing::info!("Computing tool embeddings (cache miss)...");
-                let inference_views: Vec<String> =
-                    loaded.iter().map(|t| t.inference_view.clone()).collect();
-                let embeddings = bi_encoder.encode_batch(&inference_views)?;
+                let embedding_views: Vec<String> =
+                    loaded.iter().map(|t| t.embedding_view.clone()).collect();
+                let embeddings = EmbeddingMatrix::compress(
+                    bi_encoder.encode_batch(&embedding_views)?,
+                    config.vector_index.precision,
+                );
 
                 tracing::info!(
                     num_tools = loaded.len(),
@@ -175,7 +229,7 @@ impl AppState {
                 (loaded, embeddings, bi_encoder_pool)
             }
         } else {
-            tracing::info!("No TOOLS_PATH configured, semantic routing disabled");
+            tracing::info!("No TOOLS_PATH or MCP servers configured, semantic routing disabled");
             // Load bi-encoder pool (may be used for future tool additions)
             let bi_encoder = BiEncoderModel::load_pool(
                 &config.bi_encoder_model_path,
@@ -184,18 +238,58 @@ impl AppState {
                 physical_cores,
                 config.intra_threads,
             )?;
-            (Vec::new(), Array2::zeros((0, 768)), bi_encoder)
+            // Sized for the model, so tools added through the API fit the matrix
+            let empty = Array2::zeros((0, bi_encoder.dim()));
+            let embeddings = EmbeddingMatrix::quantize(empty, config.vector_index.precision);
+            (Vec::new(), embeddings, bi_encoder)
         };
 
+        // The index is loaded from next to the embeddings cache when still valid
+        let index = config.vector_index.load_or_build(
+            &tools,
+            &tool_embeddings,
+            Some(&config.embeddings_cache_path),
+        );
+        let catalog = Catalog::with_index(tools, tool_embeddings, index);
+
+        // Collections share the bi-encoder pool with the default catalog
+        let collections = Collections::load(
+            &config.collections_path,
+            &atomize_options,
+            &config.vector_index,
+            |views| bi_encoder.encode_batch(views),
+        );
+
+        // Warm the query cache from the last shutdown (only if the model is unchanged)
+        let query_cache =
+            QueryEmbeddingCache::new(config.query_cache_size, bi_encoder.fingerprint());
+        if let Some(path) = &config.query_cache_path {
+            if let Err(e) = query_cache.load(path) {
+                tracing::warn!(error = %e, path = %path.display(), "Failed to load query cache");
+            }
+        }
+
+        let search_cache = ResponseCache::new(
+            config.search_cache_size,
+            Duration::from_secs(config.search_cache_ttl_secs),
+        );
+        let catalog_writer = CatalogWriter::new(config.embeddings_cache_path.clone());
+
         let state = Self {
             model: Arc::new(model),
-            tokenizer: Arc::new(tokenizer),
+            tokenizer,
             semaphore: Arc::new(Semaphore::new(permits)),
             ready: AtomicBool::new(false),
             config: Arc::new(config),
-            tools: Arc::new(tools),
+            catalog: RwLock::new(Arc::new(catalog)),
+            overlay: Mutex::new(overlay),
+            catalog_writer,
+            collections,
+            mcp_servers,
+            atomize_options,
             bi_encoder: Arc::new(bi_encoder),
-            tool_embeddings: Arc::new(tool_embeddings),
+            query_cache,
+            search_cache,
         };
 
         // Warmup the model with a dummy inference
@@ -207,6 +301,28 @@ impl AppState {
         Ok(state)
     }
 
+    /// Save the query embedding cache to QUERY_CACHE_PATH, if configured.
+    ///
+    /// Called on shutdown; failures are logged and otherwise ignored.
+    pub fn save_query_cache(&self) {
+        let Some(path) = &self.config.query_cache_path else {
+            return;
+        };
+        if !self.query_cache.is_enabled() {
+            return;
+        }
+        if let Err(e) = self.query_cache.save(path) {
+            tracing::warn!(error = %e, path = %path.display(), "Failed to save query cache");
+        }
+    }
+
+    /// Wait until the latest catalog change is saved to the embeddings cache.
+    ///
+    /// Called on shutdown; catalog swaps save in the background.
+    pub fn flush_catalog(&self) {
+        self.catalog_writer.flush();
+    }
+
     /// Run a warmup inference to trigger lazy initialization in ONNX Runtime.
     /// This ensures the first real request doesn't suffer cold-start latency.
     fn warmup(&self) -> Result<()> {
@@ -226,36 +342,435 @@ impl AppState {
         Ok(())
     }
 
+    /// The current catalog snapshot.
+    ///
+    /// Hold on to the returned `Arc` for the duration of a request: tool indices
+    /// are only meaningful within one snapshot.
+    pub fn catalog(&self) -> Arc<Catalog> {
+        Arc::clone(&self.catalog.read().expect("catalog lock poisoned"))
+    }
+
+    /// Replace `server`'s tools and swap the result in as the current catalog.
+    ///
+    /// Only new or changed embedding views are encoded. Blocks while encoding,
+    /// so call it from a blocking context.
+    pub fn apply_server_tools(
+        &self,
+        server: &str,
+        tools: Vec<EncapureTool>,
+    ) -> Result<CatalogChanges> {
+        let overlay = self.lock_updates()?;
+        let tools = overlay.apply(tools, &self.atomize_options, Some(server));
+
+        let current = self.catalog();
+        let Some((next, changes)) =
+            current.with_server_tools(server, tools, self.config.duplicate_policy, |views| {
+                self.encode_tool_views(views)
+            })?
+        else {
+            return Ok(CatalogChanges::default());
+        };
+
+        self.swap_catalog(next);
+        Ok(changes)
+    }
+
+    /// Reload every tool source (TOOLS_PATH and MCP servers) and swap in the result.
+    ///
+    /// Searches already running keep the snapshot they started with. Only new
+    /// or changed embedding views are encoded, and the current catalog is kept
+    /// if loading fails. Blocks while loading and encoding, so call it from a
+    /// blocking context.
+    pub fn reload_catalog(&self) -> Result<CatalogChanges> {
+        let overlay = self.lock_updates()?;
+        let current = self.catalog();
+        let tools = if self.config.tools_paths.is_empty()
+            && self.mcp_servers.is_empty()
+            && overlay.is_empty()
+        {
+            Vec::new()
+        } else {
+            load_tools(
+                &self.config,
+                &self.mcp_servers,
+                &overlay,
+                &self.atomize_options,
+                &current.tools,
+            )?
+        };
+
+        let Some((next, changes)) =
+            current.with_tools(tools, |views| self.encode_tool_views(views))?
+        else {
+            return Ok(CatalogChanges::default());
+        };
+
+        self.swap_catalog(next);
+        Ok(changes)
+    }
+
+    /// Register a tool through the API, failing if its id is already in the catalog.
+    ///
+    /// Only the new tool is embedded. The change is written to the tools
+    /// overlay before the catalog is swapped, so it survives restarts and
+    /// reloads. Blocks while encoding, so call it from a blocking context.
+    pub fn create_tool(&self, entry: OverlayTool) -> Result<EncapureTool> {
+        let (tool, _) = self.put_tool(entry, |tool, exists| {
+            if exists {
+                return Err(AppError::Conflict(format!(
+                    "Tool '{}' already exists",
+                    tool.id
+                )));
+            }
+            Ok(())
+        })?;
+        Ok(tool)
+    }
+
+    /// Create or replace the tool `id` through the API.
+    ///
+    /// `entry` must atomize to `id`. Returns the tool and whether it was
+    /// created. Blocks while encoding, so call it from a blocking context.
+    pub fn replace_tool(&self, id: &str, entry: OverlayTool) -> Result<(EncapureTool, bool)> {
+        self.put_tool(entry, |tool, _| {
+            if tool.id != id {
+                return Err(AppError::ValidationError(format!(
+                    "Tool definition has id '{}', expected '{}'",
+                    tool.id, id
+                )));
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove the tool `id` from the catalog and record the deletion in the overlay.
+    ///
+    /// Tools from TOOLS_PATH and MCP servers stay deleted across reloads.
+    pub fn delete_tool(&self, id: &str) -> Result<EncapureTool> {
+        let mut overlay = self.lock_updates()?;
+        let current = self.catalog();
+        let tool = current
+            .tools
+            .iter()
+            .find(|t| t.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Tool '{}' not found", id)))?;
+
+        let tools = current
+            .tools
+            .iter()
+            .filter(|t| t.id != id)
+            .cloned()
+            .collect();
+        let mut next_overlay = overlay.clone();
+        next_overlay.delete(id);
+        self.commit_tools(&next_overlay, &current, tools)?;
+        *overlay = next_overlay;
+        Ok(tool)
+    }
+
+    /// Atomize `entry`, let `check` veto it, then insert or replace it in the catalog.
+    ///
+    /// `check` receives the atomized tool and whether its id already exists.
+    fn put_tool(
+        &self,
+        entry: OverlayTool,
+        check: impl FnOnce(&EncapureTool, bool) -> Result<()>,
+    ) -> Result<(EncapureTool, bool)> {
+        let mut overlay = self.lock_updates()?;
+        let tool = entry.atomize(&self.atomize_options)?;
+        let current = self.catalog();
+        let position = current.tools.iter().position(|t| t.id == tool.id);
+        check(&tool, position.is_some())?;
+
+        let mut tools = current.tools.clone();
+        match position {
+            Some(i) => tools[i] = tool.clone(),
+            None => tools.push(tool.clone()),
+        }
+        let mut next_overlay = overlay.clone();
+        next_overlay.upsert(&tool.id, entry);
+        self.commit_tools(&next_overlay, &current, tools)?;
+        *overlay = next_overlay;
+        Ok((tool, position.is_none()))
+    }
+
+    /// Persist `overlay`, then swap in a catalog holding `tools`.
+    ///
+    /// Embedding runs first, so a model failure leaves both the overlay file
+    /// and the catalog untouched.
+    fn commit_tools(
+        &self,
+        overlay: &ToolOverlay,
+        current: &Catalog,
+        tools: Vec<EncapureTool>,
+    ) -> Result<()> {
+        let next = current.with_tools(tools, |views| self.encode_tool_views(views))?;
+        overlay.save(&self.config.tools_overlay_path)?;
+        if let Some((next, _)) = next {
+            self.swap_catalog(next);
+        }
+        Ok(())
+    }
+
+    /// Embed tool views for a catalog or collection write, waiting up to
+    /// [`WRITE_SESSION_TIMEOUT`] for a bi-encoder session. Blocks, so call it
+    /// from a blocking context.
+    pub fn encode_tool_views(&self, views: &[String]) -> Result<Array2<f32>> {
+        self.bi_encoder
+            .encode_batch_waiting(views, WRITE_SESSION_TIMEOUT)
+    }
+
+    /// Take the catalog update lock (which guards the tools overlay).
+    fn lock_updates(&self) -> Result<MutexGuard<'_, ToolOverlay>> {
+        self.overlay
+            .lock()
+            .map_err(|_| AppError::ResourceError("Catalog update lock poisoned".into()))
+    }
+
+    /// Run [`AppState::reload_catalog`] on the blocking pool and log the outcome.
+    ///
+    /// `trigger` names what asked for the reload (signal, file watcher, endpoint).
+    pub async fn reload(self: &Arc<Self>, trigger: &str) -> Result<CatalogChanges> {
+        tracing::info!(trigger, "Reloading tool catalog");
+        let start = std::time::Instant::now();
+        let state = Arc::clone(self);
+        let reloaded = tokio::task::spawn_blocking(move || state.reload_catalog())
+            .await
+            .map_err(|e| AppError::ResourceError(format!("Reload task join error: {}", e)))?;
+
+        match &reloaded {
+            Ok(changes) => tracing::info!(
+                trigger,
+                added = changes.added,
+                removed = changes.removed,
+                updated = changes.updated,
+                reembedded = changes.reembedded,
+                elapsed_ms = start.elapsed().as_millis() as u64,
+                "Tool catalog reloaded"
+            ),
+            Err(e) => tracing::warn!(
+                trigger,
+                error = %e,
+                "Tool catalog reload failed, keeping the current catalog"
+            ),
+        }
+        reloaded
+    }
+
+    /// Reload the catalog whenever a file under TOOLS_PATH is added, removed or rewritten.
+    ///
+    /// Returns `None` when TOOLS_WATCH_INTERVAL is 0 or TOOLS_PATH is unset.
+    /// Must be called from within a tokio runtime.
+    pub fn spawn_tools_watcher(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        let interval = self.config.tools_watch_interval_secs;
+        if interval == 0 || self.config.tools_paths.is_empty() {
+            return None;
+        }
+
+        tracing::info!(interval_secs = interval, "Watching TOOLS_PATH for changes");
+        let state = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            let entries = state.config.tools_paths.clone();
+            let mut last = catalog_fingerprint(&entries);
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker.tick().await;
+
+            loop {
+                ticker.tick().await;
+                let current = catalog_fingerprint(&entries);
+                if current != last {
+                    last = current;
+                    // Failures are logged by reload; the next change retries
+                    let _ = state.reload("file watcher").await;
+                }
+            }
+        }))
+    }
+
+    /// Make `next` the current catalog and queue its embeddings and index to be
+    /// saved in the background (see [`CatalogWriter`]).
+    fn swap_catalog(&self, next: Catalog) {
+        let next = Arc::new(next);
+        self.catalog_writer.schedule(Arc::clone(&next));
+
+        let previous = std::mem::replace(
+            &mut *self.catalog.write().expect("catalog lock poisoned"),
+            next,
+        );
+        // Answers from the old snapshot can no longer be served; free them now
+        self.search_cache.invalidate_catalog(&previous);
+    }
+
+    /// Keep HTTP MCP servers' tools up to date in the background.
+    ///
+    /// Returns `None` when MCP_REFRESH_INTERVAL is 0 or no HTTP servers are
+    /// configured. Must be called from within a tokio runtime.
+    pub fn spawn_catalog_refresh(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        let interval = self.config.mcp_refresh_interval_secs;
+        let servers: Vec<McpServerConfig> = self
+            .mcp_servers
+            .iter()
+            .filter(|s| matches!(s.transport, McpTransport::Http { .. }))
+            .cloned()
+            .collect();
+        if interval == 0 || servers.is_empty() {
+            return None;
+        }
+
+        tracing::info!(
+            servers = servers.len(),
+            interval_secs = interval,
+            "Refreshing HTTP MCP servers in the background"
+        );
+        let mut updates = spawn_watchers(
+            servers,
+            self.atomize_options.clone(),
+            Duration::from_secs(interval),
+            Duration::from_secs(self.config.mcp_timeout_secs),
+        );
+
+        let state = Arc::clone(self);
+        Some(tokio::spawn(async move {
+            while let Some(update) = updates.recv().await {
+                let state = Arc::clone(&state);
+                let server = update.server.clone();
+                let applied = tokio::task::spawn_blocking(move || {
+                    state.apply_server_tools(&update.server, update.tools)
+                })
+                .await;
+
+                match applied {
+                    Ok(Ok(changes)) if changes.is_empty() => {
+                        tracing::debug!(server = %server, "MCP server tools unchanged");
+                    }
+                    Ok(Ok(changes)) => {
+                        tracing::info!(
+                            server = %server,
+                            added = changes.added,
+                            removed = changes.removed,
+                            updated = changes.updated,
+                            reembedded = changes.reembedded,
+                            "Catalog updated from MCP server"
+                        );
+                    }
+                    Ok(Err(e)) => {
+                        tracing::warn!(server = %server, error = %e, "Failed to apply MCP server update");
+                    }
+                    Err(e) => {
+                        tracing::error!(server = %server, error = %e, "MCP update task failed");
+                    }
+                }
+            }
+        }))
+    }
+
     /// Check if the service is ready to handle requests.
     pub fn is_ready(&self) -> bool {
         self.ready.load(Ordering::SeqCst)
     }
 }
 
-/// Load and atomize tools from a JSON file.
-///
-/// # Arguments
-/// * `path` - Path to the JSON file containing MCP tools
+/// Load every configured tool source: TOOLS_PATH files first, then live MCP
+/// servers, deduplicated together, then the `/tools` API overlay.
 ///
-/// # Returns
-/// A vector of EncapureTool ready for semantic routing
-fn load_tools_from_file(path: &Path) -> Result<Vec<EncapureTool>> {
-    let content = std::fs::read_to_string(path).map_err(|e| {
-        AppError::ValidationError(format!(
-            "Failed to read tools file '{}': {}",
-            path.display(),
-            e
-        ))
-    })?;
-
-    let json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| AppError::ValidationError(format!("Invalid JSON in tools file: {}", e)))?;
-
-    // Extract server name from filename (e.g., "comprehensive_mock_tools.json" -> "comprehensive_mock_tools")
-    let server_name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
-
-    atomize_tools(&json, server_name)
+/// MCP servers that fail are logged and keep their tools from `previous` (the
+/// catalog being reloaded, empty at startup); file errors abort loading.
+fn load_tools(
+    config: &Config,
+    mcp_servers: &[McpServerConfig],
+    overlay: &ToolOverlay,
+    atomize_options: &AtomizeOptions,
+    previous: &[EncapureTool],
+) -> Result<Vec<EncapureTool>> {
+    tracing::info!(entries = ?config.tools_paths, "Loading tools for semantic routing");
+    let mut catalog = load_catalog(&config.tools_paths, atomize_options)?;
+
+    // Live MCP servers are merged after the files and deduplicated together
+    if !mcp_servers.is_empty() {
+        let live = load_servers_blocking(
+            mcp_servers,
+            atomize_options,
+            Duration::from_secs(config.mcp_timeout_secs),
+        )?;
+        if !live.failures.is_empty() {
+            tracing::warn!(
+                failed = ?live.failures.iter().map(|f| &f.server).collect::<Vec<_>>(),
+                "Some MCP servers could not be loaded"
+            );
+        }
+        catalog.servers.extend(live.servers);
+        catalog.tools.extend(live.tools);
+        catalog.tools = resolve_duplicates(catalog.tools, config.duplicate_policy)?;
+
+        // A server that is down for one reload should not drop out of the catalog
+        let loaded: HashSet<&str> = catalog.tools.iter().map(|t| t.id.as_str()).collect();
+        let kept: Vec<EncapureTool> = previous
+            .iter()
+            .filter(|t| live.failures.iter().any(|f| f.server == t.server_origin))
+            .filter(|t| !loaded.contains(t.id.as_str()))
+            .cloned()
+            .collect();
+        if !kept.is_empty() {
+            tracing::warn!(
+                count = kept.len(),
+                "Keeping current tools of failed MCP servers"
+            );
+            catalog.tools.extend(kept);
+        }
+    }
+    if !overlay.is_empty() {
+        catalog.tools = overlay.apply(catalog.tools, atomize_options, None);
+    }
+    tracing::info!(
+        count = catalog.tools.len(),
+        servers = catalog.servers.len(),
+        "Tools loaded successfully"
+    );
+    Ok(catalog.tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_reload_keeps_tools_of_failed_servers() {
+        let mut config = Config::from_env().unwrap();
+        config.tools_paths = Vec::new();
+        let servers = [McpServerConfig {
+            name: "ghost".to_string(),
+            transport: McpTransport::Stdio {
+                command: "/nonexistent/mcp-server".to_string(),
+                args: Vec::new(),
+                env: HashMap::new(),
+            },
+        }];
+        let previous: Vec<EncapureTool> = ["ghost", "files"]
+            .into_iter()
+            .map(|server| {
+                let mut tool = EncapureTool::new(
+                    "search".to_string(),
+                    server.to_string(),
+                    format!("Search {}", server),
+                    json!({ "name": "search" }),
+                );
+                tool.id = format!("{}::search", server);
+                tool
+            })
+            .collect();
+
+        let options = AtomizeOptions::default();
+        let overlay = ToolOverlay::default();
+        let tools = load_tools(&config, &servers, &overlay, &options, &previous).unwrap();
+        let ids: Vec<&str> = tools.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["ghost::search"]);
+
+        // At startup there is nothing to keep
+        let tools = load_tools(&config, &servers, &overlay, &options, &[]).unwrap();
+        assert!(tools.is_empty());
+    }
 }
@@ -9,7 +9,14 @@ use axum::{
     Router,
 };
 use encapure::{
-    handlers::{health_handler, ready_handler, rerank_handler},
+    handlers::search::{SearchCacheKey, SearchRequest, SearchResponse},
+    handlers::{
+        collection_search_handler, create_collection_handler, create_tool_handler,
+        delete_collection_handler, delete_tool_handler, get_tool_handler, health_handler,
+        list_collections_handler, ready_handler, reload_handler, rerank_handler, search_handler,
+        update_tool_handler, validate_handler,
+    },
+    index::{FusionConfig, FusionMethod},
     AppState, Config,
 };
 use serde_json::{json, Value};
@@ -18,10 +25,26 @@ use tower::ServiceExt;
 
 /// Helper to create a test router with the rerank endpoint.
 fn create_test_app(state: Arc<AppState>) -> Router {
-    use axum::routing::{get, post};
+    use axum::routing::{delete, get, post};
 
     Router::new()
         .route("/rerank", post(rerank_handler))
+        .route("/search", post(search_handler))
+        .route("/ingest/validate", post(validate_handler))
+        .route("/admin/reload", post(reload_handler))
+        .route(
+            "/collections",
+            get(list_collections_handler).post(create_collection_handler),
+        )
+        .route("/collections/:name", delete(delete_collection_handler))
+        .route("/collections/:name/search", post(collection_search_handler))
+        .route("/tools", post(create_tool_handler))
+        .route(
+            "/tools/*id",
+            get(get_tool_handler)
+                .put(update_tool_handler)
+                .delete(delete_tool_handler),
+        )
         .route("/health", get(health_handler))
         .route("/ready", get(ready_handler))
         .with_state(state)
@@ -40,12 +63,17 @@ async fn json_request(
             .uri(uri)
             .body(Body::empty())
             .unwrap(),
-        "POST" => Request::builder()
-            .method("POST")
+        "POST" | "PUT" => Request::builder()
+            .method(method)
             .uri(uri)
             .header("content-type", "application/json")
             .body(Body::from(body.unwrap_or(json!({})).to_string()))
             .unwrap(),
+        "DELETE" => Request::builder()
+            .method("DELETE")
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap(),
         _ => panic!("Unsupported method"),
     };
 
@@ -222,6 +250,328 @@ async fn test_rerank_semantic_relevance() {
     );
 }
 
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_ingest_validate_reports_skipped_tools() {
+    let config = Config::from_env().expect("Failed to load config");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let num_tools = state.catalog().len();
+    let app = create_test_app(state.clone());
+
+    let body = json!({
+        "server": "github",
+        "document": {
+            "result": {
+                "tools": [
+                    { "name": "create_issue", "description": "Create an issue" },
+                    { "description": "no name" }
+                ]
+            }
+        }
+    });
+
+    let (status, response) = json_request(app, "POST", "/ingest/validate", Some(body)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["tools"][0]["id"], "github::create_issue");
+    assert_eq!(response["skipped"][0]["index"], 1);
+    // Dry-run must not change the loaded catalog
+    assert_eq!(state.catalog().len(), num_tools);
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_reload_with_unchanged_sources_keeps_catalog() {
+    let config = Config::from_env().expect("Failed to load config");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let before = state.catalog();
+    let app = create_test_app(state.clone());
+
+    let (status, response) = json_request(app, "POST", "/admin/reload", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["added"], 0);
+    assert_eq!(response["removed"], 0);
+    assert_eq!(response["total_tools"], before.len());
+    // Nothing changed, so the snapshot is not replaced
+    assert!(Arc::ptr_eq(&before, &state.catalog()));
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_tool_crud_persists_to_overlay() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::from_env().expect("Failed to load config");
+    config.tools_overlay_path = dir.path().join("overlay.json");
+    config.embeddings_cache_path = dir.path().join("embeddings.bin");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let num_tools = state.catalog().len();
+    let app = create_test_app(state.clone());
+
+    let body = json!({
+        "server": "billing",
+        "definition": { "name": "refund_invoice", "description": "Refund a paid invoice" }
+    });
+    let (status, created) = json_request(app.clone(), "POST", "/tools", Some(body.clone())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["id"], "billing::refund_invoice");
+    assert_eq!(state.catalog().len(), num_tools + 1);
+
+    let (status, _) = json_request(app.clone(), "POST", "/tools", Some(body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let update = json!({
+        "server": "billing",
+        "definition": { "name": "refund_invoice", "description": "Refund part of an invoice" }
+    });
+    let (status, _) = json_request(
+        app.clone(),
+        "PUT",
+        "/tools/billing::refund_invoice",
+        Some(update),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, fetched) =
+        json_request(app.clone(), "GET", "/tools/billing::refund_invoice", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(fetched["inference_view"]
+        .as_str()
+        .unwrap()
+        .contains("Refund part of an invoice"));
+    assert!(dir.path().join("overlay.json").exists());
+
+    let (status, _) = json_request(
+        app.clone(),
+        "DELETE",
+        "/tools/billing::refund_invoice",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = json_request(app, "GET", "/tools/billing::refund_invoice", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(state.catalog().len(), num_tools);
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_collection_search_is_isolated() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::from_env().expect("Failed to load config");
+    config.collections_path = dir.path().to_path_buf();
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let app = create_test_app(state);
+
+    let body = json!({
+        "name": "billing-team",
+        "documents": [{
+            "server": "billing",
+            "document": { "tools": [
+                { "name": "refund_invoice", "description": "Refund a paid invoice" },
+                { "name": "create_invoice", "description": "Create a new invoice" }
+            ] }
+        }]
+    });
+    let (status, created) = json_request(app.clone(), "POST", "/collections", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["tools"], 2);
+
+    let (_, listed) = json_request(app.clone(), "GET", "/collections", None).await;
+    assert_eq!(listed["collections"][0]["name"], "billing-team");
+
+    let query = json!({ "query": "give the customer their money back", "top_k": 5 });
+    let (status, response) = json_request(
+        app.clone(),
+        "POST",
+        "/collections/billing-team/search",
+        Some(query.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    // Only the collection's own tools can come back
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["id"], "billing::refund_invoice");
+
+    let (status, _) = json_request(app.clone(), "DELETE", "/collections/billing-team", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) =
+        json_request(app, "POST", "/collections/billing-team/search", Some(query)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+/// IDs of the tools in a search response, best first.
+fn result_ids(response: &Value) -> Vec<&str> {
+    response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_search_fills_top_k_when_best_tools_are_excluded() {
+    let config = Config::from_env().expect("Failed to load config");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    assert!(
+        state.catalog().len() >= 6,
+        "needs a catalog of at least 6 tools"
+    );
+    let app = create_test_app(state);
+
+    let query = json!({ "query": "send a message to a channel", "top_k": 3 });
+    let (status, unfiltered) = json_request(app.clone(), "POST", "/search", Some(query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let best = result_ids(&unfiltered);
+    assert_eq!(best.len(), 3);
+
+    // The filter is applied before ranking, so the next best tools take their place
+    let query = json!({
+        "query": "send a message to a channel",
+        "top_k": 3,
+        "exclude_tools": best
+    });
+    let (status, filtered) = json_request(app, "POST", "/search", Some(query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let results = result_ids(&filtered);
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|id| !best.contains(id)));
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_search_cache_is_invalidated_by_tool_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::from_env().expect("Failed to load config");
+    config.tools_overlay_path = dir.path().join("overlay.json");
+    config.embeddings_cache_path = dir.path().join("embeddings.bin");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let app = create_test_app(state.clone());
+
+    let query = json!({ "query": "refund a paid invoice", "servers": ["billing"] });
+    let (status, before) = json_request(app.clone(), "POST", "/search", Some(query.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(result_ids(&before).is_empty());
+    assert_eq!(state.search_cache.len(), 1);
+
+    let body = json!({
+        "server": "billing",
+        "definition": { "name": "refund_invoice", "description": "Refund a paid invoice" }
+    });
+    let (status, _) = json_request(app.clone(), "POST", "/tools", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    // The answer for the replaced catalog snapshot is gone
+    assert!(state.search_cache.is_empty());
+
+    let (status, after) = json_request(app, "POST", "/search", Some(query)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result_ids(&after), vec!["billing::refund_invoice"]);
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_search_cache_is_invalidated_by_collection_delete() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::from_env().expect("Failed to load config");
+    config.collections_path = dir.path().to_path_buf();
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let app = create_test_app(state.clone());
+
+    let collection = |tool: &str| {
+        json!({
+            "name": "billing-team",
+            "documents": [{
+                "server": "billing",
+                "document": { "tools": [{ "name": tool, "description": "Refund a paid invoice" }] }
+            }]
+        })
+    };
+    let query = json!({ "query": "give the customer their money back" });
+    let uri = "/collections/billing-team/search";
+
+    let (status, _) = json_request(
+        app.clone(),
+        "POST",
+        "/collections",
+        Some(collection("refund")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, response) = json_request(app.clone(), "POST", uri, Some(query.clone())).await;
+    assert_eq!(result_ids(&response), vec!["billing::refund"]);
+    assert_eq!(state.search_cache.len(), 1);
+
+    let (status, _) = json_request(app.clone(), "DELETE", "/collections/billing-team", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert!(state.search_cache.is_empty());
+
+    // A collection created again under the same name never sees the old answer
+    let body = collection("refund_invoice");
+    let (status, _) = json_request(app.clone(), "POST", "/collections", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, response) = json_request(app, "POST", uri, Some(query)).await;
+    assert_eq!(result_ids(&response), vec!["billing::refund_invoice"]);
+}
+
+/// Cache an empty answer for `request`, so serving it from the cache shows.
+fn poison_search_cache(state: &AppState, request: &Value) {
+    let request: SearchRequest = serde_json::from_value(request.clone()).unwrap();
+    let key = request.cache_key(state.config.fusion);
+    let response = SearchResponse { results: vec![] };
+    state.search_cache.insert(&state.catalog(), key, response);
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_search_cache_control_no_cache() {
+    let config = Config::from_env().expect("Failed to load config");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let app = create_test_app(state.clone());
+
+    let query = json!({ "query": "send a message to a channel" });
+    poison_search_cache(&state, &query);
+    let (_, cached) = json_request(app.clone(), "POST", "/search", Some(query.clone())).await;
+    assert!(result_ids(&cached).is_empty());
+
+    // no-cache searches again and replaces the cached answer
+    let mut no_cache = query.clone();
+    no_cache["cache_control"] = json!("no-cache");
+    let (status, fresh) = json_request(app.clone(), "POST", "/search", Some(no_cache)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!result_ids(&fresh).is_empty());
+
+    let (_, cached) = json_request(app, "POST", "/search", Some(query)).await;
+    assert_eq!(result_ids(&cached), result_ids(&fresh));
+}
+
+#[tokio::test]
+#[ignore = "Requires model files - run with --ignored after exporting model"]
+async fn test_search_cache_control_no_store() {
+    let config = Config::from_env().expect("Failed to load config");
+    let state = Arc::new(AppState::new(config).expect("Failed to create AppState"));
+    let app = create_test_app(state.clone());
+
+    let query = json!({ "query": "send a message to a channel" });
+    let no_store = json!({ "query": "send a message to a channel", "cache_control": "no-store" });
+    let (status, fresh) =
+        json_request(app.clone(), "POST", "/search", Some(no_store.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!result_ids(&fresh).is_empty());
+    assert!(state.search_cache.is_empty());
+
+    // no-store searches past a cached answer and leaves it in place
+    poison_search_cache(&state, &query);
+    let (_, fresh) = json_request(app.clone(), "POST", "/search", Some(no_store)).await;
+    assert!(!result_ids(&fresh).is_empty());
+
+    let (_, cached) = json_request(app, "POST", "/search", Some(query)).await;
+    assert!(result_ids(&cached).is_empty());
+}
+
 #[tokio::test]
 #[ignore = "Requires model files - run with --ignored after exporting model"]
 async fn test_ready_endpoint_returns_200_after_warmup() {
@@ -235,6 +585,49 @@ async fn test_ready_endpoint_returns_200_after_warmup() {
     assert_eq!(body["status"], "ready");
 }
 
+// ============================================================================
+// Unit Tests for the Search Response Cache Key
+// ============================================================================
+
+fn cache_key(request: Value) -> SearchCacheKey {
+    let request: SearchRequest = serde_json::from_value(request).unwrap();
+    request.cache_key(FusionConfig::default())
+}
+
+#[test]
+fn test_cache_key_ignores_formatting_and_list_order() {
+    let key = cache_key(json!({
+        "query": "Send a  message",
+        "servers": ["slack", "github"],
+        "exclude_tools": ["slack::delete"]
+    }));
+    let equivalent = cache_key(json!({
+        "query": "  send a message\n",
+        "servers": ["github", "slack", "github"],
+        "exclude_tools": ["slack::delete"],
+        "cache_control": "no-cache"
+    }));
+    assert_eq!(key, equivalent);
+
+    let other_top_k = cache_key(json!({
+        "query": "send a message",
+        "top_k": 5,
+        "servers": ["github", "slack"],
+        "exclude_tools": ["slack::delete"]
+    }));
+    assert_ne!(key, other_top_k);
+
+    let request: SearchRequest = serde_json::from_value(json!({"query": "q"})).unwrap();
+    let weighted = FusionConfig {
+        method: FusionMethod::Weighted,
+        ..FusionConfig::default()
+    };
+    assert_ne!(
+        request.cache_key(FusionConfig::default()),
+        request.cache_key(weighted)
+    );
+}
+
 // ============================================================================
 // Unit Tests for Sigmoid Function
 // ============================================================================